@@ -0,0 +1,169 @@
+use core::fmt;
+
+// Value math shared by the program. Every function is checked: nothing wraps or
+// panics on valid u64 reserves, and failures come back as a MathError.
+//
+// Rounding policy: every division rounds in the pool's favour, up on amounts owed
+// to the pool and down on amounts paid out of it.
+
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+    InvalidFee,
+    EmptyPool,
+    InsufficientBalance,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MathError::Overflow => "overflow",
+            MathError::Underflow => "underflow",
+            MathError::DivisionByZero => "division by zero",
+            MathError::InvalidFee => "fee must be below 100%",
+            MathError::EmptyPool => "pool has no liquidity",
+            MathError::InsufficientBalance => "insufficient balance",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for MathError {}
+
+pub type MathResult<T> = Result<T, MathError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapResult {
+    pub deposit: u64,
+    pub withdraw: u64,
+    pub fee: u64,
+}
+
+// Floor of the square root.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    let bits = 128 - n.leading_zeros();
+    let mut x = 1u128 << bits.div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+// a * b as a 256-bit (high, low) pair.
+fn wide_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (lo_lo & MASK) | (mid << 64);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+// (high, low) / c as (quotient, remainder) by shift-and-subtract; the quotient must fit in a u128.
+fn wide_div(hi: u128, lo: u128, c: u128) -> MathResult<(u128, u128)> {
+    if hi >= c {
+        return Err(MathError::Overflow);
+    }
+
+    let mut rem = hi;
+    let mut quotient = 0u128;
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry == 1 || rem >= c {
+            rem = rem.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Ok((quotient, rem))
+}
+
+// Products that overflow a u128 fall back to 256-bit intermediates, so pools
+// with reserves near the u64 limit keep working instead of aborting.
+fn mul_div_rem(a: u128, b: u128, c: u128) -> MathResult<(u128, u128)> {
+    if c == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    match a.checked_mul(b) {
+        Some(product) => Ok((product / c, product % c)),
+        None => {
+            let (hi, lo) = wide_mul(a, b);
+            wide_div(hi, lo, c)
+        }
+    }
+}
+
+// a * b / c rounded down.
+pub fn mul_div(a: u128, b: u128, c: u128) -> MathResult<u128> {
+    Ok(mul_div_rem(a, b, c)?.0)
+}
+
+// a * b / c rounded up.
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> MathResult<u128> {
+    let (quotient, rem) = mul_div_rem(a, b, c)?;
+    quotient.checked_add((rem != 0) as u128).ok_or(MathError::Overflow)
+}
+
+pub fn to_u64(n: u128) -> MathResult<u64> {
+    u64::try_from(n).map_err(|_| MathError::Overflow)
+}
+
+// Tokens owed for minting `lp` shares, rounded up.
+pub fn deposit_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> MathResult<(u64, u64)> {
+    if supply == 0 {
+        return Err(MathError::EmptyPool);
+    }
+    let x = to_u64(mul_div_ceil(reserve_x as u128, lp as u128, supply as u128)?)?;
+    let y = to_u64(mul_div_ceil(reserve_y as u128, lp as u128, supply as u128)?)?;
+    Ok((x, y))
+}
+
+// Tokens paid out for burning `lp` shares, rounded down.
+pub fn withdraw_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> MathResult<(u64, u64)> {
+    if supply == 0 || lp > supply {
+        return Err(MathError::InsufficientBalance);
+    }
+    let x = to_u64(mul_div(reserve_x as u128, lp as u128, supply as u128)?)?;
+    let y = to_u64(mul_div(reserve_y as u128, lp as u128, supply as u128)?)?;
+    Ok((x, y))
+}
+
+// Swap fee charged on `amount_in`, rounded up.
+pub fn fee_amount(amount_in: u64, fee: u16) -> MathResult<u64> {
+    to_u64(mul_div_ceil(amount_in as u128, fee as u128, BPS_DENOMINATOR)?)
+}
+
+// Output of a constant-product swap with the fee taken from the input side, rounded
+// down so the product of the reserves never shrinks.
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> MathResult<u64> {
+    if fee as u128 >= BPS_DENOMINATOR {
+        return Err(MathError::InvalidFee);
+    }
+
+    let amount_in_after_fee = (amount_in as u128) * (BPS_DENOMINATOR - fee as u128);
+    let denominator = (reserve_in as u128)
+        .checked_mul(BPS_DENOMINATOR)
+        .and_then(|r| r.checked_add(amount_in_after_fee))
+        .ok_or(MathError::Overflow)?;
+
+    to_u64(mul_div(reserve_out as u128, amount_in_after_fee, denominator)?)
+}
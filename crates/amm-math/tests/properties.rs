@@ -0,0 +1,155 @@
+use amm_math::{deposit_amounts, fee_amount, isqrt, mul_div, mul_div_ceil, swap_amount_out, withdraw_amounts, MathError, BPS_DENOMINATOR};
+
+// Deterministic xorshift, so failures reproduce without a seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Mixes magnitudes so tiny, mid-sized and near-u64::MAX values all show up.
+    fn amount(&mut self) -> u64 {
+        let shift = self.next() % 64;
+        (self.next() >> shift).max(1)
+    }
+}
+
+const CASES: usize = 20_000;
+const FEES: [u16; 5] = [0, 1, 30, 100, 9_999];
+
+#[test]
+fn mul_div_matches_exact_division() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..CASES {
+        let (a, b, c) = (rng.amount() as u128, rng.amount() as u128, rng.amount() as u128);
+        let product = a * b;
+        assert_eq!(mul_div(a, b, c).unwrap(), product / c);
+        assert_eq!(mul_div_ceil(a, b, c).unwrap(), product.div_ceil(c));
+    }
+}
+
+#[test]
+fn mul_div_handles_wide_products() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..CASES {
+        let a = ((rng.next() as u128) << 64) | rng.next() as u128;
+        let b = rng.amount() as u128;
+        // a * b / b must give back a whenever the intermediate needs 256 bits
+        assert_eq!(mul_div(a, b, b).unwrap(), a);
+        assert_eq!(mul_div_ceil(a, b, b).unwrap(), a);
+    }
+    assert_eq!(mul_div(1, 1, 0), Err(MathError::DivisionByZero));
+    assert_eq!(mul_div(u128::MAX, 2, 1), Err(MathError::Overflow));
+}
+
+#[test]
+fn isqrt_is_the_floor_root() {
+    let mut rng = Rng(0xdead_beef_cafe_f00d);
+    for _ in 0..CASES {
+        let n = ((rng.next() as u128) << (rng.next() % 64)) | rng.next() as u128;
+        let root = isqrt(n);
+        assert!(root * root <= n);
+        assert!(!matches!((root + 1).checked_mul(root + 1), Some(next) if next <= n));
+    }
+    for n in 0..10_000u128 {
+        let root = isqrt(n);
+        assert!(root * root <= n && (root + 1) * (root + 1) > n);
+    }
+}
+
+#[test]
+fn fees_round_up_by_at_most_one() {
+    let mut rng = Rng(0x0123_4567_89ab_cdef);
+    for _ in 0..CASES {
+        let amount = rng.amount();
+        for fee in FEES {
+            let charged = fee_amount(amount, fee).unwrap() as u128;
+            let exact = amount as u128 * fee as u128;
+            assert!(charged * BPS_DENOMINATOR >= exact);
+            assert!(charged * BPS_DENOMINATOR < exact + BPS_DENOMINATOR);
+        }
+    }
+}
+
+#[test]
+fn swaps_never_shrink_the_product() {
+    let mut rng = Rng(0x5851_f42d_4c95_7f2d);
+    for _ in 0..CASES {
+        let (reserve_in, reserve_out, amount_in) = (rng.amount(), rng.amount(), rng.amount());
+        let fee = FEES[(rng.next() % FEES.len() as u64) as usize];
+        let out = match swap_amount_out(reserve_in, reserve_out, amount_in, fee) {
+            Ok(out) => out,
+            Err(error) => {
+                assert_eq!(error, MathError::Overflow);
+                continue;
+            }
+        };
+
+        assert!(out < reserve_out);
+        let k_before = reserve_in as u128 * reserve_out as u128;
+        let k_after = (reserve_in as u128 + amount_in as u128) * (reserve_out - out) as u128;
+        assert!(k_after >= k_before);
+    }
+}
+
+#[test]
+fn swap_output_is_monotonic() {
+    let mut rng = Rng(0x1405_7b7e_f767_814f);
+    for _ in 0..CASES {
+        let (reserve_in, reserve_out) = (rng.amount() >> 1, rng.amount() >> 1);
+        let amount_in = rng.amount() >> 2;
+        if reserve_in == 0 || reserve_out == 0 {
+            continue;
+        }
+
+        let out = swap_amount_out(reserve_in, reserve_out, amount_in, 30).unwrap();
+        assert!(swap_amount_out(reserve_in, reserve_out, amount_in + 1, 30).unwrap() >= out);
+        assert!(swap_amount_out(reserve_in, reserve_out, amount_in, 100).unwrap() <= out);
+    }
+}
+
+#[test]
+fn small_pools_are_exhaustively_safe() {
+    for reserve_in in 1..=24u64 {
+        for reserve_out in 1..=24u64 {
+            for amount_in in 0..=24u64 {
+                for fee in FEES {
+                    let out = swap_amount_out(reserve_in, reserve_out, amount_in, fee).unwrap();
+                    let k_after = (reserve_in + amount_in) as u128 * (reserve_out - out) as u128;
+                    assert!(k_after >= reserve_in as u128 * reserve_out as u128);
+                }
+            }
+        }
+    }
+    assert_eq!(swap_amount_out(1, 1, 1, 10_000), Err(MathError::InvalidFee));
+}
+
+#[test]
+fn deposit_then_withdraw_returns_no_more_than_paid() {
+    let mut rng = Rng(0x6a09_e667_f3bc_c908);
+    for _ in 0..CASES {
+        let (reserve_x, reserve_y) = (rng.amount() >> 2, rng.amount() >> 2);
+        let supply = (rng.amount() >> 2).max(1);
+        let lp = rng.amount() >> 2;
+
+        let (in_x, in_y) = match deposit_amounts(reserve_x, reserve_y, supply, lp) {
+            Ok(amounts) => amounts,
+            Err(error) => {
+                assert_eq!(error, MathError::Overflow);
+                continue;
+            }
+        };
+        // a vault can't hold more than u64::MAX, so those deposits are out of scope
+        let (Some(new_x), Some(new_y), Some(new_supply)) = (reserve_x.checked_add(in_x), reserve_y.checked_add(in_y), supply.checked_add(lp)) else {
+            continue;
+        };
+        let (out_x, out_y) = withdraw_amounts(new_x, new_y, new_supply, lp).unwrap();
+        assert!(out_x <= in_x && out_y <= in_y);
+    }
+    assert_eq!(deposit_amounts(1, 1, 0, 1), Err(MathError::EmptyPool));
+    assert_eq!(withdraw_amounts(1, 1, 1, 2), Err(MathError::InsufficientBalance));
+}
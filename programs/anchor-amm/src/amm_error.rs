@@ -1,5 +1,5 @@
 use anchor_lang::error_code;
-use constant_product_curve::CurveError;
+use amm_math::MathError;
 
 #[error_code]
 pub enum AmmError {
@@ -8,7 +8,7 @@ pub enum AmmError {
     DefaultError,
     #[msg("Offer expired.")]
     OfferExpired,
-    #[msg("This pool is locked.")]
+    #[msg("This operation is paused on this pool.")]
     PoolLocked,
     #[msg("Slippage exceeded.")]
     SlippageExceeded,
@@ -40,18 +40,213 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Signer is not the pool authority.")]
+    Unauthorized,
+    #[msg("No pending authority nominated.")]
+    NoPendingAuthority,
+    #[msg("Signer is not the pending authority.")]
+    InvalidPendingAuthority,
+    #[msg("Pool still holds liquidity.")]
+    LiquidityRemaining,
+    #[msg("Volatility account is required for dynamic fee pools.")]
+    MissingVolatilityAccount,
+    #[msg("Fee tier is not supported.")]
+    InvalidFeeTier,
+    #[msg("Pool mints must be ordered with mint_x < mint_y.")]
+    InvalidMintOrder,
+    #[msg("Referrer token account does not match the input mint.")]
+    InvalidReferrer,
+    #[msg("Mint has a permanent delegate that could drain the vault.")]
+    PermanentDelegateNotAllowed,
+    #[msg("Mint is non-transferable and cannot be pooled.")]
+    NonTransferableNotAllowed,
+    #[msg("Mint uses confidential transfers, vault balances would be hidden.")]
+    ConfidentialTransferNotAllowed,
+    #[msg("Pool weights must be non-zero and sum to 10000.")]
+    InvalidWeights,
+    #[msg("Instruction is not supported for weighted pools.")]
+    UnsupportedForWeightedPool,
+    #[msg("Invalid liquidity bootstrapping schedule.")]
+    InvalidLbpSchedule,
+    #[msg("TWAP window must be greater than zero.")]
+    InvalidTwapWindow,
+    #[msg("Not enough observations to cover the requested window.")]
+    ObservationTooOld,
+    #[msg("A flash loan or flash swap is already in progress for this pool.")]
+    FlashActive,
+    #[msg("No flash loan is in progress for this pool.")]
+    NoActiveFlashLoan,
+    #[msg("Flash loan must be repaid with its fee in the same transaction.")]
+    FlashLoanNotRepaid,
+    #[msg("Flash loans and flash swaps cannot be started through CPI.")]
+    FlashCpiNotAllowed,
+    #[msg("Flash swap input does not restore the pool invariant.")]
+    FlashSwapNotRepaid,
+    #[msg("No flash swap is in progress for this pool.")]
+    NoActiveFlashSwap,
+    #[msg("Route accounts do not form a valid chain of pools.")]
+    InvalidRoute,
+    #[msg("Split weights must be non-zero and sum to 10000.")]
+    InvalidSplitWeights,
+    #[msg("Order duration or interval must be greater than zero.")]
+    InvalidOrderDuration,
+    #[msg("Order does not belong to this pool.")]
+    InvalidOrder,
+    #[msg("Nothing is due for execution yet.")]
+    NothingToExecute,
+    #[msg("Signer is not on the pool allowlist.")]
+    NotAllowlisted,
+    #[msg("Badge mint must be a non-transferable Token-2022 mint.")]
+    InvalidBadgeMint,
+    #[msg("Signer does not hold the pool's badge token.")]
+    MissingBadge,
+    #[msg("Invalid lock duration.")]
+    InvalidLockDuration,
+    #[msg("Epoch is not the current voting epoch.")]
+    InvalidEpoch,
+    #[msg("Epoch has not ended yet.")]
+    EpochNotEnded,
+    #[msg("Epoch has not been finalized.")]
+    EpochNotFinalized,
+    #[msg("Farm does not pay out the controller's reward mint.")]
+    InvalidGauge,
+    #[msg("Token-2022 LP mints require the Token-2022 program.")]
+    InvalidTokenProgram,
+    #[msg("Initial deposit is too small to cover the locked minimum liquidity.")]
+    InsufficientInitialLiquidity,
+    #[msg("Pool invariant violated.")]
+    InvariantViolation,
+    #[msg("Pool price is already past the limit price.")]
+    PriceLimitReached,
+    #[msg("Swap moves the price by more than the pool allows.")]
+    PriceImpactTooHigh,
+    #[msg("Pool requires its oracle price account.")]
+    MissingPriceFeed,
+    #[msg("Oracle returned an unusable price.")]
+    InvalidOraclePrice,
+    #[msg("Execution price deviates too far from the oracle price.")]
+    OracleDeviationTooHigh,
+    #[msg("Price feed account does not match the pool's oracle.")]
+    InvalidPriceFeed,
+    #[msg("Every configured oracle is stale.")]
+    StaleOracle,
+    #[msg("Operation not supported for PMM pools.")]
+    UnsupportedForPmmPool,
+    #[msg("Pool is not in emergency mode.")]
+    NotInEmergency,
+    #[msg("No parameter change is pending.")]
+    NoPendingChange,
+    #[msg("Timelock has not elapsed yet.")]
+    TimelockNotElapsed,
+    #[msg("Protocol is paused.")]
+    ProtocolPaused,
+    #[msg("Account is not the protocol treasury.")]
+    InvalidTreasury,
+    #[msg("Account is not a config that needs migrating.")]
+    InvalidConfig,
+    #[msg("Withdraw fee is routed to the treasury; its fee vault is required.")]
+    MissingProtocolFeeVault,
+    #[msg("Liquidity cannot be withdrawn this soon after depositing.")]
+    WithdrawCooldown,
+    #[msg("Nothing has unlocked yet.")]
+    NothingToUnlock,
+    #[msg("Pool tracks liquidity in positions, not LP tokens.")]
+    PositionModePool,
+    #[msg("Pool does not use positions.")]
+    NotPositionModePool,
+    #[msg("Signer does not hold the position NFT.")]
+    NotPositionOwner,
+    #[msg("Position still holds liquidity or uncollected fees.")]
+    PositionNotEmpty,
+    #[msg("Buyback interval has not elapsed.")]
+    BuybackTooSoon,
+    #[msg("Stake changed too often since this epoch to claim it.")]
+    EpochClaimExpired,
+    #[msg("Epoch has not ended.")]
+    EpochNotOver,
+    #[msg("Epoch rewards are already being claimed.")]
+    RewardsAlreadyClaimed,
+    #[msg("Merkle proof does not match the distribution root.")]
+    InvalidProof,
+    #[msg("Pool hook program account is missing or does not match.")]
+    MissingHookProgram,
+    #[msg("Hook program is not registered.")]
+    HookNotRegistered,
+    #[msg("Not supported for stable pools.")]
+    UnsupportedForStablePool,
+    #[msg("Rebasing pools cannot be skimmed.")]
+    RebasingPool,
+    #[msg("Signer is not an approved delegate of the source account.")]
+    NotDelegate,
+    #[msg("Amount exceeds the delegated allowance.")]
+    DelegationExceeded,
+    #[msg("Permit signature is missing or does not match.")]
+    InvalidPermit,
+    #[msg("Permit nonce has already been used or is out of order.")]
+    InvalidNonce,
+    #[msg("Another wallet swaps against this pool in the same transaction.")]
+    SandwichDetected,
+    #[msg("Instructions sysvar account is required.")]
+    MissingInstructionsSysvar,
+    #[msg("Pool only trades through batch auctions.")]
+    BatchModePool,
+    #[msg("Batch window has closed.")]
+    BatchClosed,
+    #[msg("Batch window is still open.")]
+    BatchOpen,
+    #[msg("Batch has already been settled.")]
+    BatchSettled,
+    #[msg("Batch has not been settled.")]
+    BatchNotSettled,
+    #[msg("Revealed parameters do not match the commitment.")]
+    CommitmentMismatch,
+    #[msg("Commitment cannot be revealed yet or its reveal window has passed.")]
+    RevealWindowClosed,
+    #[msg("Commitment can only be reclaimed after its reveal window.")]
+    CommitmentActive,
+    #[msg("Deposit would take the pool over its TVL cap.")]
+    TvlCapExceeded,
+    #[msg("Deposit would take the wallet over its deposit cap.")]
+    WalletCapExceeded,
+    #[msg("Wallet deposits account is required by this pool's deposit cap.")]
+    MissingWalletDeposits,
+    #[msg("Pool is reserved for a bonding-curve launch.")]
+    LaunchPending,
+    #[msg("Launch has reached its raise target.")]
+    LaunchClosed,
+    #[msg("Launch has not reached its raise target.")]
+    LaunchNotComplete,
+    #[msg("Pool already holds liquidity.")]
+    PoolNotEmpty,
+    #[msg("Auction has cleared or ended.")]
+    AuctionClosed,
+    #[msg("Auction is still running.")]
+    AuctionOpen,
+    #[msg("Auction has not been settled.")]
+    AuctionNotSettled,
+    #[msg("Admin NFT account is missing or does not hold the pool's admin NFT.")]
+    MissingAdminNft,
+    #[msg("Admin NFT mint must be a fixed, freely held NFT: supply of one, no decimals, no mint or freeze authority.")]
+    InvalidAdminNft,
+    #[msg("Pool runs a swap hook, so it can only be traded through swap.")]
+    HookedPoolRequiresSwap,
+    #[msg("Swaps on a sandwich-guarded pool must be top-level instructions.")]
+    GuardedSwapCpi,
+    #[msg("Spot price is too far from its TWAP to settle the batch.")]
+    TwapDeviationTooHigh,
+    #[msg("Pool authority is held by an admin NFT.")]
+    AdminNftBound,
 }
 
-impl From<CurveError> for AmmError {
-    fn from(error: CurveError) -> AmmError {
+impl From<MathError> for AmmError {
+    fn from(error: MathError) -> AmmError {
          match error {
-            CurveError::InvalidPrecision => AmmError::InvalidPrecision,
-            CurveError::Overflow => AmmError::Overflow,
-            CurveError::Underflow => AmmError::Underflow,
-            CurveError::InvalidFeeAmount => AmmError::InvalidFee,
-            CurveError::InsufficientBalance => AmmError::InsufficientBalance,
-            CurveError::ZeroBalance => AmmError::ZeroBalance,
-            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
+            MathError::Overflow => AmmError::Overflow,
+            MathError::Underflow => AmmError::Underflow,
+            MathError::DivisionByZero => AmmError::ZeroBalance,
+            MathError::InvalidFee => AmmError::InvalidFee,
+            MathError::EmptyPool => AmmError::NoLiquidityInPool,
+            MathError::InsufficientBalance => AmmError::InsufficientBalance,
         }
     }
 
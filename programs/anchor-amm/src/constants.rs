@@ -0,0 +1,44 @@
+// every swap fee, dynamic, launch and spread-adjusted ones included
+pub const MAX_FEE_BPS: u16 = 1_000;
+// the protocol and referral cuts are shares of the swap fee, not of the trade
+pub const MAX_FEE_SHARE_BPS: u16 = 10_000;
+// bound for the price impact breaker and the oracle deviation guard
+pub const MAX_GUARD_BPS: u16 = 10_000;
+pub const FEE_TIERS: [u16; 4] = [1, 5, 30, 100];
+pub const BPS_DENOMINATOR: u128 = 10_000;
+pub const TOTAL_WEIGHT: u16 = 10_000;
+pub const PRICE_SCALE: u128 = 1_000_000_000;
+pub const VOLATILITY_EMA_PERIOD: u64 = 10;
+pub const VOLATILITY_CAP_BPS: u64 = 500;
+pub const OBSERVATION_CARDINALITY: usize = 64;
+pub const FLASH_LOAN_FEE_BPS: u16 = 9;
+pub const MAX_ROUTE_HOPS: usize = 4;
+pub const ROUTE_HOP_ACCOUNTS: usize = 9;
+pub const KEEPER_BOUNTY_BPS: u16 = 10;
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+pub const BASE_BOOST_BPS: u16 = 10_000;
+pub const MAX_LOCK_BOOST_BPS: u16 = 25_000;
+pub const MAX_LOCK_DURATION: i64 = 365 * 24 * 60 * 60;
+pub const EARLY_EXIT_PENALTY_BPS: u16 = 5_000;
+pub const PAUSE_SWAP: u8 = 1 << 0;
+pub const PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const PAUSE_WITHDRAW: u8 = 1 << 2;
+pub const PAUSE_EMERGENCY: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_SWAP | PAUSE_DEPOSIT | PAUSE_WITHDRAW;
+pub const TIMELOCK_DELAY: i64 = 2 * 24 * 60 * 60;
+pub const REGISTRY_PAGE_SIZE: usize = 64;
+pub const CONFIG_VERSION: u8 = 3;
+pub const CONFIG_RESERVED_BYTES: usize = 32;
+pub const MAX_WITHDRAW_FEE_BPS: u16 = 1_000;
+// roughly an hour of slots
+pub const MAX_JIT_WINDOW_SLOTS: u64 = 9_000;
+pub const VOLUME_TIER_COUNT: usize = 3;
+pub const HOLDING_TIER_COUNT: usize = 3;
+pub const MAX_AMP: u64 = 10_000;
+// roughly a minute of slots to reveal a committed swap before it can only be reclaimed
+pub const REVEAL_WINDOW_SLOTS: u64 = 150;
+// the inventory spread is at its widest once the spot price is 10% off its target
+pub const SPREAD_FULL_DEVIATION_BPS: u128 = 1_000;
+// a batch nets out only while the spot price is within 2% of its TWAP over the batch window
+pub const BATCH_MAX_TWAP_DEVIATION_BPS: u128 = 200;
@@ -2,8 +2,74 @@ pub mod init;
 pub mod deposit;
 pub mod withdraw;
 pub mod swap;
+pub mod lock;
+pub mod update_fee;
+pub mod authority;
+pub mod close_pool;
+pub mod collect_fees;
+pub mod dynamic_fee;
+pub mod quote;
+pub mod flash_loan;
+pub mod flash_swap;
+pub mod route_swap;
+pub mod pool_swap;
+pub mod twamm;
+pub mod dca;
+pub mod limit_order;
+pub mod allowlist;
+pub mod badge;
+pub mod farm;
+pub mod gauge;
+pub mod reserves;
+pub mod price_guard;
+pub mod global_config;
+pub mod migrate;
+pub mod lp_lock;
+pub mod position;
+pub mod buyback;
+pub mod fee_share;
+pub mod distribution;
+pub mod hook;
+pub mod permit;
+pub mod batch;
+pub mod commit_reveal;
+pub mod launch;
+pub mod price_auction;
 
 pub use init::*;
 pub use deposit::*;
 pub use withdraw::*;
 pub use swap::*;
+pub use lock::*;
+pub use update_fee::*;
+pub use authority::*;
+pub use close_pool::*;
+pub use collect_fees::*;
+pub use dynamic_fee::*;
+pub use quote::*;
+pub use flash_loan::*;
+pub use flash_swap::*;
+pub use route_swap::*;
+pub use pool_swap::*;
+pub use twamm::*;
+pub use dca::*;
+pub use limit_order::*;
+pub use allowlist::*;
+pub use badge::*;
+pub use farm::*;
+pub use gauge::*;
+pub use reserves::*;
+pub use price_guard::*;
+pub use global_config::*;
+pub use migrate::*;
+pub use lp_lock::*;
+pub use position::*;
+pub use buyback::*;
+pub use fee_share::*;
+pub use distribution::*;
+pub use hook::*;
+pub use permit::*;
+pub use batch::*;
+pub use commit_reveal::*;
+pub use launch::*;
+pub use price_auction::*;
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::amm_error::AmmError;
+use crate::state::{AllowlistEntry, Config};
+
+#[derive(Accounts)]
+pub struct SetAllowlist<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> SetAllowlist<'info> {
+    pub fn set_allowlist(&mut self, deposits: bool, swaps: bool) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        self.config.allowlist_deposits = deposits;
+        self.config.allowlist_swaps = swaps;
+        self.config.allowlist_ends_at = 0;
+        Ok(())
+    }
+
+    // A launch window: only allowlisted wallets may deposit and swap until ends_at,
+    // after which the pool is open to everyone without another admin call.
+    pub fn set_allowlist_phase(&mut self, ends_at: i64) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(ends_at > Clock::get()?.unix_timestamp, AmmError::InvalidAmount);
+
+        self.config.allowlist_deposits = true;
+        self.config.allowlist_swaps = true;
+        self.config.allowlist_ends_at = ends_at;
+        Ok(())
+    }
+
+    // Launch caps; either can be raised or cleared with 0 at any time.
+    pub fn set_deposit_caps(&mut self, max_tvl: u64, max_deposit_per_wallet: u64) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        self.config.max_tvl = max_tvl;
+        self.config.max_deposit_per_wallet = max_deposit_per_wallet;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AddToAllowlist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"allowlist", config.key().as_ref(), user.as_ref()],
+        bump,
+        space = 8 + AllowlistEntry::INIT_SPACE
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddToAllowlist<'info> {
+    pub fn add_to_allowlist(&mut self, user: Pubkey, bumps: &AddToAllowlistBumps) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        self.allowlist_entry.set_inner(AllowlistEntry {
+            config: self.config.key(),
+            user,
+            bump: bumps.allowlist_entry,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = config,
+        seeds = [b"allowlist", config.key().as_ref(), allowlist_entry.user.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+impl<'info> RemoveFromAllowlist<'info> {
+    pub fn remove_from_allowlist(&mut self) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())
+    }
+}
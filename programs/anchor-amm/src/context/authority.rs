@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::amm_error::AmmError;
+use crate::events::AuthorityRenounced;
+use crate::state::Config;
+use crate::utils::{check_admin_nft_extensions, emit_event_cpi};
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub fn accept_authority(&mut self) -> Result<()> {
+        require!(self.config.admin_nft_mint.is_none(), AmmError::AdminNftBound);
+        match self.config.pending_authority {
+            Some(pending) => require_keys_eq!(
+                pending,
+                self.pending_authority.key(),
+                AmmError::InvalidPendingAuthority
+            ),
+            None => return err!(AmmError::NoPendingAuthority),
+        }
+
+        self.config.authority = self.config.pending_authority.take();
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RenounceAuthority<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> RenounceAuthority<'info> {
+    // There is no way back: every admin instruction fails with NoAuthoritySet from here
+    // on, so a pending transfer or parameter change is dropped along with the authority.
+    pub fn renounce_authority(&mut self, bumps: &RenounceAuthorityBumps) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        self.config.authority = None;
+        self.config.admin_nft_mint = None;
+        self.config.pending_authority = None;
+        self.config.pending_change = None;
+        self.config.change_effective_at = 0;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, AuthorityRenounced {
+            config: self.config.key(),
+            authority: self.authority.key(),
+        })
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetAdminNft<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub admin_nft_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+}
+
+impl<'info> SetAdminNft<'info> {
+    // Binding hands the pool to whoever holds the NFT, so selling it transfers
+    // administration. Passing no mint unbinds it and makes the signing holder the
+    // authority, so a previous authority can't resurface.
+    pub fn set_admin_nft(&mut self) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        self.config.admin_nft_mint = match self.admin_nft_mint {
+            Some(ref admin_nft_mint) => {
+                require!(admin_nft_mint.supply == 1 && admin_nft_mint.decimals == 0, AmmError::InvalidAdminNft);
+                require!(admin_nft_mint.mint_authority.is_none() && admin_nft_mint.freeze_authority.is_none(), AmmError::InvalidAdminNft);
+                check_admin_nft_extensions(&admin_nft_mint.to_account_info())?;
+                Some(admin_nft_mint.key())
+            }
+            None => {
+                self.config.authority = Some(self.authority.key());
+                None
+            }
+        };
+        self.config.pending_authority = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::amm_error::AmmError;
+use crate::state::Config;
+use crate::utils::is_non_transferable;
+
+#[derive(Accounts)]
+pub struct SetBadgeMint<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub badge_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+}
+
+impl<'info> SetBadgeMint<'info> {
+    // Passing no badge mint turns gating off.
+    pub fn set_badge_mint(&mut self) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        self.config.badge_mint = match self.badge_mint {
+            Some(ref badge_mint) => {
+                // a transferable badge could simply be lent out to unverified traders
+                require!(is_non_transferable(&badge_mint.to_account_info())?, AmmError::InvalidBadgeMint);
+                Some(badge_mint.key())
+            }
+            None => None,
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{BATCH_MAX_TWAP_DEVIATION_BPS, BPS_DENOMINATOR, PAUSE_SWAP};
+use crate::context::*;
+use crate::events::BatchSettled;
+use crate::executor::SwapRequest;
+use crate::math::{batch_net_amount, mul_div, to_u64};
+use crate::price::Price;
+use crate::state::{AllowlistEntry, Batch, BatchOrder, Config};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+#[instruction(batch_id: u64)]
+pub struct CommitBatchSwap<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        seeds = [b"batch", config.key().as_ref(), batch_id.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Batch::INIT_SPACE
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"batch_order", batch.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = 8 + BatchOrder::INIT_SPACE
+    )]
+    pub order: Box<Account<'info, BatchOrder>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+        associated_token::token_program = token_program_x
+    )]
+    pub batch_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+        associated_token::token_program = token_program_y
+    )]
+    pub batch_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = owner)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitBatchSwap<'info> {
+    // Escrows the input into the batch for the window that is currently open; batch ids
+    // count windows since the epoch, so every committer in a window lands in the same one.
+    pub fn commit_batch_swap(&mut self, batch_id: u64, is_x: bool, amount_in: u64, bumps: &CommitBatchSwapBumps) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAP)?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
+        require!(self.config.batch_window_secs != 0, AmmError::InvalidConfig);
+        require!(amount_in != 0, AmmError::InvalidAmount);
+
+        let window = self.config.batch_window_secs as u64;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= 0 && now as u64 / window == batch_id, AmmError::BatchClosed);
+
+        if self.batch.config == Pubkey::default() {
+            let ends_at = batch_id.checked_add(1).and_then(|next| next.checked_mul(window)).ok_or(AmmError::Overflow)?;
+            self.batch.config = self.config.key();
+            self.batch.batch_id = batch_id;
+            self.batch.ends_at = ends_at as i64;
+            self.batch.bump = bumps.batch;
+        }
+        require!(!self.batch.settled && now < self.batch.ends_at, AmmError::BatchClosed);
+
+        let (from, to, mint, decimals, token_program) = match is_x {
+            true => (self.owner_ata_x.to_account_info(), self.batch_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.owner_ata_y.to_account_info(), self.batch_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+        let before = match is_x {
+            true => self.batch_ata_x.amount,
+            false => self.batch_ata_y.amount,
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+        transfer_tokens(cpi_ctx, amount_in, decimals)?;
+
+        let escrow = match is_x {
+            true => &mut self.batch_ata_x,
+            false => &mut self.batch_ata_y,
+        };
+        escrow.reload()?;
+        let received = escrow.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+
+        match is_x {
+            true => self.batch.total_x_in = self.batch.total_x_in.checked_add(received).ok_or(AmmError::Overflow)?,
+            false => self.batch.total_y_in = self.batch.total_y_in.checked_add(received).ok_or(AmmError::Overflow)?,
+        }
+
+        self.order.set_inner(BatchOrder {
+            owner: self.owner.key(),
+            batch: self.batch.key(),
+            is_x,
+            amount_in: received,
+            bump: bumps.order,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleBatch<'info> {
+    pub caller: Signer<'info>,
+    pub pool: PoolSwap<'info>,
+    #[account(
+        mut,
+        constraint = batch.config == pool.config.key() @ AmmError::InvalidConfig,
+        seeds = [b"batch", batch.config.as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+    #[account(
+        mut,
+        associated_token::mint = pool.mint_x,
+        associated_token::authority = batch,
+        associated_token::token_program = pool.token_program_x
+    )]
+    pub batch_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = pool.mint_y,
+        associated_token::authority = batch,
+        associated_token::token_program = pool.token_program_y
+    )]
+    pub batch_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'info> SettleBatch<'info> {
+    // Permissionless once the window has closed. Opposing orders are matched against
+    // each other and only the net imbalance trades against the pool, sized so that
+    // both sides clear at the price that trade executes at.
+    pub fn settle_batch(&mut self, bumps: &SettleBatchBumps) -> Result<()> {
+        require!(!self.batch.settled, AmmError::BatchSettled);
+        require!(Clock::get()?.unix_timestamp >= self.batch.ends_at, AmmError::BatchOpen);
+
+        self.pool.config.rebase(self.pool.vault_x.amount, self.pool.vault_y.amount)?;
+        let (is_x, net) = batch_net_amount(self.pool.config.reserve_x, self.pool.config.reserve_y, self.batch.total_x_in, self.batch.total_y_in)?;
+
+        if net > 0 {
+            // the crank is permissionless and takes no minimum, so the net only settles
+            // while the spot price sits near its TWAP over the batch window
+            let (reserve_x, reserve_y) = (self.pool.config.reserve_x, self.pool.config.reserve_y);
+            let (twap, _) = self.pool.observations.load()?.consult(&self.pool.oracle, reserve_x, reserve_y, self.pool.config.batch_window_secs)?;
+            let deviation_bps = mul_div(Price::from_reserves(reserve_x, reserve_y).0.abs_diff(twap.0), BPS_DENOMINATOR, twap.0)?;
+            require!(deviation_bps <= BATCH_MAX_TWAP_DEVIATION_BPS, AmmError::TwapDeviationTooHigh);
+
+            let (from, to) = match is_x {
+                true => (self.batch_ata_x.to_account_info(), self.batch_ata_y.to_account_info()),
+                false => (self.batch_ata_y.to_account_info(), self.batch_ata_x.to_account_info()),
+            };
+            let batch_id = self.batch.batch_id.to_le_bytes();
+            let signer_seeds: &[&[&[u8]]; 1] = &[&[
+                b"batch",
+                self.batch.config.as_ref(),
+                &batch_id[..],
+                &[self.batch.bump],
+            ]];
+            let request = SwapRequest {
+                is_x,
+                amount_in: net,
+                min_amount_out: 0,
+                signer: Pubkey::default(),
+                runs_hook: false,
+                batch_settlement: true,
+            };
+            self.pool.swap(request, from, to, self.batch.to_account_info(), signer_seeds)?;
+        }
+
+        // whatever the escrows now hold is owed to the opposite side's orders
+        self.batch_ata_x.reload()?;
+        self.batch_ata_y.reload()?;
+        self.batch.amount_x_out = self.batch_ata_x.amount;
+        self.batch.amount_y_out = self.batch_ata_y.amount;
+        self.batch.settled = true;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, BatchSettled {
+            config: self.batch.config,
+            batch_id: self.batch.batch_id,
+            total_x_in: self.batch.total_x_in,
+            total_y_in: self.batch.total_y_in,
+            amount_x_out: self.batch.amount_x_out,
+            amount_y_out: self.batch.amount_y_out,
+        })
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimBatchOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"batch", config.key().as_ref(), batch.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Box<Account<'info, Batch>>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = batch @ AmmError::InvalidOrder,
+        seeds = [b"batch_order", batch.key().as_ref(), owner.key().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, BatchOrder>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = batch,
+        associated_token::token_program = token_program_x
+    )]
+    pub batch_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = batch,
+        associated_token::token_program = token_program_y
+    )]
+    pub batch_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimBatchOrder<'info> {
+    // Pays the order its pro-rata share of the other side's settled proceeds; rounding
+    // dust stays in the batch escrow.
+    pub fn claim_batch_order(&mut self) -> Result<()> {
+        require!(self.batch.settled, AmmError::BatchNotSettled);
+
+        let is_x = self.order.is_x;
+        let (proceeds, total_in) = match is_x {
+            true => (self.batch.amount_y_out, self.batch.total_x_in),
+            false => (self.batch.amount_x_out, self.batch.total_y_in),
+        };
+        let amount = to_u64(mul_div(self.order.amount_in as u128, proceeds as u128, total_in as u128)?)?;
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to, mint, decimals, token_program) = match is_x {
+            true => (self.batch_ata_y.to_account_info(), self.owner_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+            false => (self.batch_ata_x.to_account_info(), self.owner_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+        };
+        let batch_id = self.batch.batch_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"batch",
+            self.batch.config.as_ref(),
+            &batch_id[..],
+            &[self.batch.bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.batch.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
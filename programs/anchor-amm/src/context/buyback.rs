@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::amm_error::AmmError;
+use crate::constants::BPS_DENOMINATOR;
+use crate::events::BuybackExecuted;
+use crate::executor::{SwapExecutor, SwapRequest};
+use crate::state::{Buyback, Config, GlobalConfig, Observations, Oracle, OracleType};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct ConfigureBuyback<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        constraint = mint.key() == config.mint_x || mint.key() == config.mint_y @ AmmError::InvalidToken
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"buyback", config.key().as_ref()],
+        bump,
+        space = 8 + Buyback::INIT_SPACE
+    )]
+    pub buyback: Box<Account<'info, Buyback>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ConfigureBuyback<'info> {
+    // A max_amount_in of 0 switches buybacks off.
+    pub fn configure_buyback(&mut self, max_amount_in: u64, min_interval: i64, max_price_impact_bps: u16, bumps: &ConfigureBuybackBumps) -> Result<()> {
+        require!(min_interval >= 0, AmmError::InvalidAmount);
+        require!(max_price_impact_bps as u128 <= BPS_DENOMINATOR, AmmError::InvalidAmount);
+
+        self.buyback.config = self.config.key();
+        self.buyback.mint = self.mint.key();
+        self.buyback.max_amount_in = max_amount_in;
+        self.buyback.min_interval = min_interval;
+        self.buyback.max_price_impact_bps = max_price_impact_bps;
+        self.buyback.bump = bumps.buyback;
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteBuyback<'info> {
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"buyback", config.key().as_ref()],
+        bump = buyback.bump
+    )]
+    pub buyback: Box<Account<'info, Buyback>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    /// CHECK: address is checked against the instructions sysvar; required by sandwich-guarded pools
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ExecuteBuyback<'info> {
+    // Permissionless: sells the other side's protocol fees into the pool and burns what
+    // it buys. There is no caller-supplied minimum, so the rate limits and the price
+    // impact cap bound what a sandwich around a run can extract.
+    pub fn execute_buyback(&mut self, remaining_accounts: &[AccountInfo<'info>], bumps: &ExecuteBuybackBumps) -> Result<()> {
+        require!(self.config.oracle_type == OracleType::None, AmmError::MissingPriceFeed);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.buyback.last_executed_at.saturating_add(self.buyback.min_interval), AmmError::BuybackTooSoon);
+
+        let is_x = self.buyback.mint == self.config.mint_y;
+        let amount_in = match is_x {
+            true => self.protocol_fee_vault_x.amount,
+            false => self.protocol_fee_vault_y.amount,
+        }
+        .min(self.buyback.max_amount_in);
+
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out: 0,
+            signer: Pubkey::default(),
+            runs_hook: false,
+            batch_settlement: false,
+        };
+        let mut executor = SwapExecutor::begin(
+            &mut self.config,
+            &self.global_config,
+            &request,
+            self.instructions.as_deref(),
+            (self.vault_x.amount, self.vault_y.amount),
+            &mut self.oracle,
+            &self.observations,
+            None,
+        )?;
+        let fee = executor.pool_fee(&self.config, None)?;
+        let res = executor.quote(&self.config, fee)?;
+
+        let config_key = self.config.key();
+        let treasury_seeds: &[&[&[u8]]; 1] = &[&[b"treasury", config_key.as_ref(), &[self.config.treasury_bump]]];
+        let (fee_vault_in, vault_in) = match is_x {
+            true => (self.protocol_fee_vault_x.to_account_info(), self.vault_x.to_account_info()),
+            false => (self.protocol_fee_vault_y.to_account_info(), self.vault_y.to_account_info()),
+        };
+        let before = match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount,
+        };
+        self.transfer(is_x, fee_vault_in, vault_in, self.treasury.to_account_info(), treasury_seeds, amount_in, remaining_accounts)?;
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        let received = vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+
+        // the input already is protocol revenue, so none of the fee leaves the pool
+        let (_, withdraw) = executor.settle(&mut self.config, &res, amount_in, received, 0)?;
+
+        let (vault_out, fee_vault_out) = match is_x {
+            true => (self.vault_y.to_account_info(), self.protocol_fee_vault_y.to_account_info()),
+            false => (self.vault_x.to_account_info(), self.protocol_fee_vault_x.to_account_info()),
+        };
+        let config_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+        let before = match is_x {
+            true => self.protocol_fee_vault_y.amount,
+            false => self.protocol_fee_vault_x.amount,
+        };
+        self.transfer(!is_x, vault_out, fee_vault_out, self.config.to_account_info(), config_seeds, withdraw, remaining_accounts)?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        executor.finish(&self.config, (self.vault_x.amount, self.vault_y.amount), &res, withdraw, None)?;
+        require!(
+            self.config.price_impact_bps(executor.reserve_x, executor.reserve_y)? <= self.buyback.max_price_impact_bps as u128,
+            AmmError::PriceImpactTooHigh
+        );
+
+        // burn only what this run bought, not fees already sitting in the vault
+        let fee_vault_out = match is_x {
+            true => &mut self.protocol_fee_vault_y,
+            false => &mut self.protocol_fee_vault_x,
+        };
+        fee_vault_out.reload()?;
+        let bought = fee_vault_out.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.burn_bought(!is_x, bought, treasury_seeds)?;
+
+        self.buyback.last_executed_at = now;
+        self.buyback.total_spent = self.buyback.total_spent.checked_add(received).ok_or(AmmError::Overflow)?;
+        self.buyback.total_burned = self.buyback.total_burned.checked_add(bought).ok_or(AmmError::Overflow)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, BuybackExecuted {
+            config: config_key,
+            mint: self.buyback.mint,
+            amount_in: received,
+            amount_burned: bought,
+        })
+    }
+
+    fn transfer(&self, is_x: bool, from: AccountInfo<'info>, to: AccountInfo<'info>, authority: AccountInfo<'info>, signer_seeds: &[&[&[u8]]], amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (mint, decimals, token_program) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+
+    fn burn_bought(&self, is_x: bool, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let (mint, from, token_program) = match is_x {
+            true => (self.mint_x.to_account_info(), self.protocol_fee_vault_x.to_account_info(), self.token_program_x.to_account_info()),
+            false => (self.mint_y.to_account_info(), self.protocol_fee_vault_y.to_account_info(), self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = Burn {
+            mint,
+            from,
+            authority: self.treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        burn(cpi_ctx, amount)
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface};
+
+use crate::amm_error::AmmError;
+use crate::state::Config;
+use crate::utils::has_close_authority;
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = mint_x,
+        has_one = mint_y,
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        mint::token_program = token_program
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    // the LP mint's program
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClosePool<'info> {
+    pub fn close_pool(&mut self) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        require!(self.lp_mint.supply == 0, AmmError::LiquidityRemaining);
+        require!(self.vault_x.amount == 0 && self.vault_y.amount == 0, AmmError::LiquidityRemaining);
+
+        self.close_token_account(self.vault_x.to_account_info(), self.token_program_x.to_account_info())?;
+        self.close_token_account(self.vault_y.to_account_info(), self.token_program_y.to_account_info())?;
+
+        // Only Token-2022 LP mints carry the config as close authority; a legacy SPL
+        // LP mint stays open, and with it the pool's PDA can't be initialized again.
+        if has_close_authority(&self.lp_mint.to_account_info())? {
+            self.close_token_account(self.lp_mint.to_account_info(), self.token_program.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    // Token-2022 closes a mint through the same instruction as a token account.
+    fn close_token_account(&self, account: AccountInfo<'info>, cpi_program: AccountInfo<'info>) -> Result<()> {
+
+        let cpi_accounts = CloseAccount {
+            account,
+            destination: self.authority.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        close_account(cpi_ctx)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::amm_error::AmmError;
+use crate::events::FeesCollected;
+use crate::state::{Config, GlobalConfig};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    #[account(constraint = authority.key() == global_config.admin @ AmmError::Unauthorized)]
+    pub authority: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = global_config.treasury,
+        token::token_program = token_program_x
+    )]
+    pub destination_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = global_config.treasury,
+        token::token_program = token_program_y
+    )]
+    pub destination_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CollectFees<'info> {
+    // Protocol fees belong to the protocol: the admin sweeps them to the global treasury.
+    pub fn collect_fees(&mut self, remaining_accounts: &[AccountInfo<'info>], bumps: &CollectFeesBumps) -> Result<()> {
+        self.collect_token(true, remaining_accounts, bumps)?;
+        self.collect_token(false, remaining_accounts, bumps)?;
+
+        Ok(())
+    }
+
+    fn collect_token(&self, is_x: bool, remaining_accounts: &[AccountInfo<'info>], bumps: &CollectFeesBumps) -> Result<()> {
+        let (from, to, mint, decimals, amount) = match is_x {
+            true => (
+                self.protocol_fee_vault_x.to_account_info(),
+                self.destination_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.protocol_fee_vault_x.amount,
+            ),
+            false => (
+                self.protocol_fee_vault_y.to_account_info(),
+                self.destination_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.protocol_fee_vault_y.amount,
+            ),
+        };
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint: mint.clone(),
+            authority: self.treasury.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"treasury",
+            config_key.as_ref(),
+            &[self.config.treasury_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, FeesCollected {
+            config: config_key,
+            mint: mint.key(),
+            amount,
+        })?;
+
+        Ok(())
+    }
+}
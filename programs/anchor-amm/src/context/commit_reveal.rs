@@ -0,0 +1,357 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{PAUSE_SWAP, REVEAL_WINDOW_SLOTS};
+use crate::context::*;
+use crate::events::Swapped;
+use crate::executor::SwapRequest;
+use crate::state::{AllowlistEntry, Config, SwapCommitment, SwapReveal};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct CommitSwap<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"swap_commitment", config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        space = 8 + SwapCommitment::INIT_SPACE
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = commitment,
+        associated_token::token_program = token_program_x
+    )]
+    pub commitment_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = commitment,
+        associated_token::token_program = token_program_y
+    )]
+    pub commitment_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = owner)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitSwap<'info> {
+    // Both amounts are escrowed; only what the reveal names is swapped and the rest is
+    // refunded, so funding the other side as well hides the direction.
+    pub fn commit_swap(&mut self, hash: [u8; 32], amount_x: u64, amount_y: u64, bumps: &CommitSwapBumps) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAP)?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
+        require!(amount_x != 0 || amount_y != 0, AmmError::InvalidAmount);
+
+        if amount_x > 0 {
+            self.escrow(true, amount_x)?;
+        }
+        if amount_y > 0 {
+            self.escrow(false, amount_y)?;
+        }
+
+        self.commitment.set_inner(SwapCommitment {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            hash,
+            committed_slot: Clock::get()?.slot,
+            bump: bumps.commitment,
+        });
+        Ok(())
+    }
+
+    fn escrow(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, token_program) = match is_x {
+            true => (self.owner_ata_x.to_account_info(), self.commitment_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.owner_ata_y.to_account_info(), self.commitment_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevealSwap<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub pool: PoolSwap<'info>,
+    // the ATA constraints below can't reach into the nested pool accounts
+    #[account(address = pool.config.mint_x)]
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.config.mint_y)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.token_program_x.key())]
+    pub token_program_x: Interface<'info, TokenInterface>,
+    #[account(address = pool.token_program_y.key())]
+    pub token_program_y: Interface<'info, TokenInterface>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        constraint = commitment.config == pool.config.key() @ AmmError::InvalidConfig,
+        seeds = [b"swap_commitment", commitment.config.as_ref(), owner.key().as_ref()],
+        bump = commitment.bump
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = commitment,
+        associated_token::token_program = token_program_x
+    )]
+    pub commitment_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = commitment,
+        associated_token::token_program = token_program_y
+    )]
+    pub commitment_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RevealSwap<'info> {
+    // Must land after the commit's slot and within REVEAL_WINDOW_SLOTS of it. The swap
+    // is paid from the escrow, and the output plus anything left over go to the owner.
+    pub fn reveal_swap(&mut self, reveal: SwapReveal, salt: [u8; 32], bumps: &RevealSwapBumps) -> Result<()> {
+        require!(self.pool.config.batch_window_secs == 0, AmmError::BatchModePool);
+        let slot = Clock::get()?.slot;
+        require!(
+            slot > self.commitment.committed_slot && slot <= self.commitment.committed_slot.saturating_add(REVEAL_WINDOW_SLOTS),
+            AmmError::RevealWindowClosed
+        );
+
+        let mut params = Vec::new();
+        reveal.serialize(&mut params)?;
+        require!(keccak::hashv(&[&params, &salt]).0 == self.commitment.hash, AmmError::CommitmentMismatch);
+
+        let (from, to) = match reveal.is_x {
+            true => (self.commitment_ata_x.to_account_info(), self.owner_ata_y.to_account_info()),
+            false => (self.commitment_ata_y.to_account_info(), self.owner_ata_x.to_account_info()),
+        };
+        let config_key = self.commitment.config;
+        let owner_key = self.owner.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"swap_commitment", config_key.as_ref(), owner_key.as_ref(), &[self.commitment.bump]]];
+        let request = SwapRequest {
+            is_x: reveal.is_x,
+            amount_in: reveal.amount_in,
+            min_amount_out: reveal.min_amount_out,
+            signer: owner_key,
+            runs_hook: false,
+            batch_settlement: false,
+        };
+        let res = self.pool.swap(request, from, to, self.commitment.to_account_info(), signer_seeds)?;
+
+        self.commitment_ata_x.reload()?;
+        self.commitment_ata_y.reload()?;
+        self.drain(true, signer_seeds)?;
+        self.drain(false, signer_seeds)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: config_key,
+            user: owner_key,
+            is_x: reveal.is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            protocol_fee: res.protocol_fee,
+            referral_fee: 0,
+            reserve_x: res.reserve_x,
+            reserve_y: res.reserve_y,
+        })
+    }
+
+    fn drain(&self, is_x: bool, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let (from, to, mint, decimals, amount, token_program) = match is_x {
+            true => (self.commitment_ata_x.to_account_info(), self.owner_ata_x.to_account_info(), self.pool.mint_x.to_account_info(), self.pool.mint_x.decimals, self.commitment_ata_x.amount, self.pool.token_program_x.to_account_info()),
+            false => (self.commitment_ata_y.to_account_info(), self.owner_ata_y.to_account_info(), self.pool.mint_y.to_account_info(), self.pool.mint_y.decimals, self.commitment_ata_y.amount, self.pool.token_program_y.to_account_info()),
+        };
+
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: from.clone(),
+                to,
+                mint,
+                authority: self.commitment.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+            transfer_tokens(cpi_ctx, amount, decimals)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: from,
+            destination: self.owner.to_account_info(),
+            authority: self.commitment.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReclaimSwapCommitment<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"swap_commitment", config.key().as_ref(), owner.key().as_ref()],
+        bump = commitment.bump
+    )]
+    pub commitment: Box<Account<'info, SwapCommitment>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = commitment,
+        associated_token::token_program = token_program_x
+    )]
+    pub commitment_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = commitment,
+        associated_token::token_program = token_program_y
+    )]
+    pub commitment_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ReclaimSwapCommitment<'info> {
+    // Returns the escrow of a commitment that was never revealed in time.
+    pub fn reclaim_swap_commitment(&mut self) -> Result<()> {
+        require!(
+            Clock::get()?.slot > self.commitment.committed_slot.saturating_add(REVEAL_WINDOW_SLOTS),
+            AmmError::CommitmentActive
+        );
+
+        let config_key = self.config.key();
+        let owner_key = self.owner.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"swap_commitment", config_key.as_ref(), owner_key.as_ref(), &[self.commitment.bump]]];
+        self.drain(true, signer_seeds)?;
+        self.drain(false, signer_seeds)
+    }
+
+    fn drain(&self, is_x: bool, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let (from, to, mint, decimals, amount, token_program) = match is_x {
+            true => (self.commitment_ata_x.to_account_info(), self.owner_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.commitment_ata_x.amount, self.token_program_x.to_account_info()),
+            false => (self.commitment_ata_y.to_account_info(), self.owner_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.commitment_ata_y.amount, self.token_program_y.to_account_info()),
+        };
+
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: from.clone(),
+                to,
+                mint,
+                authority: self.commitment.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+            transfer_tokens(cpi_ctx, amount, decimals)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: from,
+            destination: self.owner.to_account_info(),
+            authority: self.commitment.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,325 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{KEEPER_BOUNTY_BPS, PAUSE_SWAP};
+use crate::context::*;
+use crate::events::Swapped;
+use crate::executor::SwapRequest;
+use crate::math::to_u64;
+use crate::price::Price;
+use crate::state::{AllowlistEntry, Config, Dca};
+use crate::utils::{bps_of, emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateDca<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"dca", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Dca::INIT_SPACE
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = dca,
+        associated_token::token_program = token_program_x
+    )]
+    pub dca_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = dca,
+        associated_token::token_program = token_program_y
+    )]
+    pub dca_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = owner)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateDca<'info> {
+    pub fn create_dca(&mut self, seed: u64, is_x: bool, amount: u64, amount_per_fill: u64, interval: i64, min_price: Price, bumps: &CreateDcaBumps) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAP)?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
+        require!(amount != 0 && amount_per_fill != 0, AmmError::InvalidAmount);
+        require!(interval > 0, AmmError::InvalidOrderDuration);
+
+        let (from, to, mint, decimals, token_program) = match is_x {
+            true => (self.owner_ata_x.to_account_info(), self.dca_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.owner_ata_y.to_account_info(), self.dca_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        // the first fill can be cranked straight away
+        self.dca.set_inner(Dca {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            seed,
+            is_x,
+            amount_per_fill,
+            interval,
+            min_price,
+            next_fill_time: Clock::get()?.unix_timestamp,
+            amount_spent: 0,
+            amount_out: 0,
+            bump: bumps.dca,
+        });
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteDca<'info> {
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        token::authority = keeper
+    )]
+    pub keeper_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub pool: PoolSwap<'info>,
+    #[account(
+        mut,
+        constraint = dca.config == pool.config.key() @ AmmError::InvalidOrder,
+        seeds = [b"dca", dca.config.as_ref(), dca.owner.as_ref(), dca.seed.to_le_bytes().as_ref()],
+        bump = dca.bump
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(
+        mut,
+        associated_token::mint = pool.mint_x,
+        associated_token::authority = dca,
+        associated_token::token_program = pool.token_program_x
+    )]
+    pub dca_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = pool.mint_y,
+        associated_token::authority = dca,
+        associated_token::token_program = pool.token_program_y
+    )]
+    pub dca_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'info> ExecuteDca<'info> {
+    // Permissionless: any keeper can run a fill once the interval has passed and
+    // is paid a bounty out of the fill's input.
+    pub fn execute_dca(&mut self, bumps: &ExecuteDcaBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= self.dca.next_fill_time, AmmError::NothingToExecute);
+
+        let is_x = self.dca.is_x;
+        let (from, to, mint_in, decimals, balance, token_program) = match is_x {
+            true => (self.dca_ata_x.to_account_info(), self.dca_ata_y.to_account_info(), self.pool.mint_x.to_account_info(), self.pool.mint_x.decimals, self.dca_ata_x.amount, self.pool.token_program_x.to_account_info()),
+            false => (self.dca_ata_y.to_account_info(), self.dca_ata_x.to_account_info(), self.pool.mint_y.to_account_info(), self.pool.mint_y.decimals, self.dca_ata_y.amount, self.pool.token_program_y.to_account_info()),
+        };
+        require_keys_eq!(self.keeper_ata.mint, mint_in.key(), AmmError::InvalidOrder);
+
+        let fill = self.dca.amount_per_fill.min(balance);
+        require!(fill != 0, AmmError::NothingToExecute);
+        let bounty = bps_of(fill, KEEPER_BOUNTY_BPS)?;
+        let amount_in = fill - bounty;
+        let min_amount_out = to_u64(self.dca.min_price.quote(amount_in as u128)?)?;
+        let seed = self.dca.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"dca",
+            self.dca.config.as_ref(),
+            self.dca.owner.as_ref(),
+            &seed[..],
+            &[self.dca.bump],
+        ]];
+
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out,
+            signer: self.dca.owner,
+            runs_hook: false,
+            batch_settlement: false,
+        };
+        let res = self.pool.swap(request, from.clone(), to, self.dca.to_account_info(), signer_seeds)?;
+
+        if bounty > 0 {
+            let cpi_accounts = TransferChecked {
+                from,
+                to: self.keeper_ata.to_account_info(),
+                mint: mint_in,
+                authority: self.dca.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+            transfer_tokens(cpi_ctx, bounty, decimals)?;
+        }
+
+        self.dca.amount_spent = self.dca.amount_spent.checked_add(fill).ok_or(AmmError::Overflow)?;
+        self.dca.amount_out = self.dca.amount_out.checked_add(res.amount_out).ok_or(AmmError::Overflow)?;
+        self.dca.next_fill_time = now.checked_add(self.dca.interval).ok_or(AmmError::Overflow)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: self.pool.config.key(),
+            user: self.dca.key(),
+            is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            protocol_fee: res.protocol_fee,
+            referral_fee: 0,
+            reserve_x: res.reserve_x,
+            reserve_y: res.reserve_y,
+        })
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseDca<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = config @ AmmError::InvalidOrder,
+        seeds = [b"dca", config.key().as_ref(), owner.key().as_ref(), dca.seed.to_le_bytes().as_ref()],
+        bump = dca.bump
+    )]
+    pub dca: Box<Account<'info, Dca>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = dca,
+        associated_token::token_program = token_program_x
+    )]
+    pub dca_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = dca,
+        associated_token::token_program = token_program_y
+    )]
+    pub dca_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseDca<'info> {
+    // Returns unspent input and accrued proceeds to the owner.
+    pub fn close_dca(&mut self) -> Result<()> {
+        self.drain(true)?;
+        self.drain(false)
+    }
+
+    fn drain(&self, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals, amount, token_program) = match is_x {
+            true => (self.dca_ata_x.to_account_info(), self.owner_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.dca_ata_x.amount, self.token_program_x.to_account_info()),
+            false => (self.dca_ata_y.to_account_info(), self.owner_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.dca_ata_y.amount, self.token_program_y.to_account_info()),
+        };
+
+        let seed = self.dca.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"dca",
+            self.dca.config.as_ref(),
+            self.dca.owner.as_ref(),
+            &seed[..],
+            &[self.dca.bump],
+        ]];
+
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: from.clone(),
+                to,
+                mint,
+                authority: self.dca.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+            transfer_tokens(cpi_ctx, amount, decimals)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: from,
+            destination: self.owner.to_account_info(),
+            authority: self.dca.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -1,99 +1,523 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{TransferChecked, transfer_checked, Mint, TokenInterface, TokenAccount, MintTo, mint_to}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{TransferChecked, Mint, TokenInterface, TokenAccount, MintTo, mint_to}};
 
-use constant_product_curve::ConstantProduct;
-
-use crate::state::Config;
+use crate::state::{AllowlistEntry, Config, DepositRecord, GlobalConfig, Observations, Oracle, Volatility, WalletDeposits};
 use crate::amm_error::AmmError;
+use crate::events::Deposited;
+use crate::constants::{MINIMUM_LIQUIDITY, PAUSE_DEPOSIT};
+use crate::executor::{SwapExecutor, SwapRequest};
+use crate::math::{balanced_liquidity, deposit_amounts, isqrt, mul_div, mul_div_ceil, to_u64, zap_swap_amount};
+use crate::utils::{bps_of, check_expiration, check_spend_authority, emit_event_cpi, is_native_mint, transfer_tokens, wrap_sol};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    pub mint_x: InterfaceAccount<'info, Mint>,
-    pub mint_y: InterfaceAccount<'info, Mint>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
     #[account(
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_x,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x
     )]
-    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_y,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y
     )]
-    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    // pay instead of the user's ATAs; the user must own them or be their delegate
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::token_program = token_program_x
+    )]
+    pub source_ata_x: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::token_program = token_program_y
+    )]
+    pub source_ata_y: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         mut,
         associated_token::mint = mint_x,
-        associated_token::authority = config
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
     )]
-    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_y,
-        associated_token::authority = config
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
     )]
-    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     pub config: Account<'info, Config>,
     #[account(
-        seeds = [b"lp", config.key().as_ref()],
-        bump = config.lp_bump
+        seeds = [b"global_config"],
+        bump = global_config.bump
     )]
-    pub lp_mint: InterfaceAccount<'info, Mint>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = lp_mint,
         associated_token::authority = user
     )]
-    pub user_lp_ata: InterfaceAccount<'info, TokenAccount>,
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = lp_mint
+    )]
+    pub recipient_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // holds the minimum liquidity locked at bootstrap; no instruction ever moves it
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = lp_mint,
+        associated_token::authority = config
+    )]
+    pub locked_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    // kept for whoever receives the LP, since that is the wallet that can withdraw it
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"deposit_record", config.key().as_ref(), recipient_lp_ata.as_ref().map_or(user.key(), |ata| ata.owner).as_ref()],
+        bump,
+        space = 8 + DepositRecord::INIT_SPACE
+    )]
+    pub deposit_record: Box<Account<'info, DepositRecord>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"wallet_deposits", config.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + WalletDeposits::INIT_SPACE
+    )]
+    pub wallet_deposits: Option<Box<Account<'info, WalletDeposits>>>,
+    // the accounts below are only read by the zaps, whose swap leg runs like any other swap
+    #[account(token::authority = user)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+    /// CHECK: validated against the pool's configured oracle in Config::oracle_price
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated against the pool's secondary oracle in Config::oracle_price
+    pub secondary_price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: address is checked against the instructions sysvar; required by sandwich-guarded pools
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>
     
 }
 
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
-        require!(self.config.locked == false, AmmError::PoolLocked);
+    pub fn deposit(&mut self, amount: u64, max_x: u64, max_y: u64, expiration: Option<i64>, native_sol: bool, remaining_accounts: &[AccountInfo<'info>], bumps: &DepositBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_DEPOSIT)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        self.config.check_deposit_allowed(self.allowlist_entry.as_deref())?;
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
         require!(amount != 0, AmmError::InvalidAmount);
 
-        let (x, y) = match self.lp_mint.supply == 0 && self.vault_x.amount == 0 && self.vault_y.amount == 0 {
+        let supply = self.lp_mint.supply;
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        let bootstrap = supply == 0 && reserve_x == 0 && reserve_y == 0;
+
+        let (x, y) = match bootstrap {
             true => (max_x, max_y),
+            false => deposit_amounts(reserve_x, reserve_y, supply, amount)?,
+        };
+
+        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+
+        if native_sol {
+            self.wrap_native(x, y)?;
+        }
+
+        let received_x = self.deposit_token(true, x, remaining_accounts)?;
+        let received_y = self.deposit_token(false, y, remaining_accounts)?;
+
+        // transfer fees can leave the vaults short of the quoted amounts,
+        // so LP is only minted for what actually arrived
+        let lp = match bootstrap {
+            true => {
+                // the first MINIMUM_LIQUIDITY units are locked forever so the share
+                // price can't be inflated against later depositors
+                let liquidity = to_u64(isqrt((received_x as u128) * (received_y as u128)))?;
+                require!(liquidity > MINIMUM_LIQUIDITY, AmmError::InsufficientInitialLiquidity);
+                self.mint_lp_tokens(self.locked_lp_ata.to_account_info(), MINIMUM_LIQUIDITY)?;
+                self.lp_mint.reload()?;
+
+                let lp = liquidity - MINIMUM_LIQUIDITY;
+                require!(lp >= amount, AmmError::SlippageExceeded);
+                lp
+            },
             false => {
-                let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                    self.vault_x.amount,
-                    self.vault_y.amount,
-                    self.lp_mint.supply,
-                    amount,
-                    6
-                ).unwrap();
-                (amounts.x, amounts.y)
+                let lp_x = mul_div(supply as u128, received_x as u128, reserve_x as u128)?;
+                let lp_y = mul_div(supply as u128, received_y as u128, reserve_y as u128)?;
+                to_u64(lp_x.min(lp_y))?.min(amount)
             },
         };
+        require!(lp != 0, AmmError::InvalidAmount);
+        self.check_caps(received_x, received_y, bumps)?;
+
+        self.mint_lp_tokens(self.lp_recipient(), lp)?;
+        self.record_deposit(bumps)?;
+        self.emit_deposited(received_x, received_y, lp, bumps)?;
+
+        Ok(())
+    }
+
+    pub fn deposit_single(&mut self, is_x: bool, amount_in: u64, min_lp_out: u64, expiration: Option<i64>, native_sol: bool, remaining_accounts: &[AccountInfo<'info>], bumps: &DepositBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_DEPOSIT)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(self.config.batch_window_secs == 0, AmmError::BatchModePool);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        self.config.check_deposit_allowed(self.allowlist_entry.as_deref())?;
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.config.check_product_curve()?;
+        require!(amount_in != 0, AmmError::InvalidAmount);
+
+        let supply = self.lp_mint.supply;
+        require!(supply != 0 && self.config.reserve_x != 0 && self.config.reserve_y != 0, AmmError::NoLiquidityInPool);
+
+        if native_sol {
+            match is_x {
+                true => self.wrap_native(amount_in, 0)?,
+                false => self.wrap_native(0, amount_in)?,
+            }
+        }
+
+        let (received, lp) = self.zap(is_x, amount_in, supply, remaining_accounts)?;
+
+        require!(lp != 0, AmmError::InvalidAmount);
+        require!(lp >= min_lp_out, AmmError::SlippageExceeded);
+        match is_x {
+            true => self.check_caps(received, 0, bumps)?,
+            false => self.check_caps(0, received, bumps)?,
+        }
+
+        self.mint_lp_tokens(self.lp_recipient(), lp)?;
+        self.record_deposit(bumps)?;
+        match is_x {
+            true => self.emit_deposited(received, 0, lp, bumps)?,
+            false => self.emit_deposited(0, received, lp, bumps)?,
+        }
+
+        Ok(())
+    }
+
+    pub fn deposit_imbalanced(&mut self, amount_x: u64, amount_y: u64, min_lp_out: u64, expiration: Option<i64>, native_sol: bool, remaining_accounts: &[AccountInfo<'info>], bumps: &DepositBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_DEPOSIT)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(self.config.batch_window_secs == 0, AmmError::BatchModePool);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        self.config.check_deposit_allowed(self.allowlist_entry.as_deref())?;
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
+        self.config.check_product_curve()?;
+        require!(amount_x != 0 || amount_y != 0, AmmError::InvalidAmount);
+
+        let supply = self.lp_mint.supply;
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        require!(supply != 0 && reserve_x != 0 && reserve_y != 0, AmmError::NoLiquidityInPool);
+        let k_before = self.config.k();
+
+        // deposit the balanced part at the pool ratio, rounding the matched side up
+        let x_excess = (amount_x as u128) * (reserve_y as u128) >= (amount_y as u128) * (reserve_x as u128);
+        let (limiting, reserve_limiting, excess_amount, reserve_excess) = match x_excess {
+            true => (amount_y, reserve_y, amount_x, reserve_x),
+            false => (amount_x, reserve_x, amount_y, reserve_y),
+        };
+        let matched = mul_div_ceil(limiting as u128, reserve_excess as u128, reserve_limiting as u128)?;
+        let matched = to_u64(matched)?.min(excess_amount);
+        let (balanced_x, balanced_y) = match x_excess {
+            true => (matched, limiting),
+            false => (limiting, matched),
+        };
 
-        self.deposit_token(true, x)?;
-        self.deposit_token(false, y)?;
+        if native_sol {
+            self.wrap_native(amount_x, amount_y)?;
+        }
 
-        self.mint_lp_tokens(amount)?;
+        let received_x = match balanced_x {
+            0 => 0,
+            _ => self.deposit_token(true, balanced_x, remaining_accounts)?,
+        };
+        let received_y = match balanced_y {
+            0 => 0,
+            _ => self.deposit_token(false, balanced_y, remaining_accounts)?,
+        };
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        self.config.check_invariant(k_before, self.vault_x.amount, self.vault_y.amount)?;
+        let lp_balanced = balanced_liquidity(reserve_x, reserve_y, supply, received_x, received_y)?;
+
+        // zap the remainder of the excess side, which swaps part of it through the pool
+        let (zapped, lp_zap) = match excess_amount - matched {
+            0 => (0, 0),
+            excess => self.zap(x_excess, excess, supply.checked_add(lp_balanced).ok_or(AmmError::Overflow)?, remaining_accounts)?,
+        };
+        let (amount_x, amount_y) = match x_excess {
+            true => (received_x.checked_add(zapped).ok_or(AmmError::Overflow)?, received_y),
+            false => (received_x, received_y.checked_add(zapped).ok_or(AmmError::Overflow)?),
+        };
+
+        let lp = lp_balanced.checked_add(lp_zap).ok_or(AmmError::Overflow)?;
+        require!(lp != 0, AmmError::InvalidAmount);
+        require!(lp >= min_lp_out, AmmError::SlippageExceeded);
+        self.check_caps(amount_x, amount_y, bumps)?;
+
+        self.mint_lp_tokens(self.lp_recipient(), lp)?;
+        self.record_deposit(bumps)?;
+        self.emit_deposited(amount_x, amount_y, lp, bumps)?;
 
         Ok(())
     }
-    fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
 
-        let (from, to, mint, decimals) = match is_x {
-            true => (self.user_ata_x.to_account_info(), self.vault_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals),
-            false => (self.user_ata_y.to_account_info(), self.vault_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals)
+    // Swaps part of `amount_in` through the pool as any swap would, then deposits the
+    // rest beside the output at the post-swap ratio. `supply` is the LP supply the
+    // deposit is priced against. Returns what reached the vault and the LP it buys.
+    fn zap(&mut self, is_x: bool, amount_in: u64, supply: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<(u64, u64)> {
+        let oracle_price = self.config.oracle_price(self.price_feed.as_deref(), self.secondary_price_feed.as_deref(), self.mint_x.decimals, self.mint_y.decimals)?;
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out: 0,
+            signer: self.user.key(),
+            runs_hook: false,
+            batch_settlement: false,
         };
+        let mut executor = SwapExecutor::begin(
+            &mut self.config,
+            &self.global_config,
+            &request,
+            self.instructions.as_deref(),
+            (self.vault_x.amount, self.vault_y.amount),
+            &mut self.oracle,
+            &self.observations,
+            oracle_price,
+        )?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
 
-        let cpi_program = self.token_program.to_account_info();
+        let reserve_in = match is_x {
+            true => executor.reserve_x,
+            false => executor.reserve_y,
+        };
+        let fee = executor.pool_fee(&self.config, self.volatility.as_deref())?;
+        let res = executor.quote_zap(&self.config, fee, zap_swap_amount(reserve_in, amount_in, fee)?)?;
+        let protocol_fee = bps_of(res.fee, self.config.protocol_fee_bps)?;
+        let lp_deposit = res.deposit.checked_sub(protocol_fee).ok_or(AmmError::Underflow)?;
+
+        let swap_received = self.transfer_to_vault(is_x, lp_deposit, remaining_accounts)?;
+        if protocol_fee > 0 {
+            let protocol_fee_vault = match is_x {
+                true => self.protocol_fee_vault_x.as_ref(),
+                false => self.protocol_fee_vault_y.as_ref(),
+            }
+            .ok_or(AmmError::MissingProtocolFeeVault)?
+            .to_account_info();
+            self.transfer_from_user(is_x, protocol_fee_vault, protocol_fee, remaining_accounts)?;
+        }
+        let (_, swapped) = executor.settle(&mut self.config, &res, lp_deposit, swap_received, protocol_fee)?;
+        executor.finish(&self.config, (self.vault_x.amount, self.vault_y.amount), &res, swapped, self.volatility.as_deref_mut())?;
+
+        // the output never leaves the vault: it is deposited back with the rest of the input
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        let rest = amount_in.checked_sub(res.deposit).ok_or(AmmError::Underflow)?;
+        let deposited = self.deposit_token(is_x, rest, remaining_accounts)?;
+        self.config.credit_reserve(!is_x, swapped)?;
+        let lp = match is_x {
+            true => balanced_liquidity(reserve_x, reserve_y, supply, deposited, swapped)?,
+            false => balanced_liquidity(reserve_x, reserve_y, supply, swapped, deposited)?,
+        };
+
+        let received = swap_received.checked_add(deposited).ok_or(AmmError::Overflow)?;
+        Ok((received, lp))
+    }
+
+    // Runs once the deposit is credited, so both caps see the pool as it ends up.
+    fn check_caps(&mut self, amount_x: u64, amount_y: u64, bumps: &DepositBumps) -> Result<()> {
+        self.config.check_tvl_cap()?;
+        if self.config.max_deposit_per_wallet == 0 {
+            return Ok(());
+        }
+
+        let value = self.config.y_value(amount_x, amount_y)?;
+        let wallet_deposits = self.wallet_deposits.as_mut().ok_or(AmmError::MissingWalletDeposits)?;
+        if wallet_deposits.config == Pubkey::default() {
+            wallet_deposits.config = self.config.key();
+            wallet_deposits.user = self.user.key();
+            wallet_deposits.bump = bumps.wallet_deposits.unwrap_or_default();
+        }
+        wallet_deposits.deposited = wallet_deposits.deposited.checked_add(value).ok_or(AmmError::Overflow)?;
+        require!(wallet_deposits.deposited <= self.config.max_deposit_per_wallet, AmmError::WalletCapExceeded);
+        Ok(())
+    }
+
+    fn record_deposit(&mut self, bumps: &DepositBumps) -> Result<()> {
+        let clock = Clock::get()?;
+        self.deposit_record.set_inner(DepositRecord {
+            config: self.config.key(),
+            user: self.lp_owner(),
+            last_deposit_slot: clock.slot,
+            last_deposit_at: clock.unix_timestamp,
+            bump: bumps.deposit_record,
+        });
+        Ok(())
+    }
+
+    // Reserves are credited by deposit_token, so they are already post-deposit.
+    fn emit_deposited(&self, amount_x: u64, amount_y: u64, lp_amount: u64, bumps: &DepositBumps) -> Result<()> {
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Deposited {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount,
+            reserve_x: self.config.reserve_x,
+            reserve_y: self.config.reserve_y,
+            lp_supply: self.lp_mint.supply.checked_add(lp_amount).ok_or(AmmError::Overflow)?,
+        })?;
+        Ok(())
+    }
+
+    fn wrap_native(&self, x: u64, y: u64) -> Result<()> {
+        // a separate source is spent as-is, never topped up from the user's lamports
+        let (user_ata, amount, token_program) = match (is_native_mint(&self.mint_x.key()), is_native_mint(&self.mint_y.key())) {
+            (true, _) if self.source_ata_x.is_none() => (self.user_ata_x.to_account_info(), x, self.token_program_x.to_account_info()),
+            (_, true) if self.source_ata_y.is_none() => (self.user_ata_y.to_account_info(), y, self.token_program_y.to_account_info()),
+            _ => return Ok(()),
+        };
+
+        wrap_sol(
+            self.user.to_account_info(),
+            user_ata,
+            self.system_program.to_account_info(),
+            token_program,
+            amount,
+        )
+    }
+
+    // Returns the amount that actually reached the vault.
+    fn deposit_token(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let received = self.transfer_to_vault(is_x, amount, remaining_accounts)?;
+        self.config.credit_reserve(is_x, received)?;
+        Ok(received)
+    }
+
+    // Like deposit_token, but leaves the reserves to the caller.
+    fn transfer_to_vault(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let (to, before) = match is_x {
+            true => (self.vault_x.to_account_info(), self.vault_x.amount),
+            false => (self.vault_y.to_account_info(), self.vault_y.amount),
+        };
+
+        self.transfer_from_user(is_x, to, amount, remaining_accounts)?;
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+
+        vault.amount.checked_sub(before).ok_or(error!(AmmError::Underflow))
+    }
+
+    fn transfer_from_user(&self, is_x: bool, to: AccountInfo<'info>, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (mint, decimals) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let source_ata = match is_x {
+            true => self.source_ata_x.as_ref(),
+            false => self.source_ata_y.as_ref(),
+        };
+        let from = match source_ata {
+            Some(source_ata) => {
+                check_spend_authority(source_ata, &self.user.key(), amount)?;
+                source_ata.to_account_info()
+            }
+            None if is_x => self.user_ata_x.to_account_info(),
+            None => self.user_ata_y.to_account_info(),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
 
         let cpi_accounts = TransferChecked {
             from,
@@ -102,26 +526,41 @@ impl<'info> Deposit<'info> {
             authority: self.user.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
 
-        transfer_checked(cpi_ctx, amount, decimals)?;
+    fn lp_owner(&self) -> Pubkey {
+        match self.recipient_lp_ata {
+            Some(ref recipient_lp_ata) => recipient_lp_ata.owner,
+            None => self.user.key(),
+        }
+    }
 
-        Ok(())
+    fn lp_recipient(&self) -> AccountInfo<'info> {
+        match self.recipient_lp_ata {
+            Some(ref recipient_lp_ata) => recipient_lp_ata.to_account_info(),
+            None => self.user_lp_ata.to_account_info(),
+        }
     }
 
-    fn mint_lp_tokens(&self, amount: u64) -> Result<()> {
+    fn mint_lp_tokens(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
 
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_account = MintTo {
             mint: self.lp_mint.to_account_info(),
-            to: self.user_lp_ata.to_account_info(),
+            to,
             authority: self.config.to_account_info(),
         };
 
         let signer_seeds: &[&[&[u8]]; 1] = &[&[
             b"config",
-            &self.config.seed.to_le_bytes()[..],
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
             &[self.config.config_bump]
         ]];
 
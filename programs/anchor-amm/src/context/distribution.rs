@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::events::MerkleClaimed;
+use crate::state::{ClaimStatus, Distribution, GlobalConfig};
+use crate::utils::{emit_event_cpi, transfer_tokens, verify_merkle_proof};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateDistribution<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = admin,
+        associated_token::token_program = token_program
+    )]
+    pub admin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"distribution", mint.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Distribution::INIT_SPACE
+    )]
+    pub distribution: Box<Account<'info, Distribution>>,
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = distribution,
+        associated_token::token_program = token_program
+    )]
+    pub distribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateDistribution<'info> {
+    // The vault is funded with the full campaign up front, so every valid leaf is
+    // guaranteed to be claimable.
+    pub fn create_distribution(&mut self, seed: u64, root: [u8; 32], total_amount: u64, bumps: &CreateDistributionBumps) -> Result<()> {
+        require!(total_amount != 0, AmmError::InvalidAmount);
+
+        let cpi_accounts = TransferChecked {
+            from: self.admin_ata.to_account_info(),
+            to: self.distribution_vault.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.admin.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, total_amount, self.mint.decimals)?;
+
+        // fee-on-transfer mints may deliver less than was sent
+        self.distribution_vault.reload()?;
+        self.distribution.set_inner(Distribution {
+            admin: self.admin.key(),
+            mint: self.mint.key(),
+            seed,
+            root,
+            total_amount: self.distribution_vault.amount,
+            claimed_amount: 0,
+            num_claimed: 0,
+            bump: bumps.distribution,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct ClaimDistribution<'info> {
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+    #[account(
+        mut,
+        has_one = mint,
+        seeds = [b"distribution", mint.key().as_ref(), distribution.seed.to_le_bytes().as_ref()],
+        bump = distribution.bump
+    )]
+    pub distribution: Box<Account<'info, Distribution>>,
+    #[account(
+        init,
+        payer = claimant,
+        seeds = [b"claim_status", distribution.key().as_ref(), index.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + ClaimStatus::INIT_SPACE
+    )]
+    pub claim_status: Box<Account<'info, ClaimStatus>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = distribution,
+        associated_token::token_program = token_program
+    )]
+    pub distribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = claimant,
+        associated_token::token_program = token_program
+    )]
+    pub claimant_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimDistribution<'info> {
+    pub fn claim(&mut self, index: u64, amount: u64, proof: Vec<[u8; 32]>, bumps: &ClaimDistributionBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let leaf = keccak::hashv(&[
+            &index.to_le_bytes(),
+            self.claimant.key().as_ref(),
+            &amount.to_le_bytes(),
+        ]);
+        require!(verify_merkle_proof(&proof, self.distribution.root, leaf.0), AmmError::InvalidProof);
+
+        let claimed_amount = self.distribution.claimed_amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        require!(claimed_amount <= self.distribution.total_amount, AmmError::InsufficientBalance);
+
+        let mint = self.mint.key();
+        let seed = self.distribution.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"distribution",
+            mint.as_ref(),
+            &seed[..],
+            &[self.distribution.bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            from: self.distribution_vault.to_account_info(),
+            to: self.claimant_ata.to_account_info(),
+            mint: self.mint.to_account_info(),
+            authority: self.distribution.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, self.mint.decimals)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        self.distribution.claimed_amount = claimed_amount;
+        self.distribution.num_claimed = self.distribution.num_claimed.checked_add(1).ok_or(AmmError::Overflow)?;
+        self.claim_status.set_inner(ClaimStatus {
+            distribution: self.distribution.key(),
+            claimant: self.claimant.key(),
+            amount,
+            claimed_at: now,
+            bump: bumps.claim_status,
+        });
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, MerkleClaimed {
+            distribution: self.distribution.key(),
+            claimant: self.claimant.key(),
+            index,
+            amount,
+        })
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::state::{Config, Volatility};
+
+#[derive(Accounts)]
+pub struct InitVolatility<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"volatility", config.key().as_ref()],
+        bump,
+        space = 8 + Volatility::INIT_SPACE
+    )]
+    pub volatility: Account<'info, Volatility>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitVolatility<'info> {
+    // The dynamic fee itself goes through propose_change; a DynamicFee change can
+    // only turn it on once this account exists to track volatility.
+    pub fn init_volatility(&mut self, bumps: &InitVolatilityBumps) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        self.volatility.set_inner(Volatility {
+            config: self.config.key(),
+            last_price: 0,
+            ema_bps: 0,
+            last_update: 0,
+            bump: bumps.volatility,
+        });
+        Ok(())
+    }
+}
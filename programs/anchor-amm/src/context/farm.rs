@@ -0,0 +1,327 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{BASE_BOOST_BPS, EARLY_EXIT_PENALTY_BPS, MAX_LOCK_DURATION};
+use crate::state::{Config, Farm, StakePosition};
+use crate::utils::{bps_of, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct CreateFarm<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"farm", config.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        space = 8 + Farm::INIT_SPACE
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = lp_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program
+    )]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateFarm<'info> {
+    pub fn create_farm(&mut self, emission_rate: u64, bumps: &CreateFarmBumps) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        self.farm.set_inner(Farm {
+            config: self.config.key(),
+            lp_mint: self.lp_mint.key(),
+            reward_mint: self.reward_mint.key(),
+            emission_rate,
+            acc_reward_per_share: 0,
+            total_staked: 0,
+            total_weight: 0,
+            unallocated_rewards: 0,
+            last_update: Clock::get()?.unix_timestamp,
+            bump: bumps.farm,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    pub funder: Signer<'info>,
+    #[account(
+        mut,
+        has_one = reward_mint,
+        seeds = [b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder,
+        token::token_program = reward_token_program
+    )]
+    pub funder_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FundRewards<'info> {
+    pub fn fund_rewards(&mut self, amount: u64) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+
+        let cpi_accounts = TransferChecked {
+            from: self.funder_ata.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.reward_token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        // only what reached the vault can be emitted
+        let before = self.reward_vault.amount;
+        self.reward_vault.reload()?;
+        let received = self.reward_vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.farm.unallocated_rewards = self.farm.unallocated_rewards.checked_add(received).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = lp_mint,
+        seeds = [b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"stake", farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + StakePosition::INIT_SPACE
+    )]
+    pub position: Box<Account<'info, StakePosition>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = token_program
+    )]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Stake<'info> {
+    pub fn stake(&mut self, amount: u64, bumps: &StakeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        self.sync_position(bumps)?;
+
+        let cpi_accounts = TransferChecked {
+            from: self.user_lp_ata.to_account_info(),
+            to: self.lp_vault.to_account_info(),
+            mint: self.lp_mint.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, self.lp_mint.decimals)?;
+
+        self.position.amount = self.position.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.farm.total_staked = self.farm.total_staked.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.farm.reweight(&mut self.position)?;
+        self.position.sync(self.farm.acc_reward_per_share)
+    }
+
+    pub fn unstake(&mut self, amount: u64, bumps: &StakeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        require!(amount <= self.position.amount, AmmError::InsufficientBalance);
+        self.sync_position(bumps)?;
+
+        // leaving a lock early forfeits part of the pending rewards back to the farm
+        if self.position.is_locked(Clock::get()?.unix_timestamp) {
+            let penalty = bps_of(self.position.pending_rewards, EARLY_EXIT_PENALTY_BPS)?;
+            self.position.pending_rewards -= penalty;
+            self.farm.unallocated_rewards = self.farm.unallocated_rewards.checked_add(penalty).ok_or(AmmError::Overflow)?;
+            self.position.lock_end = 0;
+            self.position.boost_bps = BASE_BOOST_BPS;
+        }
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"farm",
+            self.farm.config.as_ref(),
+            self.farm.reward_mint.as_ref(),
+            &[self.farm.bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            from: self.lp_vault.to_account_info(),
+            to: self.user_lp_ata.to_account_info(),
+            mint: self.lp_mint.to_account_info(),
+            authority: self.farm.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, self.lp_mint.decimals)?;
+
+        self.position.amount -= amount;
+        self.farm.total_staked -= amount;
+        self.farm.reweight(&mut self.position)?;
+        self.position.sync(self.farm.acc_reward_per_share)
+    }
+
+    // Locks the whole stake until now + duration. A lock can be extended but never shortened.
+    pub fn lock_stake(&mut self, duration: i64, bumps: &StakeBumps) -> Result<()> {
+        require!(duration > 0 && duration <= MAX_LOCK_DURATION, AmmError::InvalidLockDuration);
+        self.sync_position(bumps)?;
+        require!(self.position.amount != 0, AmmError::InsufficientBalance);
+
+        let lock_end = Clock::get()?.unix_timestamp.checked_add(duration).ok_or(AmmError::Overflow)?;
+        require!(lock_end >= self.position.lock_end, AmmError::InvalidLockDuration);
+
+        self.position.lock_end = lock_end;
+        self.position.boost_bps = StakePosition::lock_boost(duration);
+        self.farm.reweight(&mut self.position)?;
+        self.position.sync(self.farm.acc_reward_per_share)
+    }
+
+    // Brings the farm up to date and banks the position's accrued rewards before its stake changes.
+    fn sync_position(&mut self, bumps: &StakeBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.farm.update(now)?;
+
+        if self.position.farm == Pubkey::default() {
+            self.position.set_inner(StakePosition {
+                farm: self.farm.key(),
+                owner: self.user.key(),
+                amount: 0,
+                weight: 0,
+                boost_bps: BASE_BOOST_BPS,
+                lock_end: 0,
+                reward_debt: 0,
+                pending_rewards: 0,
+                bump: bumps.position,
+            });
+        }
+
+        self.position.settle(self.farm.acc_reward_per_share)?;
+        self.farm.expire_lock(&mut self.position, now)?;
+        self.position.sync(self.farm.acc_reward_per_share)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Harvest<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = reward_mint,
+        seeds = [b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        has_one = farm,
+        constraint = position.owner == user.key() @ AmmError::Unauthorized,
+        seeds = [b"stake", farm.key().as_ref(), user.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, StakePosition>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+        associated_token::token_program = reward_token_program
+    )]
+    pub user_reward_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Harvest<'info> {
+    pub fn harvest(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.farm.update(now)?;
+        self.position.settle(self.farm.acc_reward_per_share)?;
+        self.farm.expire_lock(&mut self.position, now)?;
+        self.position.sync(self.farm.acc_reward_per_share)?;
+
+        let amount = self.position.pending_rewards;
+        require!(amount != 0, AmmError::InvalidAmount);
+        self.position.pending_rewards = 0;
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"farm",
+            self.farm.config.as_ref(),
+            self.farm.reward_mint.as_ref(),
+            &[self.farm.bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            from: self.reward_vault.to_account_info(),
+            to: self.user_reward_ata.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            authority: self.farm.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.reward_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, self.reward_mint.decimals)
+    }
+}
@@ -0,0 +1,369 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::math::{mul_div, to_u64};
+use crate::state::{EpochReward, FeeClaim, FeeEpoch, FeeShareStake, FeeShareVault, GlobalConfig};
+use crate::utils::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct CreateFeeShareVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    pub gov_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"fee_share", gov_mint.key().as_ref()],
+        bump,
+        space = 8 + FeeShareVault::INIT_SPACE
+    )]
+    pub vault: Box<Account<'info, FeeShareVault>>,
+    #[account(
+        init,
+        payer = admin,
+        associated_token::mint = gov_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub stake_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateFeeShareVault<'info> {
+    pub fn create_fee_share_vault(&mut self, epoch_duration: i64, bumps: &CreateFeeShareVaultBumps) -> Result<()> {
+        require!(epoch_duration > 0, AmmError::InvalidAmount);
+
+        self.vault.set_inner(FeeShareVault {
+            gov_mint: self.gov_mint.key(),
+            total_staked: 0,
+            eligible_stake: 0,
+            current_epoch: 0,
+            epoch_start: Clock::get()?.unix_timestamp,
+            epoch_duration,
+            bump: bumps.vault,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct StakeGov<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        has_one = gov_mint,
+        seeds = [b"fee_share", gov_mint.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, FeeShareVault>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"fee_share_stake", vault.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + FeeShareStake::INIT_SPACE
+    )]
+    pub stake: Box<Account<'info, FeeShareStake>>,
+    pub gov_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = gov_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = gov_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub stake_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> StakeGov<'info> {
+    pub fn stake_gov(&mut self, amount: u64, bumps: &StakeGovBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        self.init_stake(bumps);
+
+        let cpi_accounts = TransferChecked {
+            from: self.user_ata.to_account_info(),
+            to: self.stake_vault.to_account_info(),
+            mint: self.gov_mint.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, self.gov_mint.decimals)?;
+
+        let staked = self.stake.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.stake.apply_change(self.vault.current_epoch, staked);
+        self.vault.total_staked = self.vault.total_staked.checked_add(amount).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Unstaking mid-epoch also lowers the stake eligible for the running epoch.
+    pub fn unstake_gov(&mut self, amount: u64, bumps: &StakeGovBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        self.init_stake(bumps);
+        require!(amount <= self.stake.amount, AmmError::InsufficientBalance);
+
+        let gov_mint = self.gov_mint.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"fee_share", gov_mint.as_ref(), &[self.vault.bump]]];
+        let cpi_accounts = TransferChecked {
+            from: self.stake_vault.to_account_info(),
+            to: self.user_ata.to_account_info(),
+            mint: self.gov_mint.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, self.gov_mint.decimals)?;
+
+        let staked = self.stake.amount - amount;
+        let dropped = self.stake.apply_change(self.vault.current_epoch, staked);
+        self.vault.eligible_stake = self.vault.eligible_stake.checked_sub(dropped).ok_or(AmmError::Underflow)?;
+        self.vault.total_staked -= amount;
+        Ok(())
+    }
+
+    fn init_stake(&mut self, bumps: &StakeGovBumps) {
+        if self.stake.vault == Pubkey::default() {
+            self.stake.set_inner(FeeShareStake {
+                vault: self.vault.key(),
+                owner: self.user.key(),
+                amount: 0,
+                checkpoint_epoch: self.vault.current_epoch,
+                checkpoint_min: 0,
+                balance_before: 0,
+                previous_checkpoint_epoch: 0,
+                bump: bumps.stake,
+            });
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseFeeEpoch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"fee_share", vault.gov_mint.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, FeeShareVault>>,
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"fee_epoch", vault.key().as_ref(), vault.current_epoch.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + FeeEpoch::INIT_SPACE
+    )]
+    pub fee_epoch: Box<Account<'info, FeeEpoch>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseFeeEpoch<'info> {
+    // Permissionless: snapshots the running epoch and starts the next one, in which
+    // everything currently staked is eligible.
+    pub fn close_fee_epoch(&mut self, bumps: &CloseFeeEpochBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let epoch_end = self.vault.epoch_start.checked_add(self.vault.epoch_duration).ok_or(AmmError::Overflow)?;
+        require!(now >= epoch_end, AmmError::EpochNotOver);
+
+        self.fee_epoch.set_inner(FeeEpoch {
+            vault: self.vault.key(),
+            epoch: self.vault.current_epoch,
+            eligible_stake: self.vault.eligible_stake,
+            bump: bumps.fee_epoch,
+        });
+
+        self.vault.current_epoch += 1;
+        self.vault.epoch_start = now;
+        self.vault.eligible_stake = self.vault.total_staked;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FundFeeEpoch<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        seeds = [b"fee_epoch", fee_epoch.vault.as_ref(), fee_epoch.epoch.to_le_bytes().as_ref()],
+        bump = fee_epoch.bump
+    )]
+    pub fee_epoch: Box<Account<'info, FeeEpoch>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        seeds = [b"epoch_reward", fee_epoch.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        space = 8 + EpochReward::INIT_SPACE
+    )]
+    pub epoch_reward: Box<Account<'info, EpochReward>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder,
+        token::token_program = reward_token_program
+    )]
+    pub funder_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = funder,
+        associated_token::mint = reward_mint,
+        associated_token::authority = epoch_reward,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FundFeeEpoch<'info> {
+    // Pays collected protocol fees into a closed epoch; typically the admin forwarding
+    // what collect_fees swept to the treasury.
+    pub fn fund_fee_epoch(&mut self, amount: u64, bumps: &FundFeeEpochBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        require!(self.fee_epoch.eligible_stake != 0, AmmError::ZeroBalance);
+        require!(self.epoch_reward.claimed == 0, AmmError::RewardsAlreadyClaimed);
+
+        if self.epoch_reward.fee_epoch == Pubkey::default() {
+            self.epoch_reward.fee_epoch = self.fee_epoch.key();
+            self.epoch_reward.mint = self.reward_mint.key();
+            self.epoch_reward.bump = bumps.epoch_reward;
+        }
+
+        let cpi_accounts = TransferChecked {
+            from: self.funder_ata.to_account_info(),
+            to: self.reward_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            authority: self.funder.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.reward_token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        // only what reached the vault can be claimed
+        let before = self.reward_vault.amount;
+        self.reward_vault.reload()?;
+        let received = self.reward_vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.epoch_reward.amount = self.epoch_reward.amount.checked_add(received).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct ClaimFees<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        has_one = vault,
+        constraint = stake.owner == user.key() @ AmmError::Unauthorized,
+        seeds = [b"fee_share_stake", vault.key().as_ref(), user.key().as_ref()],
+        bump = stake.bump
+    )]
+    pub stake: Box<Account<'info, FeeShareStake>>,
+    #[account(
+        seeds = [b"fee_share", vault.gov_mint.as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Box<Account<'info, FeeShareVault>>,
+    #[account(
+        has_one = vault,
+        seeds = [b"fee_epoch", vault.key().as_ref(), epoch.to_le_bytes().as_ref()],
+        bump = fee_epoch.bump
+    )]
+    pub fee_epoch: Box<Account<'info, FeeEpoch>>,
+    #[account(
+        mut,
+        has_one = fee_epoch,
+        constraint = epoch_reward.mint == reward_mint.key() @ AmmError::InvalidToken,
+        seeds = [b"epoch_reward", fee_epoch.key().as_ref(), reward_mint.key().as_ref()],
+        bump = epoch_reward.bump
+    )]
+    pub epoch_reward: Box<Account<'info, EpochReward>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"fee_claim", epoch_reward.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + FeeClaim::INIT_SPACE
+    )]
+    pub fee_claim: Box<Account<'info, FeeClaim>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+        associated_token::token_program = reward_token_program
+    )]
+    pub user_reward_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = epoch_reward,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimFees<'info> {
+    pub fn claim_fees(&mut self, epoch: u64, bumps: &ClaimFeesBumps) -> Result<()> {
+        let eligible = self.stake.eligible_stake(epoch)?;
+        let amount = to_u64(mul_div(
+            self.epoch_reward.amount as u128,
+            eligible as u128,
+            self.fee_epoch.eligible_stake as u128,
+        )?)?;
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let fee_epoch = self.fee_epoch.key();
+        let reward_mint = self.reward_mint.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"epoch_reward",
+            fee_epoch.as_ref(),
+            reward_mint.as_ref(),
+            &[self.epoch_reward.bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            from: self.reward_vault.to_account_info(),
+            to: self.user_reward_ata.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            authority: self.epoch_reward.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.reward_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        self.epoch_reward.claimed = self.epoch_reward.claimed.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.fee_claim.set_inner(FeeClaim {
+            epoch_reward: self.epoch_reward.key(),
+            owner: self.user.key(),
+            amount,
+            bump: bumps.fee_claim,
+        });
+        Ok(())
+    }
+}
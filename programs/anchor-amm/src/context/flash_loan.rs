@@ -0,0 +1,218 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{BPS_DENOMINATOR, FLASH_LOAN_FEE_BPS, PAUSE_SWAP};
+use crate::events::FlashLoanRepaid;
+use crate::math::{mul_div_ceil, to_u64};
+use crate::state::{Config, GlobalConfig};
+use crate::utils::{emit_event_cpi, require_followed_by, transfer_tokens};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: address is checked against the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FlashLoan<'info> {
+    pub fn flash_loan_begin(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAP)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let vault_balance = match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount,
+        };
+        require!(amount <= vault_balance, AmmError::InsufficientBalance);
+
+        require_followed_by(
+            &self.instructions.to_account_info(),
+            &crate::instruction::FlashLoanEnd::DISCRIMINATOR,
+            &self.config.key(),
+            AmmError::FlashLoanNotRepaid,
+        )?;
+
+        self.config.flash_loan_active = true;
+        self.config.flash_loan_is_x = is_x;
+        self.config.flash_loan_amount = amount;
+        self.config.flash_loan_balance = vault_balance;
+
+        self.lend_tokens(is_x, amount, remaining_accounts)
+    }
+
+    pub fn flash_loan_end(&mut self, remaining_accounts: &[AccountInfo<'info>], bumps: &FlashLoanBumps) -> Result<()> {
+        require!(self.config.flash_loan_active, AmmError::NoActiveFlashLoan);
+
+        let is_x = self.config.flash_loan_is_x;
+        let amount = self.config.flash_loan_amount;
+        let fee = to_u64(mul_div_ceil(amount as u128, FLASH_LOAN_FEE_BPS as u128, BPS_DENOMINATOR)?)?;
+        let repayment = amount.checked_add(fee).ok_or(AmmError::Overflow)?;
+
+        self.repay_tokens(is_x, repayment, remaining_accounts)?;
+
+        let required = self.config.flash_loan_balance.checked_add(fee).ok_or(AmmError::Overflow)?;
+        let (vault, mint) = match is_x {
+            true => (&mut self.vault_x, self.mint_x.key()),
+            false => (&mut self.vault_y, self.mint_y.key()),
+        };
+        vault.reload()?;
+        require!(vault.amount >= required, AmmError::FlashLoanNotRepaid);
+
+        // the fee stays in the vault and accrues to LPs
+        let config: &mut Config = &mut self.config;
+        let fees = match is_x {
+            true => &mut config.fees_x,
+            false => &mut config.fees_y,
+        };
+        *fees = fees.checked_add(fee as u128).ok_or(AmmError::Overflow)?;
+        config.credit_reserve(is_x, fee)?;
+        config.accrue_position_fee(is_x, fee)?;
+
+        config.flash_loan_active = false;
+        config.flash_loan_amount = 0;
+        config.flash_loan_balance = 0;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, FlashLoanRepaid {
+            config: self.config.key(),
+            user: self.user.key(),
+            mint,
+            amount,
+            fee,
+        })?;
+
+        Ok(())
+    }
+
+    fn lend_tokens(&self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_ata_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_ata_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+
+    fn repay_tokens(&self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_ata_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_ata_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
@@ -0,0 +1,280 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::PAUSE_SWAP;
+use crate::curve::CurveCalculator;
+use crate::events::Swapped;
+use crate::state::{AllowlistEntry, Config, GlobalConfig, Observations, Oracle, Volatility};
+use crate::utils::{bps_of, emit_event_cpi, require_followed_by, require_sole_swapper, transfer_tokens};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x
+    )]
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y
+    )]
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    /// CHECK: address is checked against the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = user)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: validated against the pool's configured oracle in Config::oracle_price
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated against the pool's secondary oracle in Config::oracle_price
+    pub secondary_price_feed: Option<UncheckedAccount<'info>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> FlashSwap<'info> {
+    // Sends the output up front; flash_swap_end must later pay in enough of the
+    // input token for a regular swap of that size to produce at least this output.
+    pub fn flash_swap_begin(&mut self, is_x: bool, amount_out: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAP)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(self.config.batch_window_secs == 0, AmmError::BatchModePool);
+        // the hook accounts only travel with swap, so a hooked pool is never flash-swapped
+        require!(self.config.hook_program.is_none(), AmmError::HookedPoolRequiresSwap);
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
+        self.config.check_product_curve()?;
+        require!(amount_out != 0, AmmError::InvalidAmount);
+        if self.config.sandwich_guard {
+            require_sole_swapper(&self.instructions.to_account_info(), &self.config.key(), &self.user.key())?;
+        }
+
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        let reserve_out = match is_x {
+            true => reserve_y,
+            false => reserve_x,
+        };
+        require!(amount_out < reserve_out, AmmError::InsufficientBalance);
+
+        self.oracle.update(&mut *self.observations.load_mut()?, reserve_x, reserve_y)?;
+
+        require_followed_by(
+            &self.instructions.to_account_info(),
+            &crate::instruction::FlashSwapEnd::DISCRIMINATOR,
+            &self.config.key(),
+            AmmError::FlashSwapNotRepaid,
+        )?;
+
+        self.config.flash_swap_active = true;
+        self.config.flash_swap_is_x = is_x;
+        self.config.flash_swap_amount_out = amount_out;
+        self.config.flash_swap_balance_x = self.vault_x.amount;
+        self.config.flash_swap_balance_y = self.vault_y.amount;
+
+        self.withdraw_tokens(!is_x, amount_out, remaining_accounts)
+    }
+
+    pub fn flash_swap_end(&mut self, amount_in: u64, remaining_accounts: &[AccountInfo<'info>], bumps: &FlashSwapBumps) -> Result<()> {
+        require!(self.config.flash_swap_active, AmmError::NoActiveFlashSwap);
+
+        let is_x = self.config.flash_swap_is_x;
+        let amount_out = self.config.flash_swap_amount_out;
+        let (balance_x, balance_y) = (self.config.flash_swap_balance_x, self.config.flash_swap_balance_y);
+
+        if amount_in > 0 {
+            self.deposit_tokens(is_x, amount_in, remaining_accounts)?;
+        }
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+
+        // whatever reached the input vault since begin counts as the swap input
+        let (reserve_in, reserve_out, balance_before, balance_in) = match is_x {
+            true => (self.config.reserve_x, self.config.reserve_y, balance_x, self.vault_x.amount),
+            false => (self.config.reserve_y, self.config.reserve_x, balance_y, self.vault_y.amount),
+        };
+        let received = balance_in.checked_sub(balance_before).ok_or(AmmError::FlashSwapNotRepaid)?;
+
+        let oracle_price = self.config.oracle_price(self.price_feed.as_deref(), self.secondary_price_feed.as_deref(), self.mint_x.decimals, self.mint_y.decimals)?;
+        let fee = self.config.current_fee(self.volatility.as_deref(), is_x, oracle_price)?;
+        let owed_out = self.config.curve(is_x, oracle_price)?.amount_out(reserve_in, reserve_out, received, fee)?;
+        require!(owed_out >= amount_out, AmmError::FlashSwapNotRepaid);
+
+        let fee_amount = bps_of(received, fee)?;
+        let (reserve_x_before, reserve_y_before) = (self.config.reserve_x, self.config.reserve_y);
+        let k_before = self.config.k();
+        self.config.record_swap(is_x, received, fee_amount)?;
+        self.config.credit_reserve(is_x, received)?;
+        self.config.debit_reserve(!is_x, amount_out)?;
+        self.config.accrue_position_fee(is_x, fee_amount)?;
+        self.config.check_invariant(k_before, self.vault_x.amount, self.vault_y.amount)?;
+        self.config.check_price_impact(reserve_x_before, reserve_y_before)?;
+        self.config.check_oracle_price(oracle_price, is_x, received, amount_out)?;
+
+        self.config.flash_swap_active = false;
+        self.config.flash_swap_amount_out = 0;
+        self.config.flash_swap_balance_x = 0;
+        self.config.flash_swap_balance_y = 0;
+
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        self.config.update_volatility(self.volatility.as_deref_mut())?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: received,
+            amount_out,
+            fee: fee_amount,
+            protocol_fee: 0,
+            referral_fee: 0,
+            reserve_x,
+            reserve_y,
+        })?;
+
+        Ok(())
+    }
+
+    fn deposit_tokens(&self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.user_ata_x.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.user_ata_y.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+
+    fn withdraw_tokens(&self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (from, to, mint, decimals) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_ata_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_ata_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+            ),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
@@ -0,0 +1,414 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::math::{mul_div, to_u64};
+use crate::state::{Epoch, Farm, Gauge, GaugeController, GaugeEpoch, GaugeVote};
+use crate::utils::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct CreateGaugeController<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub governance_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"gauge_controller", governance_mint.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        space = 8 + GaugeController::INIT_SPACE
+    )]
+    pub controller: Box<Account<'info, GaugeController>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = reward_mint,
+        associated_token::authority = controller,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = governance_mint,
+        associated_token::authority = controller,
+        associated_token::token_program = governance_token_program
+    )]
+    pub vote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub governance_token_program: Interface<'info, TokenInterface>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateGaugeController<'info> {
+    // Rewards are funded by transferring straight into the controller's reward vault.
+    pub fn create_gauge_controller(&mut self, epoch_duration: i64, emission_per_epoch: u64, bumps: &CreateGaugeControllerBumps) -> Result<()> {
+        require!(epoch_duration > 0 && emission_per_epoch != 0, AmmError::InvalidAmount);
+
+        self.controller.set_inner(GaugeController {
+            authority: self.authority.key(),
+            governance_mint: self.governance_mint.key(),
+            reward_mint: self.reward_mint.key(),
+            genesis: Clock::get()?.unix_timestamp,
+            epoch_duration,
+            emission_per_epoch,
+            pending_distribution: 0,
+            bump: bumps.controller,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct AddGauge<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ AmmError::Unauthorized,
+        seeds = [b"gauge_controller", controller.governance_mint.as_ref(), controller.reward_mint.as_ref()],
+        bump = controller.bump
+    )]
+    pub controller: Box<Account<'info, GaugeController>>,
+    #[account(
+        constraint = farm.reward_mint == controller.reward_mint @ AmmError::InvalidGauge,
+        seeds = [b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"gauge", controller.key().as_ref(), farm.key().as_ref()],
+        bump,
+        space = 8 + Gauge::INIT_SPACE
+    )]
+    pub gauge: Box<Account<'info, Gauge>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddGauge<'info> {
+    pub fn add_gauge(&mut self, bumps: &AddGaugeBumps) -> Result<()> {
+        self.gauge.set_inner(Gauge {
+            controller: self.controller.key(),
+            farm: self.farm.key(),
+            bump: bumps.gauge,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct VoteGauge<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(
+        has_one = governance_mint,
+        seeds = [b"gauge_controller", controller.governance_mint.as_ref(), controller.reward_mint.as_ref()],
+        bump = controller.bump
+    )]
+    pub controller: Box<Account<'info, GaugeController>>,
+    pub governance_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = controller,
+        seeds = [b"gauge", controller.key().as_ref(), gauge.farm.as_ref()],
+        bump = gauge.bump
+    )]
+    pub gauge: Box<Account<'info, Gauge>>,
+    #[account(
+        init_if_needed,
+        payer = voter,
+        seeds = [b"epoch", controller.key().as_ref(), epoch.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + Epoch::INIT_SPACE
+    )]
+    pub epoch_state: Box<Account<'info, Epoch>>,
+    #[account(
+        init_if_needed,
+        payer = voter,
+        seeds = [b"gauge_epoch", gauge.key().as_ref(), epoch.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + GaugeEpoch::INIT_SPACE
+    )]
+    pub gauge_epoch: Box<Account<'info, GaugeEpoch>>,
+    #[account(
+        init_if_needed,
+        payer = voter,
+        seeds = [b"gauge_vote", gauge.key().as_ref(), voter.key().as_ref(), epoch.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + GaugeVote::INIT_SPACE
+    )]
+    pub vote: Box<Account<'info, GaugeVote>>,
+    #[account(
+        mut,
+        token::mint = governance_mint,
+        token::authority = voter,
+        token::token_program = governance_token_program
+    )]
+    pub voter_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = governance_mint,
+        associated_token::authority = controller,
+        associated_token::token_program = governance_token_program
+    )]
+    pub vote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub governance_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> VoteGauge<'info> {
+    // Votes are weighted by governance tokens escrowed until the epoch ends, so the
+    // same tokens can't be counted twice.
+    pub fn vote_gauge(&mut self, epoch: u64, amount: u64, bumps: &VoteGaugeBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(epoch == self.controller.current_epoch(now), AmmError::InvalidEpoch);
+
+        let cpi_accounts = TransferChecked {
+            from: self.voter_ata.to_account_info(),
+            to: self.vote_vault.to_account_info(),
+            mint: self.governance_mint.to_account_info(),
+            authority: self.voter.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.governance_token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, self.governance_mint.decimals)?;
+
+        if self.epoch_state.controller == Pubkey::default() {
+            self.epoch_state.set_inner(Epoch {
+                controller: self.controller.key(),
+                epoch,
+                total_votes: 0,
+                finalized: false,
+                emission: 0,
+                bump: bumps.epoch_state,
+            });
+        }
+        if self.gauge_epoch.gauge == Pubkey::default() {
+            self.gauge_epoch.set_inner(GaugeEpoch {
+                gauge: self.gauge.key(),
+                epoch,
+                votes: 0,
+                distributed: false,
+                bump: bumps.gauge_epoch,
+            });
+        }
+        if self.vote.voter == Pubkey::default() {
+            self.vote.set_inner(GaugeVote {
+                voter: self.voter.key(),
+                gauge: self.gauge.key(),
+                epoch,
+                amount: 0,
+                bump: bumps.vote,
+            });
+        }
+
+        self.epoch_state.total_votes = self.epoch_state.total_votes.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.gauge_epoch.votes = self.gauge_epoch.votes.checked_add(amount).ok_or(AmmError::Overflow)?;
+        self.vote.amount = self.vote.amount.checked_add(amount).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct FinalizeEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [b"gauge_controller", controller.governance_mint.as_ref(), controller.reward_mint.as_ref()],
+        bump = controller.bump
+    )]
+    pub controller: Box<Account<'info, GaugeController>>,
+    #[account(
+        mut,
+        has_one = controller,
+        seeds = [b"epoch", controller.key().as_ref(), epoch_state.epoch.to_le_bytes().as_ref()],
+        bump = epoch_state.bump
+    )]
+    pub epoch_state: Box<Account<'info, Epoch>>,
+    #[account(
+        associated_token::mint = controller.reward_mint,
+        associated_token::authority = controller,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FinalizeEpoch<'info> {
+    // Permissionless: once the epoch is over its emission is fixed out of the unreserved vault balance.
+    pub fn finalize_epoch(&mut self) -> Result<()> {
+        require!(!self.epoch_state.finalized, AmmError::NothingToExecute);
+        let epoch_end = self.controller.epoch_end(self.epoch_state.epoch)?;
+        require!(Clock::get()?.unix_timestamp >= epoch_end, AmmError::EpochNotEnded);
+
+        let available = self.reward_vault.amount.saturating_sub(self.controller.pending_distribution);
+        let emission = match self.epoch_state.total_votes {
+            0 => 0,
+            _ => self.controller.emission_per_epoch.min(available),
+        };
+
+        self.controller.pending_distribution = self.controller.pending_distribution.checked_add(emission).ok_or(AmmError::Overflow)?;
+        self.epoch_state.emission = emission;
+        self.epoch_state.finalized = true;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct DistributeGauge<'info> {
+    #[account(
+        mut,
+        has_one = reward_mint,
+        seeds = [b"gauge_controller", controller.governance_mint.as_ref(), controller.reward_mint.as_ref()],
+        bump = controller.bump
+    )]
+    pub controller: Box<Account<'info, GaugeController>>,
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = controller,
+        seeds = [b"epoch", controller.key().as_ref(), epoch_state.epoch.to_le_bytes().as_ref()],
+        bump = epoch_state.bump
+    )]
+    pub epoch_state: Box<Account<'info, Epoch>>,
+    #[account(
+        has_one = controller,
+        has_one = farm,
+        seeds = [b"gauge", controller.key().as_ref(), farm.key().as_ref()],
+        bump = gauge.bump
+    )]
+    pub gauge: Box<Account<'info, Gauge>>,
+    #[account(
+        mut,
+        has_one = gauge,
+        seeds = [b"gauge_epoch", gauge.key().as_ref(), epoch_state.epoch.to_le_bytes().as_ref()],
+        bump = gauge_epoch.bump
+    )]
+    pub gauge_epoch: Box<Account<'info, GaugeEpoch>>,
+    #[account(
+        mut,
+        has_one = reward_mint,
+        seeds = [b"farm", farm.config.as_ref(), farm.reward_mint.as_ref()],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = controller,
+        associated_token::token_program = reward_token_program
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = farm,
+        associated_token::token_program = reward_token_program
+    )]
+    pub farm_reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> DistributeGauge<'info> {
+    // Permissionless crank: pays a gauge its vote-weighted share of a finalized epoch's emission.
+    pub fn distribute_gauge(&mut self) -> Result<()> {
+        require!(self.epoch_state.finalized, AmmError::EpochNotFinalized);
+        require!(!self.gauge_epoch.distributed, AmmError::NothingToExecute);
+        self.gauge_epoch.distributed = true;
+
+        let share = to_u64(mul_div(
+            self.epoch_state.emission as u128,
+            self.gauge_epoch.votes as u128,
+            self.epoch_state.total_votes as u128,
+        )?)?;
+        if share == 0 {
+            return Ok(());
+        }
+
+        self.farm.update(Clock::get()?.unix_timestamp)?;
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"gauge_controller",
+            self.controller.governance_mint.as_ref(),
+            self.controller.reward_mint.as_ref(),
+            &[self.controller.bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            from: self.reward_vault.to_account_info(),
+            to: self.farm_reward_vault.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            authority: self.controller.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.reward_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, share, self.reward_mint.decimals)?;
+
+        let before = self.farm_reward_vault.amount;
+        self.farm_reward_vault.reload()?;
+        let received = self.farm_reward_vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.farm.unallocated_rewards = self.farm.unallocated_rewards.checked_add(received).ok_or(AmmError::Overflow)?;
+        self.controller.pending_distribution = self.controller.pending_distribution.checked_sub(share).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct WithdrawGaugeVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(
+        has_one = governance_mint,
+        seeds = [b"gauge_controller", controller.governance_mint.as_ref(), controller.reward_mint.as_ref()],
+        bump = controller.bump
+    )]
+    pub controller: Box<Account<'info, GaugeController>>,
+    pub governance_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        close = voter,
+        has_one = voter,
+        seeds = [b"gauge_vote", vote.gauge.as_ref(), voter.key().as_ref(), vote.epoch.to_le_bytes().as_ref()],
+        bump = vote.bump
+    )]
+    pub vote: Box<Account<'info, GaugeVote>>,
+    #[account(
+        mut,
+        token::mint = governance_mint,
+        token::authority = voter,
+        token::token_program = governance_token_program
+    )]
+    pub voter_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = governance_mint,
+        associated_token::authority = controller,
+        associated_token::token_program = governance_token_program
+    )]
+    pub vote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub governance_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawGaugeVote<'info> {
+    pub fn withdraw_gauge_vote(&mut self) -> Result<()> {
+        let epoch_end = self.controller.epoch_end(self.vote.epoch)?;
+        require!(Clock::get()?.unix_timestamp >= epoch_end, AmmError::EpochNotEnded);
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"gauge_controller",
+            self.controller.governance_mint.as_ref(),
+            self.controller.reward_mint.as_ref(),
+            &[self.controller.bump],
+        ]];
+        let cpi_accounts = TransferChecked {
+            from: self.vote_vault.to_account_info(),
+            to: self.voter_ata.to_account_info(),
+            mint: self.governance_mint.to_account_info(),
+            authority: self.controller.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.governance_token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, self.vote.amount, self.governance_mint.decimals)
+    }
+}
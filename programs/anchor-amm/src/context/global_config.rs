@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::{BPS_DENOMINATOR, HOLDING_TIER_COUNT, MAX_FEE_SHARE_BPS, VOLUME_TIER_COUNT};
+use crate::program::AnchorAmm;
+use crate::state::{FeeWaiver, GlobalConfig};
+
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"global_config"],
+        bump,
+        space = 8 + GlobalConfig::INIT_SPACE
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ AmmError::Unauthorized)]
+    pub program: Program<'info, AnchorAmm>,
+    // only the upgrade authority can claim the protocol admin role
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ AmmError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeGlobalConfig<'info> {
+    pub fn initialize_global_config(&mut self, treasury: Pubkey, default_protocol_fee_bps: u16, pool_creation_fee: u64, bumps: &InitializeGlobalConfigBumps) -> Result<()> {
+        require!(default_protocol_fee_bps <= MAX_FEE_SHARE_BPS, AmmError::InvalidFee);
+
+        self.global_config.set_inner(GlobalConfig {
+            admin: self.admin.key(),
+            treasury,
+            default_protocol_fee_bps,
+            pool_creation_fee,
+            paused: false,
+            pool_count: 0,
+            bump: bumps.global_config,
+            discount_mint: Pubkey::default(),
+            holding_thresholds: [0; HOLDING_TIER_COUNT],
+            holding_discount_bps: [0; HOLDING_TIER_COUNT],
+            volume_discount_bps: [0; VOLUME_TIER_COUNT],
+            allow_unsafe_extensions: false,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+impl<'info> UpdateGlobalConfig<'info> {
+    pub fn update_global_config(&mut self, admin: Pubkey, treasury: Pubkey, default_protocol_fee_bps: u16, pool_creation_fee: u64) -> Result<()> {
+        require!(default_protocol_fee_bps <= MAX_FEE_SHARE_BPS, AmmError::InvalidFee);
+
+        self.global_config.admin = admin;
+        self.global_config.treasury = treasury;
+        self.global_config.default_protocol_fee_bps = default_protocol_fee_bps;
+        self.global_config.pool_creation_fee = pool_creation_fee;
+        Ok(())
+    }
+
+    pub fn set_global_pause(&mut self, paused: bool) -> Result<()> {
+        self.global_config.paused = paused;
+        Ok(())
+    }
+
+    // Only affects pools initialized afterwards; existing pools keep their mints.
+    pub fn set_allow_unsafe_extensions(&mut self, allowed: bool) -> Result<()> {
+        self.global_config.allow_unsafe_extensions = allowed;
+        Ok(())
+    }
+
+    pub fn set_fee_discounts(
+        &mut self,
+        discount_mint: Pubkey,
+        holding_thresholds: [u64; HOLDING_TIER_COUNT],
+        holding_discount_bps: [u16; HOLDING_TIER_COUNT],
+        volume_discount_bps: [u16; VOLUME_TIER_COUNT],
+    ) -> Result<()> {
+        require!(
+            holding_discount_bps.iter().chain(volume_discount_bps.iter()).all(|discount| *discount as u128 <= BPS_DENOMINATOR),
+            AmmError::InvalidFee
+        );
+
+        self.global_config.discount_mint = discount_mint;
+        self.global_config.holding_thresholds = holding_thresholds;
+        self.global_config.holding_discount_bps = holding_discount_bps;
+        self.global_config.volume_discount_bps = volume_discount_bps;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct AddFeeWaiver<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"fee_waiver", creator.as_ref()],
+        bump,
+        space = 8 + FeeWaiver::INIT_SPACE
+    )]
+    pub fee_waiver: Account<'info, FeeWaiver>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddFeeWaiver<'info> {
+    pub fn add_fee_waiver(&mut self, creator: Pubkey, bumps: &AddFeeWaiverBumps) -> Result<()> {
+        self.fee_waiver.set_inner(FeeWaiver {
+            creator,
+            bump: bumps.fee_waiver,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFeeWaiver<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"fee_waiver", fee_waiver.creator.as_ref()],
+        bump = fee_waiver.bump
+    )]
+    pub fee_waiver: Account<'info, FeeWaiver>,
+}
+
+impl<'info> RemoveFeeWaiver<'info> {
+    pub fn remove_fee_waiver(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::state::{GlobalConfig, HookRegistration};
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct RegisterHook<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"hook", program_id.as_ref()],
+        bump,
+        space = 8 + HookRegistration::INIT_SPACE
+    )]
+    pub hook_registration: Account<'info, HookRegistration>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterHook<'info> {
+    pub fn register_hook(&mut self, program_id: Pubkey, bumps: &RegisterHookBumps) -> Result<()> {
+        self.hook_registration.set_inner(HookRegistration {
+            program_id,
+            bump: bumps.hook_registration,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct DeregisterHook<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        has_one = admin @ AmmError::Unauthorized,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"hook", hook_registration.program_id.as_ref()],
+        bump = hook_registration.bump
+    )]
+    pub hook_registration: Account<'info, HookRegistration>,
+}
+
+impl<'info> DeregisterHook<'info> {
+    pub fn deregister_hook(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
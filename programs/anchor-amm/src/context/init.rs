@@ -1,66 +1,404 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenInterface, TokenAccount}};
+use anchor_lang::system_program::{create_account, transfer, CreateAccount, Transfer};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{initialize_mint2, InitializeMint2, Mint, TokenInterface, TokenAccount}};
+use anchor_spl::token_2022::spl_token_2022::{self, extension::ExtensionType, state::Mint as MintState};
+use anchor_spl::token_2022_extensions::{
+    metadata_pointer_initialize, mint_close_authority_initialize, non_transferable_mint_initialize, token_metadata_initialize,
+    MetadataPointerInitialize, MintCloseAuthorityInitialize, NonTransferableMintInitialize, TokenMetadataInitialize,
+};
 
-use crate::state::Config;
+use crate::amm_error::AmmError;
+use crate::constants::{CONFIG_RESERVED_BYTES, CONFIG_VERSION, FEE_TIERS, MAX_AMP, MAX_FEE_SHARE_BPS, REGISTRY_PAGE_SIZE, TOTAL_WEIGHT, VOLUME_TIER_COUNT};
+use crate::events::PoolInitialized;
+use crate::price::Price;
+use crate::state::{Config, CurveType, FeeWaiver, GlobalConfig, Observations, Oracle, OracleType, PoolRecord, RegistryPage};
+use crate::utils::{check_mint_extensions, emit_event_cpi, has_transfer_fee};
 
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(seed: u64)]
+#[instruction(fee_tier: u16)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
-    pub mint_x: InterfaceAccount<'info, Mint>,
-    pub mint_y: InterfaceAccount<'info, Mint>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        constraint = mint_x.key() < mint_y.key() @ AmmError::InvalidMintOrder
+    )]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         init,
         payer = initializer,
-        seeds = [b"config", seed.to_le_bytes().as_ref()],
+        seeds = [b"config", mint_x.key().as_ref(), mint_y.key().as_ref(), fee_tier.to_le_bytes().as_ref()],
         bump,
         space = 8 + Config::INIT_SPACE
     )]
     pub config: Account<'info, Config>,
     #[account(
-        init,
+        mut,
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        init_if_needed,
         payer = initializer,
-        seeds = [b"lp_mint", config.key().as_ref()],
+        seeds = [b"registry", (global_config.pool_count / REGISTRY_PAGE_SIZE as u64).to_le_bytes().as_ref()],
         bump,
-        mint::decimals = 6,
-        mint::authority = config
+        space = 8 + RegistryPage::INIT_SPACE
+    )]
+    pub registry_page: Box<Account<'info, RegistryPage>>,
+    #[account(
+        mut,
+        address = global_config.treasury @ AmmError::InvalidTreasury
+    )]
+    pub protocol_treasury: SystemAccount<'info>,
+    #[account(
+        seeds = [b"fee_waiver", initializer.key().as_ref()],
+        bump = fee_waiver.bump
+    )]
+    pub fee_waiver: Option<Account<'info, FeeWaiver>>,
+    /// CHECK: created and initialized as the LP mint in the handler
+    #[account(
+        mut,
+        seeds = [b"lp", config.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump
     )]
-    pub lp_mint: InterfaceAccount<'info, Mint>,
+    pub treasury: SystemAccount<'info>,
     #[account(
         init,
         payer = initializer,
         associated_token::mint = mint_x,
-        associated_token::authority = config
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
     )]
-    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    pub protocol_fee_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         init,
         payer = initializer,
         associated_token::mint = mint_y,
-        associated_token::authority = config
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
     )]
-    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    pub protocol_fee_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump,
+        space = 8 + Oracle::INIT_SPACE
+    )]
+    pub oracle: Account<'info, Oracle>,
+    #[account(
+        init,
+        payer = initializer,
+        seeds = [b"observations", config.key().as_ref()],
+        bump,
+        space = 8 + std::mem::size_of::<Observations>()
+    )]
+    pub observations: AccountLoader<'info, Observations>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    // the LP mint's program; each side of the pair may use either token program
     pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>
 
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LpMintConfig {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub non_transferable: bool,
+}
 
 impl<'info> Initialize<'info> {
-    pub fn init(&mut self, seed: u64, fee: u16, authority: Option<Pubkey>, bumps: &InitializeBumps) -> Result<()> {
+    pub fn init(
+        &mut self,
+        fee_tier: u16,
+        protocol_fee_bps: Option<u16>,
+        authority: Option<Pubkey>,
+        weight_x: u16,
+        weight_y: u16,
+        lp_token_2022: Option<LpMintConfig>,
+        position_mode: bool,
+        amp: u64,
+        bumps: &InitializeBumps,
+    ) -> Result<()> {
+        self.global_config.check_not_paused()?;
+        require!(FEE_TIERS.contains(&fee_tier), AmmError::InvalidFeeTier);
+        require!(
+            weight_x != 0 && weight_y != 0 && weight_x as u32 + weight_y as u32 == TOTAL_WEIGHT as u32,
+            AmmError::InvalidWeights
+        );
+        // a non-zero amp makes this a stable pool, which trades the pair near par
+        require!(amp <= MAX_AMP, AmmError::InvalidAmount);
+        require!(amp == 0 || weight_x == weight_y, AmmError::InvalidWeights);
+        let curve_type = match amp {
+            0 if weight_x != weight_y => CurveType::Weighted,
+            0 => CurveType::ConstantProduct,
+            _ => CurveType::Stable,
+        };
+        check_mint_extensions(&self.mint_x.to_account_info(), self.global_config.allow_unsafe_extensions)?;
+        check_mint_extensions(&self.mint_y.to_account_info(), self.global_config.allow_unsafe_extensions)?;
+        let fee_on_transfer = has_transfer_fee(&self.mint_x.to_account_info())? || has_transfer_fee(&self.mint_y.to_account_info())?;
+        let protocol_fee_bps = protocol_fee_bps.unwrap_or(self.global_config.default_protocol_fee_bps);
+        require!(protocol_fee_bps <= MAX_FEE_SHARE_BPS, AmmError::InvalidFee);
+        self.pay_creation_fee()?;
+        self.create_lp_mint(fee_tier, lp_token_2022, bumps)?;
+
         self.config.set_inner(Config{
-            seed,
+            fee_tier,
             authority,
+            pending_authority: None,
+            pending_change: None,
+            change_effective_at: 0,
             mint_x: self.mint_x.key(), 
             mint_y: self.mint_y.key(), 
+            lp_mint: self.lp_mint.key(),
             lp_bump: bumps.lp_mint,
             config_bump: bumps.config,
-            fee,
-            locked: false
+            treasury_bump: bumps.treasury,
+            reserve_x: 0,
+            reserve_y: 0,
+            fee: fee_tier,
+            weight_x,
+            weight_y,
+            lbp_start_time: 0,
+            lbp_end_time: 0,
+            lbp_start_weight_x: 0,
+            lbp_end_weight_x: 0,
+            protocol_fee_bps,
+            referral_fee_bps: 0,
+            dynamic_fee: false,
+            min_fee: fee_tier,
+            max_fee: fee_tier,
+            max_price_impact_bps: 0,
+            oracle_type: OracleType::None,
+            oracle_feed: [0; 32],
+            secondary_oracle_type: OracleType::None,
+            secondary_oracle_feed: [0; 32],
+            halt_on_stale_oracle: false,
+            pmm_enabled: false,
+            pmm_k_bps: 0,
+            max_oracle_deviation_bps: 0,
+            max_staleness_secs: 0,
+            paused: 0,
+            allowlist_deposits: false,
+            allowlist_swaps: false,
+            badge_mint: None,
+            flash_loan_active: false,
+            flash_loan_is_x: false,
+            flash_loan_amount: 0,
+            flash_loan_balance: 0,
+            flash_swap_active: false,
+            flash_swap_is_x: false,
+            flash_swap_amount_out: 0,
+            flash_swap_balance_x: 0,
+            flash_swap_balance_y: 0,
+            fees_x: 0,
+            fees_y: 0,
+            volume_x_in: 0,
+            volume_y_in: 0,
+            swap_count: 0,
+            version: CONFIG_VERSION,
+            withdraw_fee_bps: 0,
+            withdraw_fee_to_treasury: false,
+            jit_window_slots: 0,
+            jit_fee_bps: 0,
+            position_mode,
+            position_liquidity: 0,
+            fee_growth_x: 0,
+            fee_growth_y: 0,
+            unclaimed_fees_x: 0,
+            unclaimed_fees_y: 0,
+            volume_tiers: [0; VOLUME_TIER_COUNT],
+            hook_program: None,
+            curve_type,
+            amp,
+            fee_on_transfer,
+            rebasing: false,
+            sandwich_guard: false,
+            batch_window_secs: 0,
+            max_tvl: 0,
+            max_deposit_per_wallet: 0,
+            allowlist_ends_at: 0,
+            launch_pending: false,
+            launch_fee: 0,
+            launch_fee_starts_at: 0,
+            launch_fee_decay_secs: 0,
+            spread_max_bps: 0,
+            spread_target: Price(0),
+            admin_nft_mint: None,
+            reserved: [0; CONFIG_RESERVED_BYTES],
+        });
+
+        self.oracle.set_inner(Oracle {
+            config: self.config.key(),
+            price_x_cumulative: 0,
+            price_y_cumulative: 0,
+            last_timestamp: Clock::get()?.unix_timestamp,
+            bump: bumps.oracle,
+        });
+
+        let mut observations = self.observations.load_init()?;
+        observations.config = self.config.key();
+        observations.bump = bumps.observations;
+        drop(observations);
+
+        self.register_pool(fee_tier, curve_type, bumps)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, PoolInitialized {
+            config: self.config.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            lp_mint: self.lp_mint.key(),
+            fee_tier,
+            protocol_fee_bps,
+            weight_x,
+            weight_y,
+            authority,
+        })?;
+        Ok(())
+    }
+
+    fn register_pool(&mut self, fee_tier: u16, curve_type: CurveType, bumps: &InitializeBumps) -> Result<()> {
+        let page = self.global_config.pool_count / REGISTRY_PAGE_SIZE as u64;
+        if self.registry_page.pools.is_empty() {
+            self.registry_page.page = page;
+            self.registry_page.bump = bumps.registry_page;
+        }
 
+        self.registry_page.pools.push(PoolRecord {
+            config: self.config.key(),
+            mint_x: self.mint_x.key(),
+            mint_y: self.mint_y.key(),
+            fee_tier,
+            curve_type,
         });
+        self.global_config.pool_count = self.global_config.pool_count.checked_add(1).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Lamports paid to the protocol treasury to deter spam pools, unless the creator holds a waiver.
+    fn pay_creation_fee(&self) -> Result<()> {
+        let fee = self.global_config.pool_creation_fee;
+        if fee == 0 || self.fee_waiver.is_some() {
+            return Ok(());
+        }
+
+        let cpi_accounts = Transfer {
+            from: self.initializer.to_account_info(),
+            to: self.protocol_treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+        transfer(cpi_ctx, fee)
+    }
+
+    // Token-2022 LP mints carry their metadata in the mint itself through the
+    // metadata pointer extension, and can optionally be made non-transferable.
+    // They also name the config as close authority so close_pool can reclaim them.
+    fn create_lp_mint(&self, fee_tier: u16, lp_token_2022: Option<LpMintConfig>, bumps: &InitializeBumps) -> Result<()> {
+        let config_key = self.config.key();
+        let lp_seeds: &[&[&[u8]]; 1] = &[&[b"lp", config_key.as_ref(), &[bumps.lp_mint]]];
+        let fee_tier_bytes = fee_tier.to_le_bytes();
+        let mint_x = self.mint_x.key();
+        let mint_y = self.mint_y.key();
+        let config_seeds: &[&[&[u8]]; 1] = &[&[b"config", mint_x.as_ref(), mint_y.as_ref(), &fee_tier_bytes[..], &[bumps.config]]];
+
+        let lp_token_2022_program = self.token_program.key() == spl_token_2022::ID;
+        let mut extensions = vec![];
+        if lp_token_2022_program {
+            extensions.push(ExtensionType::MintCloseAuthority);
+        }
+        let mut metadata_len = 0;
+        if let Some(lp) = &lp_token_2022 {
+            require_keys_eq!(self.token_program.key(), spl_token_2022::ID, AmmError::InvalidTokenProgram);
+            extensions.push(ExtensionType::MetadataPointer);
+            if lp.non_transferable {
+                extensions.push(ExtensionType::NonTransferable);
+            }
+            // TLV header, update authority, mint, three length-prefixed strings and an empty additional metadata vec
+            metadata_len = 4 + 32 + 32 + 4 + lp.name.len() + 4 + lp.symbol.len() + 4 + lp.uri.len() + 4;
+        }
+
+        let space = ExtensionType::try_calculate_account_len::<MintState>(&extensions)?;
+        let cpi_accounts = CreateAccount {
+            from: self.initializer.to_account_info(),
+            to: self.lp_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.system_program.to_account_info(), cpi_accounts, lp_seeds);
+        create_account(cpi_ctx, Rent::get()?.minimum_balance(space + metadata_len), space as u64, self.token_program.key)?;
+
+        if lp_token_2022_program {
+            let cpi_accounts = MintCloseAuthorityInitialize {
+                token_program_id: self.token_program.to_account_info(),
+                mint: self.lp_mint.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+            mint_close_authority_initialize(cpi_ctx, Some(&config_key))?;
+        }
+
+        if let Some(lp) = &lp_token_2022 {
+            let cpi_accounts = MetadataPointerInitialize {
+                token_program_id: self.token_program.to_account_info(),
+                mint: self.lp_mint.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+            metadata_pointer_initialize(cpi_ctx, Some(config_key), Some(self.lp_mint.key()))?;
+
+            if lp.non_transferable {
+                let cpi_accounts = NonTransferableMintInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.lp_mint.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+                non_transferable_mint_initialize(cpi_ctx)?;
+            }
+        }
+
+        // bootstrap LP is sqrt(x * y), so its natural scale is the mean of the pair's decimals
+        let lp_decimals = (self.mint_x.decimals as u16 + self.mint_y.decimals as u16).div_ceil(2) as u8;
+        let cpi_accounts = InitializeMint2 {
+            mint: self.lp_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        initialize_mint2(cpi_ctx, lp_decimals, &config_key, None)?;
+
+        if let Some(lp) = lp_token_2022 {
+            let cpi_accounts = TokenMetadataInitialize {
+                token_program_id: self.token_program.to_account_info(),
+                mint: self.lp_mint.to_account_info(),
+                metadata: self.lp_mint.to_account_info(),
+                mint_authority: self.config.to_account_info(),
+                update_authority: self.config.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, config_seeds);
+            token_metadata_initialize(cpi_ctx, lp.name, lp.symbol, lp.uri)?;
+        }
+
         Ok(())
     }
 }
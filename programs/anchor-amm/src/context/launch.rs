@@ -0,0 +1,397 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{burn, mint_to, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::events::LaunchGraduated;
+use crate::math::{isqrt, mul_div, swap_amount_out, to_u64};
+use crate::state::{Config, CurveType, Launch};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct CreateLaunch<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"launch", config.key().as_ref()],
+        bump,
+        space = 8 + Launch::INIT_SPACE
+    )]
+    pub launch: Box<Account<'info, Launch>>,
+    #[account(
+        mut,
+        token::authority = creator
+    )]
+    pub creator_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint_x,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program_x
+    )]
+    pub launch_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint_y,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program_y
+    )]
+    pub launch_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLaunch<'info> {
+    // The pool's authority reserves its empty constant-product pool for the launch and
+    // puts base_amount of the base mint up for sale; deposits stay closed until it graduates.
+    pub fn create_launch(&mut self, base_is_x: bool, base_amount: u64, virtual_quote: u64, raise_target: u64, bumps: &CreateLaunchBumps) -> Result<()> {
+        self.config.check_authority(self.creator.key(), self.admin_nft_ata.as_deref())?;
+        require!(self.config.curve_type == CurveType::ConstantProduct && !self.config.pmm_enabled, AmmError::InvalidConfig);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        require!(
+            self.config.reserve_x == 0 && self.config.reserve_y == 0 && self.lp_mint.supply == 0,
+            AmmError::PoolNotEmpty
+        );
+        require!(base_amount != 0 && virtual_quote != 0 && raise_target != 0, AmmError::InvalidAmount);
+
+        let (to, mint, decimals, token_program) = match base_is_x {
+            true => (self.launch_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.launch_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+        require_keys_eq!(self.creator_base_ata.mint, mint.key(), AmmError::InvalidToken);
+
+        let cpi_accounts = TransferChecked {
+            from: self.creator_base_ata.to_account_info(),
+            to,
+            mint,
+            authority: self.creator.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+        transfer_tokens(cpi_ctx, base_amount, decimals)?;
+
+        let escrow = match base_is_x {
+            true => &mut self.launch_ata_x,
+            false => &mut self.launch_ata_y,
+        };
+        escrow.reload()?;
+
+        self.launch.set_inner(Launch {
+            creator: self.creator.key(),
+            config: self.config.key(),
+            base_is_x,
+            virtual_quote,
+            raise_target,
+            quote_raised: 0,
+            base_remaining: escrow.amount,
+            graduated: false,
+            bump: bumps.launch,
+        });
+        self.config.launch_pending = true;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BuyLaunch<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"launch", config.key().as_ref()],
+        bump = launch.bump
+    )]
+    pub launch: Box<Account<'info, Launch>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_x,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program_x
+    )]
+    pub buyer_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint_y,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program_y
+    )]
+    pub buyer_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program_x
+    )]
+    pub launch_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program_y
+    )]
+    pub launch_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BuyLaunch<'info> {
+    // Open until the raise target is reached; the buy that crosses it fills in full.
+    pub fn buy_launch(&mut self, quote_in: u64, min_base_out: u64) -> Result<()> {
+        require!(!self.launch.graduated && self.launch.quote_raised < self.launch.raise_target, AmmError::LaunchClosed);
+        require!(quote_in != 0, AmmError::InvalidAmount);
+
+        let quote_is_x = !self.launch.base_is_x;
+        let before = match quote_is_x {
+            true => self.launch_ata_x.amount,
+            false => self.launch_ata_y.amount,
+        };
+        let (from, to) = match quote_is_x {
+            true => (self.buyer_ata_x.to_account_info(), self.launch_ata_x.to_account_info()),
+            false => (self.buyer_ata_y.to_account_info(), self.launch_ata_y.to_account_info()),
+        };
+        self.transfer(quote_is_x, from, to, self.buyer.to_account_info(), &[], quote_in)?;
+
+        let escrow = match quote_is_x {
+            true => &mut self.launch_ata_x,
+            false => &mut self.launch_ata_y,
+        };
+        escrow.reload()?;
+        let received = escrow.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+
+        let base_out = swap_amount_out(self.launch.quote_reserve()?, self.launch.base_remaining, received, 0)?;
+        require!(base_out != 0, AmmError::InvalidAmount);
+        require!(base_out >= min_base_out, AmmError::SlippageExceeded);
+
+        let (from, to) = match quote_is_x {
+            true => (self.launch_ata_y.to_account_info(), self.buyer_ata_y.to_account_info()),
+            false => (self.launch_ata_x.to_account_info(), self.buyer_ata_x.to_account_info()),
+        };
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"launch", config_key.as_ref(), &[self.launch.bump]]];
+        self.transfer(!quote_is_x, from, to, self.launch.to_account_info(), signer_seeds, base_out)?;
+
+        self.launch.quote_raised = self.launch.quote_raised.checked_add(received).ok_or(AmmError::Overflow)?;
+        self.launch.base_remaining -= base_out;
+        Ok(())
+    }
+
+    fn transfer(&self, is_x: bool, from: AccountInfo<'info>, to: AccountInfo<'info>, authority: AccountInfo<'info>, signer_seeds: &[&[&[u8]]], amount: u64) -> Result<()> {
+        let (mint, decimals, token_program) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GraduateLaunch<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"launch", config.key().as_ref()],
+        bump = launch.bump
+    )]
+    pub launch: Box<Account<'info, Launch>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program_x
+    )]
+    pub launch_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program_y
+    )]
+    pub launch_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    // same account deposits lock MINIMUM_LIQUIDITY in; no instruction ever moves it
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = lp_mint,
+        associated_token::authority = config
+    )]
+    pub locked_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> GraduateLaunch<'info> {
+    // Permissionless once the target is reached. Everything raised is paired with just
+    // enough base to open the pool at the curve's final price, the LP is locked for good
+    // and the unsold base left over is burned.
+    pub fn graduate_launch(&mut self, bumps: &GraduateLaunchBumps) -> Result<()> {
+        require!(!self.launch.graduated, AmmError::LaunchClosed);
+        require!(self.launch.quote_raised >= self.launch.raise_target, AmmError::LaunchNotComplete);
+
+        let quote = self.launch.quote_raised;
+        let base = to_u64(mul_div(self.launch.base_remaining as u128, quote as u128, self.launch.quote_reserve()? as u128)?)?;
+        let (amount_x, amount_y) = match self.launch.base_is_x {
+            true => (base, quote),
+            false => (quote, base),
+        };
+
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"launch", config_key.as_ref(), &[self.launch.bump]]];
+        let received_x = self.seed(true, amount_x, signer_seeds)?;
+        let received_y = self.seed(false, amount_y, signer_seeds)?;
+
+        let liquidity = to_u64(isqrt((received_x as u128) * (received_y as u128)))?;
+        require!(liquidity > MINIMUM_LIQUIDITY, AmmError::InsufficientInitialLiquidity);
+        self.mint_locked_lp(liquidity)?;
+
+        let base_is_x = self.launch.base_is_x;
+        let (escrow, mint, token_program) = match base_is_x {
+            true => (&mut self.launch_ata_x, self.mint_x.to_account_info(), self.token_program_x.to_account_info()),
+            false => (&mut self.launch_ata_y, self.mint_y.to_account_info(), self.token_program_y.to_account_info()),
+        };
+        escrow.reload()?;
+        let base_burned = escrow.amount;
+        if base_burned > 0 {
+            let cpi_accounts = Burn {
+                mint,
+                from: escrow.to_account_info(),
+                authority: self.launch.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+            burn(cpi_ctx, base_burned)?;
+        }
+
+        self.launch.graduated = true;
+        self.launch.base_remaining = 0;
+        self.config.launch_pending = false;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, LaunchGraduated {
+            config: config_key,
+            launch: self.launch.key(),
+            amount_x: received_x,
+            amount_y: received_y,
+            lp_locked: liquidity,
+            base_burned,
+        })
+    }
+
+    // Moves one side into its vault and credits what arrived to the reserves.
+    fn seed(&mut self, is_x: bool, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<u64> {
+        let (from, to, mint, decimals, token_program, before) = match is_x {
+            true => (self.launch_ata_x.to_account_info(), self.vault_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info(), self.vault_x.amount),
+            false => (self.launch_ata_y.to_account_info(), self.vault_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info(), self.vault_y.amount),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.launch.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        let received = vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.config.credit_reserve(is_x, received)?;
+        Ok(received)
+    }
+
+    fn mint_locked_lp(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = MintTo {
+            mint: self.lp_mint.to_account_info(),
+            to: self.locked_lp_ata.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        mint_to(cpi_ctx, amount)
+    }
+}
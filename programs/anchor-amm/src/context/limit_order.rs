@@ -0,0 +1,321 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{KEEPER_BOUNTY_BPS, PAUSE_SWAP};
+use crate::context::*;
+use crate::events::Swapped;
+use crate::executor::SwapRequest;
+use crate::math::to_u64;
+use crate::price::Price;
+use crate::state::{AllowlistEntry, Config, LimitOrder};
+use crate::utils::{bps_of, check_expiration, emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateLimitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"limit_order", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + LimitOrder::INIT_SPACE
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = order,
+        associated_token::token_program = token_program_x
+    )]
+    pub order_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = order,
+        associated_token::token_program = token_program_y
+    )]
+    pub order_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = owner)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLimitOrder<'info> {
+    pub fn create_limit_order(&mut self, seed: u64, is_x: bool, amount: u64, min_price: Price, expires_at: i64, bumps: &CreateLimitOrderBumps) -> Result<()> {
+        self.config.check_not_paused(PAUSE_SWAP)?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
+        require!(amount != 0 && min_price.0 != 0, AmmError::InvalidAmount);
+        require!(expires_at > Clock::get()?.unix_timestamp, AmmError::OfferExpired);
+
+        let (from, to, mint, decimals, token_program) = match is_x {
+            true => (self.owner_ata_x.to_account_info(), self.order_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.owner_ata_y.to_account_info(), self.order_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.owner.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        self.order.set_inner(LimitOrder {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            seed,
+            is_x,
+            min_price,
+            expires_at,
+            filled: false,
+            amount_out: 0,
+            bump: bumps.order,
+        });
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FillLimitOrder<'info> {
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        token::authority = keeper
+    )]
+    pub keeper_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub pool: PoolSwap<'info>,
+    #[account(
+        mut,
+        constraint = order.config == pool.config.key() @ AmmError::InvalidOrder,
+        seeds = [b"limit_order", order.config.as_ref(), order.owner.as_ref(), order.seed.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+    #[account(
+        mut,
+        associated_token::mint = pool.mint_x,
+        associated_token::authority = order,
+        associated_token::token_program = pool.token_program_x
+    )]
+    pub order_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = pool.mint_y,
+        associated_token::authority = order,
+        associated_token::token_program = pool.token_program_y
+    )]
+    pub order_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'info> FillLimitOrder<'info> {
+    // Permissionless: fills the whole order once the pool pays at least the limit
+    // price after the keeper's fee, which is taken out of the order's input.
+    pub fn fill_limit_order(&mut self, bumps: &FillLimitOrderBumps) -> Result<()> {
+        check_expiration(Some(self.order.expires_at))?;
+        require!(!self.order.filled, AmmError::NothingToExecute);
+
+        let is_x = self.order.is_x;
+        let (from, to, mint_in, decimals, balance, token_program) = match is_x {
+            true => (self.order_ata_x.to_account_info(), self.order_ata_y.to_account_info(), self.pool.mint_x.to_account_info(), self.pool.mint_x.decimals, self.order_ata_x.amount, self.pool.token_program_x.to_account_info()),
+            false => (self.order_ata_y.to_account_info(), self.order_ata_x.to_account_info(), self.pool.mint_y.to_account_info(), self.pool.mint_y.decimals, self.order_ata_y.amount, self.pool.token_program_y.to_account_info()),
+        };
+        require_keys_eq!(self.keeper_ata.mint, mint_in.key(), AmmError::InvalidOrder);
+
+        require!(balance != 0, AmmError::NothingToExecute);
+        let bounty = bps_of(balance, KEEPER_BOUNTY_BPS)?;
+        let amount_in = balance - bounty;
+        let min_amount_out = to_u64(self.order.min_price.quote(amount_in as u128)?)?;
+        let seed = self.order.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"limit_order",
+            self.order.config.as_ref(),
+            self.order.owner.as_ref(),
+            &seed[..],
+            &[self.order.bump],
+        ]];
+
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out,
+            signer: self.order.owner,
+            runs_hook: false,
+            batch_settlement: false,
+        };
+        let res = self.pool.swap(request, from.clone(), to, self.order.to_account_info(), signer_seeds)?;
+
+        if bounty > 0 {
+            let cpi_accounts = TransferChecked {
+                from,
+                to: self.keeper_ata.to_account_info(),
+                mint: mint_in,
+                authority: self.order.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+            transfer_tokens(cpi_ctx, bounty, decimals)?;
+        }
+
+        self.order.filled = true;
+        self.order.amount_out = res.amount_out;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: self.pool.config.key(),
+            user: self.order.key(),
+            is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            protocol_fee: res.protocol_fee,
+            referral_fee: 0,
+            reserve_x: res.reserve_x,
+            reserve_y: res.reserve_y,
+        })
+    }
+}
+
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner,
+        has_one = config @ AmmError::InvalidOrder,
+        seeds = [b"limit_order", config.key().as_ref(), owner.key().as_ref(), order.seed.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, LimitOrder>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = order,
+        associated_token::token_program = token_program_x
+    )]
+    pub order_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = order,
+        associated_token::token_program = token_program_y
+    )]
+    pub order_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelLimitOrder<'info> {
+    // Cancels an open order or settles a filled or expired one, returning the
+    // escrowed input and any proceeds to the owner.
+    pub fn cancel_limit_order(&mut self) -> Result<()> {
+        self.drain(true)?;
+        self.drain(false)
+    }
+
+    fn drain(&self, is_x: bool) -> Result<()> {
+        let (from, to, mint, decimals, amount, token_program) = match is_x {
+            true => (self.order_ata_x.to_account_info(), self.owner_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.order_ata_x.amount, self.token_program_x.to_account_info()),
+            false => (self.order_ata_y.to_account_info(), self.owner_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.order_ata_y.amount, self.token_program_y.to_account_info()),
+        };
+
+        let seed = self.order.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"limit_order",
+            self.order.config.as_ref(),
+            self.order.owner.as_ref(),
+            &seed[..],
+            &[self.order.bump],
+        ]];
+
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: from.clone(),
+                to,
+                mint,
+                authority: self.order.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(token_program.clone(), cpi_accounts, signer_seeds);
+            transfer_tokens(cpi_ctx, amount, decimals)?;
+        }
+
+        let cpi_accounts = CloseAccount {
+            account: from,
+            destination: self.owner.to_account_info(),
+            authority: self.order.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::amm_error::AmmError;
+use crate::constants::{PAUSE_ALL, PAUSE_EMERGENCY};
+use crate::state::Config;
+
+#[derive(Accounts)]
+pub struct Lock<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> Lock<'info> {
+    pub fn lock(&mut self) -> Result<()> {
+        self.set_pause_flags(PAUSE_ALL)
+    }
+
+    pub fn unlock(&mut self) -> Result<()> {
+        self.set_pause_flags(0)
+    }
+
+    // Any mix of PAUSE_SWAP, PAUSE_DEPOSIT and PAUSE_WITHDRAW, or PAUSE_EMERGENCY to halt
+    // everything but withdrawals.
+    pub fn set_pause_flags(&mut self, flags: u8) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(flags & !(PAUSE_ALL | PAUSE_EMERGENCY) == 0, AmmError::InvalidAmount);
+
+        self.config.paused = flags;
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::events::{LiquidityLocked, LiquidityUnlocked};
+use crate::state::{Config, LpLock};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct LockLp<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"lp_lock", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + LpLock::INIT_SPACE
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = lp_mint,
+        associated_token::authority = lp_lock
+    )]
+    pub lock_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> LockLp<'info> {
+    // The lock can't be shortened or withdrawn early, so the LiquidityLocked event and
+    // the lock account serve as proof that the liquidity stays put until unlock_at.
+    pub fn lock_lp(&mut self, seed: u64, amount: u64, unlock_at: i64, vesting: bool, bumps: &LockLpBumps) -> Result<()> {
+        require!(amount != 0, AmmError::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(unlock_at > now, AmmError::InvalidLockDuration);
+
+        let cpi_accounts = TransferChecked {
+            from: self.owner_lp_ata.to_account_info(),
+            to: self.lock_vault.to_account_info(),
+            mint: self.lp_mint.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, self.lp_mint.decimals)?;
+
+        self.lp_lock.set_inner(LpLock {
+            config: self.config.key(),
+            owner: self.owner.key(),
+            lp_mint: self.lp_mint.key(),
+            seed,
+            amount,
+            withdrawn: 0,
+            locked_at: now,
+            unlock_at,
+            vesting,
+            bump: bumps.lp_lock,
+        });
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, LiquidityLocked {
+            config: self.config.key(),
+            lock: self.lp_lock.key(),
+            owner: self.owner.key(),
+            amount,
+            locked_at: now,
+            unlock_at,
+            vesting,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnlockLp<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = lp_mint,
+        seeds = [b"lp_lock", lp_lock.config.as_ref(), owner.key().as_ref(), lp_lock.seed.to_le_bytes().as_ref()],
+        bump = lp_lock.bump
+    )]
+    pub lp_lock: Box<Account<'info, LpLock>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = lp_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = lp_mint,
+        associated_token::authority = lp_lock
+    )]
+    pub lock_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnlockLp<'info> {
+    // Releases whatever has vested so far; the lock and its vault are closed once drained.
+    pub fn unlock_lp(&mut self, bumps: &UnlockLpBumps) -> Result<()> {
+        let unlocked = self.lp_lock.unlocked(Clock::get()?.unix_timestamp)?;
+        let amount = unlocked.checked_sub(self.lp_lock.withdrawn).ok_or(AmmError::Underflow)?;
+        require!(amount != 0, AmmError::NothingToUnlock);
+
+        let (config, owner, bump) = (self.lp_lock.config, self.lp_lock.owner, self.lp_lock.bump);
+        let seed = self.lp_lock.seed.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"lp_lock",
+            config.as_ref(),
+            owner.as_ref(),
+            &seed[..],
+            &[bump],
+        ]];
+
+        let cpi_accounts = TransferChecked {
+            from: self.lock_vault.to_account_info(),
+            to: self.owner_lp_ata.to_account_info(),
+            mint: self.lp_mint.to_account_info(),
+            authority: self.lp_lock.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, self.lp_mint.decimals)?;
+
+        self.lp_lock.withdrawn = unlocked;
+        let remaining = self.lp_lock.amount - unlocked;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, LiquidityUnlocked {
+            config: self.lp_lock.config,
+            lock: self.lp_lock.key(),
+            owner: self.owner.key(),
+            amount,
+            remaining,
+        })?;
+
+        if remaining == 0 {
+            let cpi_accounts = CloseAccount {
+                account: self.lock_vault.to_account_info(),
+                destination: self.owner.to_account_info(),
+                authority: self.lp_lock.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+            close_account(cpi_ctx)?;
+            self.lp_lock.close(self.owner.to_account_info())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::Mint;
+
+use crate::amm_error::AmmError;
+use crate::constants::CONFIG_VERSION;
+use crate::state::{Config, CurveType};
+use crate::utils::has_transfer_fee;
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: may still hold an older, shorter layout; the discriminator is checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    // Permissionless: grows a config written by an older program version to the
+    // current layout, with appended fields zero-filled, and stamps the version.
+    pub fn migrate_config(&mut self) -> Result<()> {
+        let info = self.config.to_account_info();
+        require!(
+            info.try_borrow_data()?.get(..8) == Some(&Config::DISCRIMINATOR[..]),
+            AmmError::InvalidConfig
+        );
+
+        let new_len = 8 + Config::INIT_SPACE;
+        if info.data_len() < new_len {
+            let rent = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+            if rent > 0 {
+                let cpi_accounts = Transfer {
+                    from: self.payer.to_account_info(),
+                    to: info.clone(),
+                };
+                transfer(CpiContext::new(self.system_program.to_account_info(), cpi_accounts), rent)?;
+            }
+            info.realloc(new_len, true)?;
+        }
+
+        // deserialized by value: Account::try_from would need the info to live for 'info
+        let mut config = Config::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(config.version < CONFIG_VERSION, AmmError::InvalidConfig);
+        // version 1 pools picked their curve from the weights and the PMM flag
+        if config.version < 2 {
+            config.curve_type = match (config.pmm_enabled, config.is_weighted()) {
+                (true, _) => CurveType::Pmm,
+                (false, true) => CurveType::Weighted,
+                (false, false) => CurveType::ConstantProduct,
+            };
+        }
+        // pools from before version 3 never checked their mints for transfer fees
+        if config.version < 3 {
+            require_keys_eq!(self.mint_x.key(), config.mint_x, AmmError::InvalidConfig);
+            require_keys_eq!(self.mint_y.key(), config.mint_y, AmmError::InvalidConfig);
+            config.fee_on_transfer = has_transfer_fee(&self.mint_x.to_account_info())? || has_transfer_fee(&self.mint_y.to_account_info())?;
+        }
+        config.version = CONFIG_VERSION;
+        let mut data = info.try_borrow_mut_data()?;
+        config.try_serialize(&mut &mut data[..])
+    }
+}
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use crate::amm_error::AmmError;
+use crate::context::*;
+use crate::events::Swapped;
+use crate::executor::SwapRequest;
+use crate::state::{AllowlistEntry, PermitNonce, SwapPermit};
+use crate::utils::{check_expiration, emit_event_cpi, require_ed25519_signature};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapWithPermit<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    /// CHECK: only its key is used, matched against the permit signature
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: PDA the owner approves as delegate on the input account; holds no data
+    #[account(
+        seeds = [b"permit", owner.key().as_ref()],
+        bump
+    )]
+    pub permit_authority: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        seeds = [b"permit_nonce", owner.key().as_ref()],
+        bump,
+        space = 8 + PermitNonce::INIT_SPACE
+    )]
+    pub permit_nonce: Box<Account<'info, PermitNonce>>,
+    pub pool: PoolSwap<'info>,
+    // the ATA constraints below can't reach into the nested pool accounts
+    #[account(address = pool.config.mint_x)]
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.config.mint_y)]
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.token_program_x.key())]
+    pub token_program_x: Interface<'info, TokenInterface>,
+    #[account(address = pool.token_program_y.key())]
+    pub token_program_y: Interface<'info, TokenInterface>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"allowlist", pool.config.key().as_ref(), owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = owner)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: address is checked against the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SwapWithPermit<'info> {
+    // Anyone may relay (and pay for) a swap the owner signed off-chain. The input is
+    // pulled through the owner's approval of permit_authority and the output always
+    // goes to the owner.
+    pub fn swap_with_permit(&mut self, permit: SwapPermit, bumps: &SwapWithPermitBumps) -> Result<()> {
+        check_expiration(Some(permit.expiry))?;
+        require_keys_eq!(permit.config, self.pool.config.key(), AmmError::InvalidPermit);
+        require!(self.pool.config.batch_window_secs == 0, AmmError::BatchModePool);
+        // gates apply to the owner the swap is for, never to the relayer
+        self.pool.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.pool.config.check_badge(self.badge_ata.as_deref())?;
+        let mut message = Vec::new();
+        permit.serialize(&mut message)?;
+        require_ed25519_signature(&self.instructions.to_account_info(), &self.owner.key(), &message)?;
+
+        if self.permit_nonce.owner == Pubkey::default() {
+            self.permit_nonce.owner = self.owner.key();
+            self.permit_nonce.bump = bumps.permit_nonce;
+        }
+        require!(permit.nonce == self.permit_nonce.next_nonce, AmmError::InvalidNonce);
+        self.permit_nonce.next_nonce = self.permit_nonce.next_nonce.checked_add(1).ok_or(AmmError::Overflow)?;
+
+        let (from, to) = match permit.is_x {
+            true => (self.owner_ata_x.to_account_info(), self.owner_ata_y.to_account_info()),
+            false => (self.owner_ata_y.to_account_info(), self.owner_ata_x.to_account_info()),
+        };
+        let owner_key = self.owner.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"permit", owner_key.as_ref(), &[bumps.permit_authority]]];
+        let request = SwapRequest {
+            is_x: permit.is_x,
+            amount_in: permit.amount_in,
+            min_amount_out: permit.min_amount_out,
+            signer: owner_key,
+            runs_hook: false,
+            batch_settlement: false,
+        };
+        let res = self.pool.swap(request, from, to, self.permit_authority.to_account_info(), signer_seeds)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: self.pool.config.key(),
+            user: owner_key,
+            is_x: permit.is_x,
+            amount_in: res.amount_in,
+            amount_out: res.amount_out,
+            fee: res.fee,
+            protocol_fee: res.protocol_fee,
+            referral_fee: 0,
+            reserve_x: res.reserve_x,
+            reserve_y: res.reserve_y,
+        })
+    }
+}
@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::amm_error::AmmError;
+use crate::executor::{SwapExecutor, SwapRequest};
+use crate::state::{Config, GlobalConfig, Observations, Oracle, Volatility};
+use crate::utils::{bps_of, transfer_tokens};
+
+pub struct PoolSwapResult {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub protocol_fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+// Pool side of a swap executed on behalf of a program-owned escrow (order PDAs).
+#[derive(Accounts)]
+pub struct PoolSwap<'info> {
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    /// CHECK: validated against the pool's configured oracle in Config::oracle_price
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated against the pool's secondary oracle in Config::oracle_price
+    pub secondary_price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: address is checked against the instructions sysvar; required by sandwich-guarded pools
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> PoolSwap<'info> {
+    // Swaps the request's input from `from` (owned by `authority`, signing with
+    // `signer_seeds`) into the pool and pays the output to `to`.
+    pub fn swap(
+        &mut self,
+        request: SwapRequest,
+        from: AccountInfo<'info>,
+        to: AccountInfo<'info>,
+        authority: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<PoolSwapResult> {
+        let is_x = request.is_x;
+        let oracle_price = self.config.oracle_price(self.price_feed.as_deref(), self.secondary_price_feed.as_deref(), self.mint_x.decimals, self.mint_y.decimals)?;
+        let mut executor = SwapExecutor::begin(
+            &mut self.config,
+            &self.global_config,
+            &request,
+            self.instructions.as_deref(),
+            (self.vault_x.amount, self.vault_y.amount),
+            &mut self.oracle,
+            &self.observations,
+            oracle_price,
+        )?;
+        let fee = executor.pool_fee(&self.config, self.volatility.as_deref())?;
+        let res = executor.quote(&self.config, fee)?;
+        let protocol_fee = bps_of(res.fee, self.config.protocol_fee_bps)?;
+        let lp_deposit = res.deposit.checked_sub(protocol_fee).ok_or(AmmError::Underflow)?;
+
+        let (vault_in, protocol_fee_vault) = match is_x {
+            true => (self.vault_x.to_account_info(), self.protocol_fee_vault_x.to_account_info()),
+            false => (self.vault_y.to_account_info(), self.protocol_fee_vault_y.to_account_info()),
+        };
+        let before = match is_x {
+            true => self.vault_x.amount,
+            false => self.vault_y.amount,
+        };
+        self.transfer(is_x, from.clone(), vault_in, authority.clone(), signer_seeds, lp_deposit)?;
+        if protocol_fee > 0 {
+            self.transfer(is_x, from, protocol_fee_vault, authority, signer_seeds, protocol_fee)?;
+        }
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        let received = vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+
+        let (_, withdraw) = executor.settle(&mut self.config, &res, lp_deposit, received, protocol_fee)?;
+
+        let vault_out = match is_x {
+            true => self.vault_y.to_account_info(),
+            false => self.vault_x.to_account_info(),
+        };
+        let config_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+        self.transfer(!is_x, vault_out, to, self.config.to_account_info(), config_seeds, withdraw)?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        executor.finish(&self.config, (self.vault_x.amount, self.vault_y.amount), &res, withdraw, self.volatility.as_deref_mut())?;
+
+        Ok(PoolSwapResult {
+            amount_in: res.deposit,
+            amount_out: withdraw,
+            fee: res.fee,
+            protocol_fee,
+            reserve_x: self.config.reserve_x,
+            reserve_y: self.config.reserve_y,
+        })
+    }
+
+    fn transfer(&self, is_x: bool, from: AccountInfo<'info>, to: AccountInfo<'info>, authority: AccountInfo<'info>, signer_seeds: &[&[&[u8]]], amount: u64) -> Result<()> {
+        let (mint, decimals) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority,
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
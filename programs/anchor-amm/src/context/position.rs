@@ -0,0 +1,573 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::spl_token_2022::instruction::AuthorityType,
+    token_interface::{burn, close_account, mint_to, set_authority, Burn, CloseAccount, Mint, MintTo, SetAuthority, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{KEEPER_BOUNTY_BPS, MINIMUM_LIQUIDITY, PAUSE_DEPOSIT, PAUSE_WITHDRAW};
+use crate::events::{Deposited, PositionCompounded, PositionFeesCollected, Withdrawn};
+use crate::math::{balanced_liquidity, deposit_amounts, isqrt, mul_div, to_u64, withdraw_amounts};
+use crate::state::{AllowlistEntry, Config, GlobalConfig, Observations, Oracle, Position};
+use crate::utils::{bps_of, check_expiration, emit_event_cpi, measure_delivery, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = config,
+        mint::token_program = token_program
+    )]
+    pub position_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = position_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub position_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"position", position_mint.key().as_ref()],
+        bump,
+        space = 8 + Position::INIT_SPACE
+    )]
+    pub position: Box<Account<'info, Position>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenPosition<'info> {
+    // Mints the position NFT and drops the mint authority so its supply is fixed at one.
+    pub fn open_position(&mut self, bumps: &OpenPositionBumps) -> Result<()> {
+        require!(self.config.position_mode, AmmError::NotPositionModePool);
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_accounts = MintTo {
+            mint: self.position_mint.to_account_info(),
+            to: self.position_ata.to_account_info(),
+            authority: self.config.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        mint_to(cpi_ctx, 1)?;
+
+        let cpi_accounts = SetAuthority {
+            current_authority: self.config.to_account_info(),
+            account_or_mint: self.position_mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        set_authority(cpi_ctx, AuthorityType::MintTokens, None)?;
+
+        self.position.set_inner(Position {
+            config: self.config.key(),
+            position_mint: self.position_mint.key(),
+            liquidity: 0,
+            fee_growth_last_x: self.config.fee_growth_x,
+            fee_growth_last_y: self.config.fee_growth_y,
+            fees_owed_x: 0,
+            fees_owed_y: 0,
+            bump: bumps.position,
+        });
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ModifyPosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_x
+    )]
+    pub owner_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program_y
+    )]
+    pub owner_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        has_one = config,
+        has_one = position_mint,
+        seeds = [b"position", position_mint.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    pub position_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        token::mint = position_mint,
+        token::authority = owner,
+        constraint = position_ata.amount == 1 @ AmmError::NotPositionOwner
+    )]
+    pub position_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), owner.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ModifyPosition<'info> {
+    pub fn deposit_position(&mut self, amount: u64, max_x: u64, max_y: u64, expiration: Option<i64>, remaining_accounts: &[AccountInfo<'info>], bumps: &ModifyPositionBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_DEPOSIT)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        self.config.check_deposit_allowed(self.allowlist_entry.as_deref())?;
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
+        require!(amount != 0, AmmError::InvalidAmount);
+        self.position.settle(self.config.fee_growth_x, self.config.fee_growth_y)?;
+
+        let supply = self.config.position_liquidity;
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        let bootstrap = supply == 0 && reserve_x == 0 && reserve_y == 0;
+
+        let (x, y) = match bootstrap {
+            true => (max_x, max_y),
+            false => deposit_amounts(reserve_x, reserve_y, supply, amount)?,
+        };
+        require!(x <= max_x && y <= max_y, AmmError::SlippageExceeded);
+
+        let received_x = self.transfer_in(true, x, remaining_accounts)?;
+        let received_y = self.transfer_in(false, y, remaining_accounts)?;
+
+        // as with LP tokens, MINIMUM_LIQUIDITY is locked at bootstrap; here it is simply
+        // counted in the pool total without belonging to any position
+        let liquidity = match bootstrap {
+            true => {
+                let liquidity = to_u64(isqrt((received_x as u128) * (received_y as u128)))?;
+                require!(liquidity > MINIMUM_LIQUIDITY, AmmError::InsufficientInitialLiquidity);
+                self.config.position_liquidity = MINIMUM_LIQUIDITY;
+                let liquidity = liquidity - MINIMUM_LIQUIDITY;
+                require!(liquidity >= amount, AmmError::SlippageExceeded);
+                liquidity
+            }
+            false => {
+                let liquidity_x = mul_div(supply as u128, received_x as u128, reserve_x as u128)?;
+                let liquidity_y = mul_div(supply as u128, received_y as u128, reserve_y as u128)?;
+                to_u64(liquidity_x.min(liquidity_y))?.min(amount)
+            }
+        };
+        require!(liquidity != 0, AmmError::InvalidAmount);
+
+        self.position.liquidity = self.position.liquidity.checked_add(liquidity).ok_or(AmmError::Overflow)?;
+        self.config.position_liquidity = self.config.position_liquidity.checked_add(liquidity).ok_or(AmmError::Overflow)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Deposited {
+            config: self.config.key(),
+            user: self.owner.key(),
+            amount_x: received_x,
+            amount_y: received_y,
+            lp_amount: liquidity,
+            reserve_x: self.config.reserve_x,
+            reserve_y: self.config.reserve_y,
+            lp_supply: self.config.position_liquidity,
+        })
+    }
+
+    pub fn withdraw_position(&mut self, amount: u64, min_x: u64, min_y: u64, expiration: Option<i64>, remaining_accounts: &[AccountInfo<'info>], bumps: &ModifyPositionBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_WITHDRAW)?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
+        require!(amount != 0 && amount <= self.position.liquidity, AmmError::InvalidAmount);
+        self.position.settle(self.config.fee_growth_x, self.config.fee_growth_y)?;
+
+        let (amount_x, amount_y) = withdraw_amounts(self.config.reserve_x, self.config.reserve_y, self.config.position_liquidity, amount)?;
+        let received_x = self.transfer_out(true, amount_x, remaining_accounts)?;
+        let received_y = self.transfer_out(false, amount_y, remaining_accounts)?;
+        require!(received_x >= min_x && received_y >= min_y, AmmError::SlippageExceeded);
+        self.config.debit_reserve(true, amount_x)?;
+        self.config.debit_reserve(false, amount_y)?;
+        self.position.liquidity -= amount;
+        self.config.position_liquidity -= amount;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Withdrawn {
+            config: self.config.key(),
+            user: self.owner.key(),
+            amount_x,
+            amount_y,
+            lp_amount: amount,
+            reserve_x: self.config.reserve_x,
+            reserve_y: self.config.reserve_y,
+            lp_supply: self.config.position_liquidity,
+        })
+    }
+
+    pub fn collect_position_fees(&mut self, remaining_accounts: &[AccountInfo<'info>], bumps: &ModifyPositionBumps) -> Result<()> {
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        self.position.settle(self.config.fee_growth_x, self.config.fee_growth_y)?;
+
+        let (amount_x, amount_y) = (self.position.fees_owed_x, self.position.fees_owed_y);
+        require!(amount_x != 0 || amount_y != 0, AmmError::InvalidAmount);
+
+        self.transfer_out(true, amount_x, remaining_accounts)?;
+        self.transfer_out(false, amount_y, remaining_accounts)?;
+        self.config.unclaimed_fees_x = self.config.unclaimed_fees_x.checked_sub(amount_x).ok_or(AmmError::Underflow)?;
+        self.config.unclaimed_fees_y = self.config.unclaimed_fees_y.checked_sub(amount_y).ok_or(AmmError::Underflow)?;
+        self.position.fees_owed_x = 0;
+        self.position.fees_owed_y = 0;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, PositionFeesCollected {
+            config: self.config.key(),
+            position: self.position.key(),
+            owner: self.owner.key(),
+            amount_x,
+            amount_y,
+        })
+    }
+
+    // Returns the amount that actually reached the vault; the reserves are credited with it.
+    fn transfer_in(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let (from, to, mint, decimals, before) = match is_x {
+            true => (self.owner_ata_x.to_account_info(), self.vault_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.vault_x.amount),
+            false => (self.owner_ata_y.to_account_info(), self.vault_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.vault_y.amount),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+
+        let received = vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.config.credit_reserve(is_x, received)?;
+        Ok(received)
+    }
+
+    // Returns the amount that reached the owner.
+    fn transfer_out(&self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        let (from, to, mint, decimals) = match is_x {
+            true => (self.vault_x.to_account_info(), self.owner_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.vault_y.to_account_info(), self.owner_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to: to.clone(),
+            mint,
+            authority: self.config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        measure_delivery(self.config.fee_on_transfer, &to, amount, || transfer_tokens(cpi_ctx, amount, decimals))
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompoundPosition<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_x,
+        associated_token::authority = caller,
+        associated_token::token_program = token_program_x
+    )]
+    pub caller_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = mint_y,
+        associated_token::authority = caller,
+        associated_token::token_program = token_program_y
+    )]
+    pub caller_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"position", position.position_mint.as_ref()],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Box<Account<'info, Oracle>>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CompoundPosition<'info> {
+    // Permissionless: the caller keeps KEEPER_BOUNTY_BPS of the fees and the rest is
+    // re-added to the position at the pool ratio, without leaving the vaults. Whatever
+    // of the excess side doesn't fit the ratio stays owed to the position.
+    pub fn compound(&mut self, remaining_accounts: &[AccountInfo<'info>], bumps: &CompoundPositionBumps) -> Result<()> {
+        self.config.check_not_paused(PAUSE_DEPOSIT)?;
+        self.global_config.check_not_paused()?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
+        self.position.settle(self.config.fee_growth_x, self.config.fee_growth_y)?;
+
+        let bounty_x = bps_of(self.position.fees_owed_x, KEEPER_BOUNTY_BPS)?;
+        let bounty_y = bps_of(self.position.fees_owed_y, KEEPER_BOUNTY_BPS)?;
+        self.pay_bounty(true, bounty_x, remaining_accounts)?;
+        self.pay_bounty(false, bounty_y, remaining_accounts)?;
+
+        let (reserve_x, reserve_y, supply) = (self.config.reserve_x, self.config.reserve_y, self.config.position_liquidity);
+        let liquidity = balanced_liquidity(reserve_x, reserve_y, supply, self.position.fees_owed_x, self.position.fees_owed_y)?;
+        require!(liquidity != 0, AmmError::InvalidAmount);
+        let (amount_x, amount_y) = deposit_amounts(reserve_x, reserve_y, supply, liquidity)?;
+
+        self.position.fees_owed_x -= amount_x;
+        self.position.fees_owed_y -= amount_y;
+        self.config.unclaimed_fees_x = self.config.unclaimed_fees_x.checked_sub(amount_x).ok_or(AmmError::Underflow)?;
+        self.config.unclaimed_fees_y = self.config.unclaimed_fees_y.checked_sub(amount_y).ok_or(AmmError::Underflow)?;
+        self.config.credit_reserve(true, amount_x)?;
+        self.config.credit_reserve(false, amount_y)?;
+        self.position.liquidity = self.position.liquidity.checked_add(liquidity).ok_or(AmmError::Overflow)?;
+        self.config.position_liquidity = self.config.position_liquidity.checked_add(liquidity).ok_or(AmmError::Overflow)?;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, PositionCompounded {
+            config: self.config.key(),
+            position: self.position.key(),
+            caller: self.caller.key(),
+            amount_x,
+            amount_y,
+            liquidity,
+            bounty_x,
+            bounty_y,
+        })
+    }
+
+    fn pay_bounty(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let (from, to, mint, decimals) = match is_x {
+            true => (self.vault_x.to_account_info(), self.caller_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.vault_y.to_account_info(), self.caller_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        let (fees_owed, unclaimed_fees) = match is_x {
+            true => (&mut self.position.fees_owed_x, &mut self.config.unclaimed_fees_x),
+            false => (&mut self.position.fees_owed_y, &mut self.config.unclaimed_fees_y),
+        };
+        *fees_owed -= amount;
+        *unclaimed_fees = unclaimed_fees.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        has_one = position_mint,
+        seeds = [b"position", position_mint.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Box<Account<'info, Position>>,
+    #[account(mut)]
+    pub position_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = position_mint,
+        token::authority = owner,
+        constraint = position_ata.amount == 1 @ AmmError::NotPositionOwner
+    )]
+    pub position_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClosePosition<'info> {
+    // An empty position earns nothing more, so its owed fees are final once collected.
+    pub fn close_position(&mut self) -> Result<()> {
+        require!(
+            self.position.liquidity == 0 && self.position.fees_owed_x == 0 && self.position.fees_owed_y == 0,
+            AmmError::PositionNotEmpty
+        );
+
+        let cpi_accounts = Burn {
+            mint: self.position_mint.to_account_info(),
+            from: self.position_ata.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+        burn(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), 1)?;
+
+        let cpi_accounts = CloseAccount {
+            account: self.position_ata.to_account_info(),
+            destination: self.owner.to_account_info(),
+            authority: self.owner.to_account_info(),
+        };
+        close_account(CpiContext::new(self.token_program.to_account_info(), cpi_accounts))
+    }
+}
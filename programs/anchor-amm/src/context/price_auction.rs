@@ -0,0 +1,536 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::MINIMUM_LIQUIDITY;
+use crate::events::PriceAuctionSettled;
+use crate::math::{isqrt, mul_div, to_u64};
+use crate::price::Price;
+use crate::state::{AuctionBid, Config, CurveType, PriceAuction};
+use crate::utils::{emit_event_cpi, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct CreatePriceAuction<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"price_auction", config.key().as_ref()],
+        bump,
+        space = 8 + PriceAuction::INIT_SPACE
+    )]
+    pub auction: Box<Account<'info, PriceAuction>>,
+    #[account(
+        mut,
+        token::authority = creator
+    )]
+    pub creator_base_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint_x,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_x
+    )]
+    pub auction_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint_y,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_y
+    )]
+    pub auction_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreatePriceAuction<'info> {
+    // The pool's authority escrows the sale and its liquidity allocation together and
+    // keeps the pool closed to deposits until the auction settles.
+    pub fn create_price_auction(&mut self, base_is_x: bool, sale_amount: u64, liquidity_amount: u64, start_price: Price, end_price: Price, duration: i64, bumps: &CreatePriceAuctionBumps) -> Result<()> {
+        self.config.check_authority(self.creator.key(), self.admin_nft_ata.as_deref())?;
+        require!(self.config.curve_type == CurveType::ConstantProduct && !self.config.pmm_enabled, AmmError::InvalidConfig);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        require!(!self.config.launch_pending, AmmError::LaunchPending);
+        require!(
+            self.config.reserve_x == 0 && self.config.reserve_y == 0 && self.lp_mint.supply == 0,
+            AmmError::PoolNotEmpty
+        );
+        require!(sale_amount != 0 && liquidity_amount != 0 && duration > 0, AmmError::InvalidAmount);
+        require!(start_price > end_price && end_price.0 != 0, AmmError::InvalidAmount);
+
+        let (to, mint, decimals, token_program) = match base_is_x {
+            true => (self.auction_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.auction_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+        require_keys_eq!(self.creator_base_ata.mint, mint.key(), AmmError::InvalidToken);
+
+        let cpi_accounts = TransferChecked {
+            from: self.creator_base_ata.to_account_info(),
+            to,
+            mint,
+            authority: self.creator.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+        transfer_tokens(cpi_ctx, sale_amount.checked_add(liquidity_amount).ok_or(AmmError::Overflow)?, decimals)?;
+
+        // a transfer fee comes out of the liquidity allocation, never the sale
+        let escrow = match base_is_x {
+            true => &mut self.auction_ata_x,
+            false => &mut self.auction_ata_y,
+        };
+        escrow.reload()?;
+        let liquidity_amount = escrow.amount.checked_sub(sale_amount).ok_or(AmmError::Underflow)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        self.auction.set_inner(PriceAuction {
+            creator: self.creator.key(),
+            config: self.config.key(),
+            base_is_x,
+            sale_amount,
+            liquidity_amount,
+            start_price,
+            end_price,
+            starts_at: now,
+            ends_at: now.checked_add(duration).ok_or(AmmError::Overflow)?,
+            total_quote: 0,
+            clearing_price: None,
+            sold_amount: 0,
+            quote_used: 0,
+            settled: false,
+            bump: bumps.auction,
+        });
+        self.config.launch_pending = true;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BidPriceAuction<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"price_auction", config.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, PriceAuction>>,
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        seeds = [b"auction_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump,
+        space = 8 + AuctionBid::INIT_SPACE
+    )]
+    pub bid: Box<Account<'info, AuctionBid>>,
+    #[account(
+        mut,
+        token::authority = bidder
+    )]
+    pub bidder_quote_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_x
+    )]
+    pub auction_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_y
+    )]
+    pub auction_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BidPriceAuction<'info> {
+    // Bids are quote amounts; every bidder pays the single clearing price at settlement
+    // and is refunded whatever that price leaves over.
+    pub fn bid_price_auction(&mut self, amount: u64, bumps: &BidPriceAuctionBumps) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(self.auction.clearing_price.is_none() && now < self.auction.ends_at, AmmError::AuctionClosed);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let quote_is_x = !self.auction.base_is_x;
+        let (to, mint, decimals, token_program, before) = match quote_is_x {
+            true => (self.auction_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info(), self.auction_ata_x.amount),
+            false => (self.auction_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info(), self.auction_ata_y.amount),
+        };
+        require_keys_eq!(self.bidder_quote_ata.mint, mint.key(), AmmError::InvalidToken);
+
+        let cpi_accounts = TransferChecked {
+            from: self.bidder_quote_ata.to_account_info(),
+            to,
+            mint,
+            authority: self.bidder.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(token_program, cpi_accounts);
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        let escrow = match quote_is_x {
+            true => &mut self.auction_ata_x,
+            false => &mut self.auction_ata_y,
+        };
+        escrow.reload()?;
+        let received = escrow.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+
+        if self.bid.auction == Pubkey::default() {
+            self.bid.bidder = self.bidder.key();
+            self.bid.auction = self.auction.key();
+            self.bid.bump = bumps.bid;
+        }
+        self.bid.amount = self.bid.amount.checked_add(received).ok_or(AmmError::Overflow)?;
+        self.auction.total_quote = self.auction.total_quote.checked_add(received).ok_or(AmmError::Overflow)?;
+
+        let price = self.auction.price_at(now)?;
+        if self.auction.total_quote as u128 >= price.quote(self.auction.sale_amount as u128)? {
+            self.auction.clearing_price = Some(price);
+        }
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettlePriceAuction<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"price_auction", config.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, PriceAuction>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_x
+    )]
+    pub auction_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_y
+    )]
+    pub auction_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::authority = auction.creator,
+        token::token_program = token_program_x
+    )]
+    pub creator_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::authority = auction.creator,
+        token::token_program = token_program_y
+    )]
+    pub creator_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = auction.creator
+    )]
+    pub creator_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    // same account deposits lock MINIMUM_LIQUIDITY in; no instruction ever moves it
+    #[account(
+        init_if_needed,
+        payer = caller,
+        associated_token::mint = lp_mint,
+        associated_token::authority = config
+    )]
+    pub locked_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SettlePriceAuction<'info> {
+    // Permissionless once the auction has cleared or run out of time. Proceeds are
+    // paired with the liquidity allocation at the settlement price to open the pool,
+    // the creator takes the LP and anything unused, and bidders claim separately.
+    pub fn settle_price_auction(&mut self, bumps: &SettlePriceAuctionBumps) -> Result<()> {
+        require!(!self.auction.settled, AmmError::AuctionClosed);
+        require!(
+            self.auction.clearing_price.is_some() || Clock::get()?.unix_timestamp >= self.auction.ends_at,
+            AmmError::AuctionOpen
+        );
+
+        let price = self.auction.settlement_price();
+        let total_quote = self.auction.total_quote;
+        let sold = price.quote_inverse(total_quote as u128)?.min(self.auction.sale_amount as u128) as u64;
+        let quote_used = to_u64(price.quote(sold as u128)?)?.min(total_quote);
+
+        // the pool opens at the same price the bidders paid; a sale too small to open it
+        // just hands the proceeds to the creator
+        let seed_base = sold.min(self.auction.liquidity_amount);
+        let seed_quote = to_u64(price.quote(seed_base as u128)?)?.min(quote_used);
+        let base_is_x = self.auction.base_is_x;
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"price_auction", config_key.as_ref(), &[self.auction.bump]]];
+        if to_u64(isqrt((seed_base as u128) * (seed_quote as u128)))? > MINIMUM_LIQUIDITY {
+            let (amount_x, amount_y) = match base_is_x {
+                true => (seed_base, seed_quote),
+                false => (seed_quote, seed_base),
+            };
+            let received_x = self.seed(true, amount_x, signer_seeds)?;
+            let received_y = self.seed(false, amount_y, signer_seeds)?;
+            let liquidity = to_u64(isqrt((received_x as u128) * (received_y as u128)))?;
+            require!(liquidity > MINIMUM_LIQUIDITY, AmmError::InsufficientInitialLiquidity);
+            self.mint_lp(self.locked_lp_ata.to_account_info(), MINIMUM_LIQUIDITY)?;
+            self.mint_lp(self.creator_lp_ata.to_account_info(), liquidity - MINIMUM_LIQUIDITY)?;
+        }
+
+        // what is left beyond the bidders' base and refunds goes back to the creator
+        let quote_owed = total_quote - quote_used;
+        self.auction_ata_x.reload()?;
+        self.auction_ata_y.reload()?;
+        let (owed_x, owed_y) = match base_is_x {
+            true => (sold, quote_owed),
+            false => (quote_owed, sold),
+        };
+        let return_x = self.auction_ata_x.amount.checked_sub(owed_x).ok_or(AmmError::Underflow)?;
+        let return_y = self.auction_ata_y.amount.checked_sub(owed_y).ok_or(AmmError::Underflow)?;
+        if return_x > 0 {
+            self.transfer(true, self.creator_ata_x.to_account_info(), return_x, signer_seeds)?;
+        }
+        if return_y > 0 {
+            self.transfer(false, self.creator_ata_y.to_account_info(), return_y, signer_seeds)?;
+        }
+
+        self.auction.sold_amount = sold;
+        self.auction.quote_used = quote_used;
+        self.auction.settled = true;
+        self.config.launch_pending = false;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, PriceAuctionSettled {
+            config: config_key,
+            auction: self.auction.key(),
+            price,
+            sold_amount: sold,
+            amount_x: self.config.reserve_x,
+            amount_y: self.config.reserve_y,
+        })
+    }
+
+    // Moves one side into its vault and credits what arrived to the reserves.
+    fn seed(&mut self, is_x: bool, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<u64> {
+        let (to, before) = match is_x {
+            true => (self.vault_x.to_account_info(), self.vault_x.amount),
+            false => (self.vault_y.to_account_info(), self.vault_y.amount),
+        };
+        self.transfer(is_x, to, amount, signer_seeds)?;
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+        let received = vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?;
+        self.config.credit_reserve(is_x, received)?;
+        Ok(received)
+    }
+
+    fn transfer(&self, is_x: bool, to: AccountInfo<'info>, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let (from, mint, decimals, token_program) = match is_x {
+            true => (self.auction_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.auction_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.auction.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+
+    fn mint_lp(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = MintTo {
+            mint: self.lp_mint.to_account_info(),
+            to,
+            authority: self.config.to_account_info(),
+        };
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        mint_to(cpi_ctx, amount)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ClaimAuctionBid<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        has_one = config @ AmmError::InvalidConfig,
+        seeds = [b"price_auction", config.key().as_ref()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, PriceAuction>>,
+    #[account(
+        mut,
+        close = bidder,
+        has_one = bidder,
+        has_one = auction @ AmmError::InvalidOrder,
+        seeds = [b"auction_bid", auction.key().as_ref(), bidder.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, AuctionBid>>,
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = mint_x,
+        associated_token::authority = bidder,
+        associated_token::token_program = token_program_x
+    )]
+    pub bidder_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = bidder,
+        associated_token::mint = mint_y,
+        associated_token::authority = bidder,
+        associated_token::token_program = token_program_y
+    )]
+    pub bidder_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_x
+    )]
+    pub auction_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = auction,
+        associated_token::token_program = token_program_y
+    )]
+    pub auction_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimAuctionBid<'info> {
+    // Pays the bid its pro-rata share of the base sold and of the unused quote;
+    // rounding dust stays in the auction escrow.
+    pub fn claim_auction_bid(&mut self) -> Result<()> {
+        require!(self.auction.settled, AmmError::AuctionNotSettled);
+
+        let total_quote = self.auction.total_quote as u128;
+        let base = to_u64(mul_div(self.bid.amount as u128, self.auction.sold_amount as u128, total_quote)?)?;
+        let refund = to_u64(mul_div(self.bid.amount as u128, total_quote - self.auction.quote_used as u128, total_quote)?)?;
+
+        let base_is_x = self.auction.base_is_x;
+        if base > 0 {
+            self.transfer(base_is_x, base)?;
+        }
+        if refund > 0 {
+            self.transfer(!base_is_x, refund)?;
+        }
+        Ok(())
+    }
+
+    fn transfer(&self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to, mint, decimals, token_program) = match is_x {
+            true => (self.auction_ata_x.to_account_info(), self.bidder_ata_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals, self.token_program_x.to_account_info()),
+            false => (self.auction_ata_y.to_account_info(), self.bidder_ata_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals, self.token_program_y.to_account_info()),
+        };
+
+        let config_key = self.config.key();
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[b"price_auction", config_key.as_ref(), &[self.auction.bump]]];
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.auction.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+        transfer_tokens(cpi_ctx, amount, decimals)
+    }
+}
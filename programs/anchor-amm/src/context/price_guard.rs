@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::amm_error::AmmError;
+use crate::constants::{MAX_FEE_BPS, MAX_GUARD_BPS};
+use crate::state::{Config, CurveType, OracleType};
+
+#[derive(Accounts)]
+pub struct SetPriceGuard<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
+
+impl<'info> SetPriceGuard<'info> {
+    pub fn set_max_price_impact(&mut self, max_price_impact_bps: u16) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(max_price_impact_bps <= MAX_GUARD_BPS, AmmError::InvalidAmount);
+
+        self.config.max_price_impact_bps = max_price_impact_bps;
+        Ok(())
+    }
+
+    // OracleType::None turns the oracle deviation guard off. oracle_feed is the Pyth
+    // feed id, the Switchboard aggregator address or the stake pool address.
+    pub fn set_oracle_guard(&mut self, oracle_type: OracleType, oracle_feed: [u8; 32], max_oracle_deviation_bps: u16, max_staleness_secs: u64) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(max_oracle_deviation_bps <= MAX_GUARD_BPS, AmmError::InvalidAmount);
+        require!(oracle_type == OracleType::None || max_staleness_secs > 0, AmmError::InvalidAmount);
+        require!(oracle_type != OracleType::None || self.config.curve_type != CurveType::Lst, AmmError::MissingPriceFeed);
+
+        self.config.oracle_type = oracle_type;
+        self.config.oracle_feed = oracle_feed;
+        self.config.max_oracle_deviation_bps = max_oracle_deviation_bps;
+        self.config.max_staleness_secs = max_staleness_secs;
+        Ok(())
+    }
+
+    // The secondary feed is read only while the primary is stale. With halt_on_stale_oracle
+    // unset, swaps fall back to pure curve pricing once both feeds are stale.
+    pub fn set_oracle_fallback(&mut self, secondary_oracle_type: OracleType, secondary_oracle_feed: [u8; 32], halt_on_stale_oracle: bool) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(self.config.oracle_type != OracleType::None, AmmError::MissingPriceFeed);
+
+        self.config.secondary_oracle_type = secondary_oracle_type;
+        self.config.secondary_oracle_feed = secondary_oracle_feed;
+        self.config.halt_on_stale_oracle = halt_on_stale_oracle;
+        Ok(())
+    }
+
+    pub fn set_sandwich_guard(&mut self, enabled: bool) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+
+        self.config.sandwich_guard = enabled;
+        Ok(())
+    }
+
+    // 0 returns the pool to continuous trading; open batches can still be settled.
+    pub fn set_batch_window(&mut self, batch_window_secs: u32) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        if batch_window_secs != 0 {
+            self.config.check_product_curve()?;
+            require!(!self.config.pmm_enabled, AmmError::UnsupportedForPmmPool);
+        }
+
+        self.config.batch_window_secs = batch_window_secs;
+        Ok(())
+    }
+
+    // Only before the pool's first swap, so a live pool's fee can't jump past the
+    // timelock; starts_at of 0 starts the decay now and a launch_fee of 0 clears it.
+    pub fn set_launch_fee(&mut self, launch_fee: u16, starts_at: i64, decay_secs: u32) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(launch_fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        if launch_fee != 0 {
+            require!(self.config.swap_count == 0, AmmError::InvalidConfig);
+            require!(decay_secs != 0, AmmError::InvalidAmount);
+        }
+
+        self.config.launch_fee = launch_fee;
+        self.config.launch_fee_starts_at = match starts_at {
+            0 => Clock::get()?.unix_timestamp,
+            starts_at => starts_at,
+        };
+        self.config.launch_fee_decay_secs = decay_secs;
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::amm_error::AmmError;
+use crate::context::Swap;
+use crate::math::{deposit_amounts, withdraw_amounts};
+use crate::state::{Config, Volatility};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LiquidityQuote {
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+    /// CHECK: validated against the pool's configured oracle in Config::oracle_price
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated against the pool's secondary oracle in Config::oracle_price
+    pub secondary_price_feed: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> Quote<'info> {
+    pub fn quote_swap(&self, amount_in: u64, is_x: bool) -> Result<SwapQuote> {
+        require!(amount_in > 0, AmmError::InvalidAmount);
+
+        let oracle_price = self.config.oracle_price(self.price_feed.as_deref(), self.secondary_price_feed.as_deref(), self.mint_x.decimals, self.mint_y.decimals)?;
+        let fee = self.config.current_fee(self.volatility.as_deref(), is_x, oracle_price)?;
+        let res = Swap::quote(&self.config, self.config.reserve_x, self.config.reserve_y, is_x, amount_in, 0, fee, oracle_price)?;
+
+        Ok(SwapQuote {
+            amount_in: res.deposit,
+            amount_out: res.withdraw,
+            fee: res.fee,
+        })
+    }
+
+    pub fn quote_deposit(&self, amount: u64) -> Result<LiquidityQuote> {
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let (amount_x, amount_y) = deposit_amounts(self.config.reserve_x, self.config.reserve_y, self.lp_mint.supply, amount)?;
+
+        Ok(LiquidityQuote { amount_x, amount_y })
+    }
+
+    pub fn quote_withdraw(&self, amount: u64) -> Result<LiquidityQuote> {
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let (amount_x, amount_y) = withdraw_amounts(self.config.reserve_x, self.config.reserve_y, self.lp_mint.supply, amount)?;
+
+        Ok(LiquidityQuote { amount_x, amount_y })
+    }
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::state::{Config, Observations, Oracle};
+use crate::utils::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    pub authority: Signer<'info>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SyncReserves<'info> {
+    // Absorbs donated tokens into the reserves without minting LP, so they accrue to existing LPs.
+    pub fn sync(&mut self) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
+
+        self.config.reserve_x = self.vault_x.amount.checked_sub(self.config.unclaimed_fees_x).ok_or(AmmError::Underflow)?;
+        self.config.reserve_y = self.vault_y.amount.checked_sub(self.config.unclaimed_fees_y).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+
+    // For mints whose balances change outside of transfers. Enabling syncs first so
+    // the pool starts from the current vault balances.
+    pub fn set_rebasing(&mut self, enabled: bool) -> Result<()> {
+        if enabled {
+            self.sync()?;
+        }
+        self.config.rebasing = enabled;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SkimReserves<'info> {
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        has_one = mint_x,
+        has_one = mint_y,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
+    )]
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SkimReserves<'info> {
+    // Permissionless: sweeps anything above the tracked reserves to the protocol treasury.
+    pub fn skim(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        // the excess of a rebasing pool is yield owed to its LPs
+        require!(!self.config.rebasing, AmmError::RebasingPool);
+
+        self.skim_token(true, remaining_accounts)?;
+        self.skim_token(false, remaining_accounts)
+    }
+
+    fn skim_token(&self, is_x: bool, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (from, to, mint, decimals, excess, token_program) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.protocol_fee_vault_x.to_account_info(),
+                self.mint_x.to_account_info(),
+                self.mint_x.decimals,
+                self.vault_x.amount.saturating_sub(self.config.reserve_x + self.config.unclaimed_fees_x),
+                self.token_program_x.to_account_info(),
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.protocol_fee_vault_y.to_account_info(),
+                self.mint_y.to_account_info(),
+                self.mint_y.decimals,
+                self.vault_y.amount.saturating_sub(self.config.reserve_y + self.config.unclaimed_fees_y),
+                self.token_program_y.to_account_info(),
+            ),
+        };
+        if excess == 0 {
+            return Ok(());
+        }
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint,
+            authority: self.config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, excess, decimals)
+    }
+}
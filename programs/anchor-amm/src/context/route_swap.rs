@@ -0,0 +1,252 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::amm_error::AmmError;
+use crate::constants::{MAX_ROUTE_HOPS, ROUTE_HOP_ACCOUNTS, TOTAL_WEIGHT};
+use crate::events::Swapped;
+use crate::executor::{SwapExecutor, SwapRequest};
+use crate::state::{Config, GlobalConfig, Observations, Oracle, OracleType};
+use crate::utils::{bps_of, check_expiration, emit_event_cpi, transfer_tokens};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_in: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = mint_in,
+        token::authority = user
+    )]
+    pub user_ata_in: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    /// CHECK: address is checked against the instructions sysvar; required by sandwich-guarded pools
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RouteSwap<'info> {
+    // Each hop takes ROUTE_HOP_ACCOUNTS remaining accounts:
+    // [config, mint_in, mint_out, vault_in, vault_out, protocol_fee_vault_in, oracle, observations, user_ata_out].
+    // Remaining accounts are consumed by the route, so transfer-hook mints are not supported.
+    // Every mint on the route must belong to token_program.
+    pub fn route_swap(&mut self, amount_in: u64, min_amount_out: u64, expiration: Option<i64>, remaining_accounts: &'info [AccountInfo<'info>], bumps: &RouteSwapBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.global_config.check_not_paused()?;
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.chunks_exact(ROUTE_HOP_ACCOUNTS).remainder().is_empty(),
+            AmmError::InvalidRoute
+        );
+        require!(remaining_accounts.len() / ROUTE_HOP_ACCOUNTS <= MAX_ROUTE_HOPS, AmmError::InvalidRoute);
+
+        let mut amount = amount_in;
+        let mut user_ata_in = self.user_ata_in.to_account_info();
+        let mut mint_in = self.mint_in.key();
+
+        for hop in remaining_accounts.chunks(ROUTE_HOP_ACCOUNTS) {
+            (amount, user_ata_in, mint_in) = self.swap_hop(hop, user_ata_in, mint_in, amount, bumps)?;
+        }
+
+        require!(amount >= min_amount_out, AmmError::SlippageExceeded);
+        Ok(())
+    }
+
+    // Splits the input across parallel pools of the same pair, one leg per pool in
+    // remaining_accounts, weighted in bps. The last leg takes any rounding remainder.
+    pub fn split_swap(&mut self, amount_in: u64, weights: Vec<u16>, min_amount_out: u64, expiration: Option<i64>, remaining_accounts: &'info [AccountInfo<'info>], bumps: &RouteSwapBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.global_config.check_not_paused()?;
+        require!(amount_in > 0, AmmError::InvalidAmount);
+        require!(
+            !weights.is_empty() && remaining_accounts.len() == weights.len() * ROUTE_HOP_ACCOUNTS,
+            AmmError::InvalidRoute
+        );
+        require!(weights.len() <= MAX_ROUTE_HOPS, AmmError::InvalidRoute);
+        require!(
+            weights.iter().all(|weight| *weight != 0)
+                && weights.iter().map(|weight| *weight as u32).sum::<u32>() == TOTAL_WEIGHT as u32,
+            AmmError::InvalidSplitWeights
+        );
+
+        let mut remaining = amount_in;
+        let mut total_out: u64 = 0;
+        let mut mint_out = None;
+
+        for (i, leg) in remaining_accounts.chunks(ROUTE_HOP_ACCOUNTS).enumerate() {
+            let leg_amount = match i == weights.len() - 1 {
+                true => remaining,
+                false => bps_of(amount_in, weights[i])?,
+            };
+            remaining -= leg_amount;
+            if leg_amount == 0 {
+                continue;
+            }
+
+            let (amount_out, _, leg_mint_out) = self.swap_hop(leg, self.user_ata_in.to_account_info(), self.mint_in.key(), leg_amount, bumps)?;
+            require!(*mint_out.get_or_insert(leg_mint_out) == leg_mint_out, AmmError::InvalidRoute);
+            total_out = total_out.checked_add(amount_out).ok_or(AmmError::Overflow)?;
+        }
+
+        require!(total_out >= min_amount_out, AmmError::SlippageExceeded);
+        Ok(())
+    }
+
+    // Returns the amount received by the user, their output account and the output mint.
+    fn swap_hop(&self, hop: &'info [AccountInfo<'info>], user_ata_in: AccountInfo<'info>, expected_mint_in: Pubkey, amount_in: u64, bumps: &RouteSwapBumps) -> Result<(u64, AccountInfo<'info>, Pubkey)> {
+        let mut config = Account::<Config>::try_from(&hop[0])?;
+        let mint_in = InterfaceAccount::<Mint>::try_from(&hop[1])?;
+        let mint_out = InterfaceAccount::<Mint>::try_from(&hop[2])?;
+        let mut vault_in = InterfaceAccount::<TokenAccount>::try_from(&hop[3])?;
+        let mut vault_out = InterfaceAccount::<TokenAccount>::try_from(&hop[4])?;
+        let protocol_fee_vault = &hop[5];
+        let mut oracle = Account::<Oracle>::try_from(&hop[6])?;
+        let observations = AccountLoader::<Observations>::try_from(&hop[7])?;
+        let mut user_ata_out = InterfaceAccount::<TokenAccount>::try_from(&hop[8])?;
+
+        // the hop has to continue from the previous output and match the pool's pair
+        require_keys_eq!(mint_in.key(), expected_mint_in, AmmError::InvalidRoute);
+        let is_x = config.mint_x == mint_in.key();
+        require!(
+            (is_x && config.mint_y == mint_out.key()) || (config.mint_y == mint_in.key() && config.mint_x == mint_out.key()),
+            AmmError::InvalidRoute
+        );
+        // allowlist entries and badges cannot be passed per hop, so gated pools are not routable
+        require!(!config.allowlist_swaps, AmmError::NotAllowlisted);
+        require!(config.badge_mint.is_none(), AmmError::MissingBadge);
+        require!(config.oracle_type == OracleType::None, AmmError::MissingPriceFeed);
+
+        let token_program = self.token_program.key();
+        require_keys_eq!(
+            vault_in.key(),
+            get_associated_token_address_with_program_id(&config.key(), &mint_in.key(), &token_program),
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(
+            vault_out.key(),
+            get_associated_token_address_with_program_id(&config.key(), &mint_out.key(), &token_program),
+            AmmError::InvalidRoute
+        );
+        let treasury = Pubkey::create_program_address(
+            &[b"treasury", config.key().as_ref(), &[config.treasury_bump]],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::InvalidRoute)?;
+        require_keys_eq!(
+            protocol_fee_vault.key(),
+            get_associated_token_address_with_program_id(&treasury, &mint_in.key(), &token_program),
+            AmmError::InvalidRoute
+        );
+        require_keys_eq!(oracle.config, config.key(), AmmError::InvalidRoute);
+        require_keys_eq!(observations.load()?.config, config.key(), AmmError::InvalidRoute);
+        require_keys_eq!(user_ata_out.owner, self.user.key(), AmmError::InvalidRoute);
+        require_keys_eq!(user_ata_out.mint, mint_out.key(), AmmError::InvalidRoute);
+
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out: 0,
+            signer: self.user.key(),
+            runs_hook: false,
+            batch_settlement: false,
+        };
+        let balances = match is_x {
+            true => (vault_in.amount, vault_out.amount),
+            false => (vault_out.amount, vault_in.amount),
+        };
+        let mut executor = SwapExecutor::begin(&mut config, &self.global_config, &request, self.instructions.as_deref(), balances, &mut oracle, &observations, None)?;
+        let fee = executor.pool_fee(&config, None)?;
+        let res = executor.quote(&config, fee)?;
+        let protocol_fee = bps_of(res.fee, config.protocol_fee_bps)?;
+        let lp_deposit = res.deposit.checked_sub(protocol_fee).ok_or(AmmError::Underflow)?;
+
+        let vault_before = vault_in.amount;
+        self.pay_in(user_ata_in.clone(), vault_in.to_account_info(), &mint_in, lp_deposit)?;
+        if protocol_fee > 0 {
+            self.pay_in(user_ata_in, protocol_fee_vault.clone(), &mint_in, protocol_fee)?;
+        }
+        vault_in.reload()?;
+        let received = vault_in.amount.checked_sub(vault_before).ok_or(AmmError::Underflow)?;
+
+        let (_, withdraw) = executor.settle(&mut config, &res, lp_deposit, received, protocol_fee)?;
+
+        let user_before = user_ata_out.amount;
+        self.pay_out(&config, vault_out.to_account_info(), user_ata_out.to_account_info(), &mint_out, withdraw)?;
+        user_ata_out.reload()?;
+        let amount_out = user_ata_out.amount.checked_sub(user_before).ok_or(AmmError::Underflow)?;
+
+        vault_out.reload()?;
+        let balances = match is_x {
+            true => (vault_in.amount, vault_out.amount),
+            false => (vault_out.amount, vault_in.amount),
+        };
+        executor.finish(&config, balances, &res, withdraw, None)?;
+
+        let (reserve_x, reserve_y) = (config.reserve_x, config.reserve_y);
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: res.deposit,
+            amount_out: withdraw,
+            fee: res.fee,
+            protocol_fee,
+            referral_fee: 0,
+            reserve_x,
+            reserve_y,
+        })?;
+
+        // accounts loaded from remaining_accounts are not persisted by Anchor
+        config.exit(&crate::ID)?;
+        oracle.exit(&crate::ID)?;
+
+        Ok((amount_out, user_ata_out.to_account_info(), mint_out.key()))
+    }
+
+    fn pay_in(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, mint: &InterfaceAccount<'info, Mint>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint: mint.to_account_info(),
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_tokens(cpi_ctx, amount, mint.decimals)
+    }
+
+    fn pay_out(&self, config: &Account<'info, Config>, from: AccountInfo<'info>, to: AccountInfo<'info>, mint: &InterfaceAccount<'info, Mint>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_accounts = TransferChecked {
+            from,
+            to,
+            mint: mint.to_account_info(),
+            authority: config.to_account_info(),
+        };
+
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            config.mint_x.as_ref(),
+            config.mint_y.as_ref(),
+            &config.fee_tier.to_le_bytes()[..],
+            &[config.config_bump],
+        ]];
+
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_tokens(cpi_ctx, amount, mint.decimals)
+    }
+}
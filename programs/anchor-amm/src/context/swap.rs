@@ -1,109 +1,431 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
-use constant_product_curve::{ConstantProduct, LiquidityPair};
+use amm_math::SwapResult;
 
 use crate::amm_error::AmmError;
-use crate::state::Config;
+use crate::curve::CurveCalculator;
+use crate::events::Swapped;
+use crate::executor::{SwapExecutor, SwapRequest};
+use crate::math::{fee_amount, max_amount_in_for_price};
+use crate::price::Price;
+use crate::state::{AllowlistEntry, Config, GlobalConfig, HookRegistration, Observations, Oracle, SwapHookArgs, UserStats, Volatility};
+use crate::utils::{bps_of, check_expiration, check_spend_authority, emit_event_cpi, invoke_swap_hook, is_native_mint, measure_delivery, transfer_tokens, unwrap_sol, wrap_sol};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Swap<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    pub mint_x: InterfaceAccount<'info, Mint>,
-    pub mint_y: InterfaceAccount<'info, Mint>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_x,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x
     )]
-    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_y,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y
     )]
-    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"global_config"],
+        bump = global_config.bump
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
     #[account(
         mut,
         associated_token::mint = mint_x,
-        associated_token::authority = config
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
     )]
-    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_y,
-        associated_token::authority = config
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
+    )]
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+    #[account(mut)]
+    pub referrer_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // receives the output instead of the user's ATA
+    #[account(mut)]
+    pub recipient_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // pays the input instead of the user's ATA; the user must own it or be its delegate
+    #[account(mut)]
+    pub source_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
     )]
-    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = user)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        token::authority = user,
+        constraint = discount_ata.mint == global_config.discount_mint @ AmmError::InvalidToken
+    )]
+    pub discount_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: validated against the pool's configured oracle in Config::oracle_price
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated against the pool's secondary oracle in Config::oracle_price
+    pub secondary_price_feed: Option<UncheckedAccount<'info>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"user_stats", config.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = 8 + UserStats::INIT_SPACE
+    )]
+    pub user_stats: Option<Box<Account<'info, UserStats>>>,
+    /// CHECK: checked against config.hook_program in Swap::hook_program
+    pub hook_program: Option<UncheckedAccount<'info>>,
+    #[account(
+        seeds = [b"hook", hook_registration.program_id.as_ref()],
+        bump = hook_registration.bump
+    )]
+    pub hook_registration: Option<Account<'info, HookRegistration>>,
+    /// CHECK: address is checked against the instructions sysvar; required by sandwich-guarded pools
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Swap<'info> {
-    pub fn swap(&mut self, amount: u64, min: u64, is_x: bool) -> Result<()> {
-        require!(self.config.locked == false, AmmError::PoolLocked);
-        require!(amount > 0, AmmError::InvalidAmount);
+    pub fn swap(&mut self, amount_in: u64, min_amount_out: u64, is_x: bool, expiration: Option<i64>, native_sol: bool, limit_price: Option<Price>, remaining_accounts: &[AccountInfo<'info>], bumps: &SwapBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
 
-        let mut curve = ConstantProduct::init(
-            self.vault_x.amount,
-            self.vault_y.amount,
-            self.vault_x.amount,
-            self.config.fee,
-            None,
-        )
-        .map_err(AmmError::from)?;
+        let oracle_price = self.config.oracle_price(self.price_feed.as_deref(), self.secondary_price_feed.as_deref(), self.mint_x.decimals, self.mint_y.decimals)?;
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out,
+            signer: self.user.key(),
+            runs_hook: true,
+            batch_settlement: false,
+        };
+        let mut executor = SwapExecutor::begin(
+            &mut self.config,
+            &self.global_config,
+            &request,
+            self.instructions.as_deref(),
+            (self.vault_x.amount, self.vault_y.amount),
+            &mut self.oracle,
+            &self.observations,
+            oracle_price,
+        )?;
+        let (reserve_x, reserve_y) = (executor.reserve_x, executor.reserve_y);
+        let fee = self.global_config.discounted_fee(
+            executor.pool_fee(&self.config, self.volatility.as_deref())?,
+            self.discount_ata.as_ref().map_or(0, |discount_ata| discount_ata.amount),
+            self.user_stats.as_ref().map_or(0, |user_stats| user_stats.tier(&self.config.volume_tiers)),
+        );
 
-        let p = match is_x {
-            true => LiquidityPair::X,
-            false => LiquidityPair::Y,
+        let hook_program = self.hook_program()?;
+        let fee = match hook_program {
+            Some(ref hook_program) => {
+                let args = SwapHookArgs {
+                    config: self.config.key(),
+                    user: self.user.key(),
+                    is_x,
+                    amount_in,
+                    amount_out: 0,
+                    fee_bps: fee,
+                    reserve_x,
+                    reserve_y,
+                };
+                invoke_swap_hook(hook_program, &self.config.to_account_info(), &self.user.to_account_info(), "before_swap", &args)?.unwrap_or(fee)
+            }
+            None => fee,
         };
 
-        let res = curve.swap(p, amount, min).map_err(AmmError::from)?;
+        // with a limit price only the part of the input that keeps the pool at or above
+        // the limit is filled; the rest never leaves the user
+        if let Some(limit_price) = limit_price {
+            self.config.check_product_curve()?;
+            require!(!self.config.pmm_enabled, AmmError::UnsupportedForPmmPool);
+            let (reserve_in, reserve_out) = match is_x {
+                true => (reserve_x, reserve_y),
+                false => (reserve_y, reserve_x),
+            };
+            executor.amount_in = amount_in.min(max_amount_in_for_price(reserve_in, reserve_out, limit_price)?);
+            require!(executor.amount_in > 0, AmmError::PriceLimitReached);
+        }
+
+        let res = executor.quote(&self.config, fee)?;
 
-        require!(res.deposit != 0, AmmError::InvalidAmount);
-        require!(res.withdraw != 0, AmmError::InvalidAmount);
+        // split the fee between LPs, the protocol treasury and an optional referrer
+        let protocol_fee = bps_of(res.fee, self.config.protocol_fee_bps)?;
+        let referral_fee = match self.referrer_ata {
+            Some(ref referrer_ata) => {
+                let mint_in = match is_x {
+                    true => self.mint_x.key(),
+                    false => self.mint_y.key(),
+                };
+                require_keys_eq!(referrer_ata.mint, mint_in, AmmError::InvalidReferrer);
+                bps_of(res.fee, self.config.referral_fee_bps)?
+            }
+            None => 0,
+        };
+        let lp_deposit = res
+            .deposit
+            .checked_sub(protocol_fee)
+            .and_then(|amount| amount.checked_sub(referral_fee))
+            .ok_or(AmmError::Underflow)?;
+
+        let (user_ata_in, user_ata_out, mint_in, mint_out) = match is_x {
+            true => (self.user_ata_x.to_account_info(), self.user_ata_y.to_account_info(), self.mint_x.key(), self.mint_y.key()),
+            false => (self.user_ata_y.to_account_info(), self.user_ata_x.to_account_info(), self.mint_y.key(), self.mint_x.key()),
+        };
+        let (token_program_in, token_program_out) = match is_x {
+            true => (self.token_program_x.to_account_info(), self.token_program_y.to_account_info()),
+            false => (self.token_program_y.to_account_info(), self.token_program_x.to_account_info()),
+        };
+        if let Some(recipient_ata) = self.recipient_ata.as_ref() {
+            require_keys_eq!(recipient_ata.mint, mint_out, AmmError::InvalidToken);
+        }
+        if let Some(source_ata) = self.source_ata.as_ref() {
+            require_keys_eq!(source_ata.mint, mint_in, AmmError::InvalidToken);
+            check_spend_authority(source_ata, &self.user.key(), res.deposit)?;
+        }
+        // a separate source is spent as-is, never topped up from the user's lamports
+        if native_sol && is_native_mint(&mint_in) && self.source_ata.is_none() {
+            wrap_sol(
+                self.user.to_account_info(),
+                user_ata_in,
+                self.system_program.to_account_info(),
+                token_program_in,
+                res.deposit,
+            )?;
+        }
 
         // deposit tokens
-        self.deposit_tokens(is_x, res.deposit)?;
-        // withdraw tokens
-        self.withdraw_tokens(is_x, res.withdraw)?;
-        // transfer fee
+        let received = self.deposit_tokens(is_x, lp_deposit, remaining_accounts)?;
+        if protocol_fee > 0 {
+            let to = match is_x {
+                true => self.protocol_fee_vault_x.to_account_info(),
+                false => self.protocol_fee_vault_y.to_account_info(),
+            };
+            self.transfer_fee(is_x, to, protocol_fee, remaining_accounts)?;
+        }
+        if let Some(referrer_ata) = self.referrer_ata.as_ref().filter(|_| referral_fee > 0) {
+            self.transfer_fee(is_x, referrer_ata.to_account_info(), referral_fee, remaining_accounts)?;
+        }
+
+        let fees_out = protocol_fee.checked_add(referral_fee).ok_or(AmmError::Overflow)?;
+        let (_, withdraw) = executor.settle(&mut self.config, &res, lp_deposit, received, fees_out)?;
+
+        // withdraw the other side of the pair
+        let delivered = self.withdraw_tokens(!is_x, withdraw, remaining_accounts)?;
+        require!(delivered >= min_amount_out, AmmError::SlippageExceeded);
+        // proceeds sent to a separate recipient are left wrapped
+        if native_sol && is_native_mint(&mint_out) && self.recipient_ata.is_none() {
+            unwrap_sol(self.user.to_account_info(), user_ata_out, token_program_out)?;
+        }
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        executor.finish(&self.config, (self.vault_x.amount, self.vault_y.amount), &res, withdraw, self.volatility.as_deref_mut())?;
+
+        let (reserve_x, reserve_y) = (self.config.reserve_x, self.config.reserve_y);
+        if let Some(user_stats) = self.user_stats.as_mut() {
+            let (volume_x, volume_y) = match is_x {
+                true => (res.deposit, withdraw),
+                false => (withdraw, res.deposit),
+            };
+            if user_stats.swap_count == 0 {
+                user_stats.config = self.config.key();
+                user_stats.user = self.user.key();
+                user_stats.bump = bumps.user_stats.unwrap_or_default();
+            }
+            user_stats.record(volume_x, volume_y, Clock::get()?.unix_timestamp);
+        }
+        if let Some(ref hook_program) = hook_program {
+            let args = SwapHookArgs {
+                config: self.config.key(),
+                user: self.user.key(),
+                is_x,
+                amount_in: res.deposit,
+                amount_out: withdraw,
+                fee_bps: executor.fee,
+                reserve_x,
+                reserve_y,
+            };
+            invoke_swap_hook(hook_program, &self.config.to_account_info(), &self.user.to_account_info(), "after_swap", &args)?;
+        }
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Swapped {
+            config: self.config.key(),
+            user: self.user.key(),
+            is_x,
+            amount_in: res.deposit,
+            amount_out: withdraw,
+            fee: res.fee,
+            protocol_fee,
+            referral_fee,
+            reserve_x,
+            reserve_y,
+        })?;
         Ok(())
     }
 
-    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
-        let (from, to, mint, decimals) = match is_x {
+    // The hook only runs while the program passed matches the pool's and is still registered.
+    fn hook_program(&self) -> Result<Option<AccountInfo<'info>>> {
+        let expected = match self.config.hook_program {
+            Some(expected) => expected,
+            None => return Ok(None),
+        };
+
+        let hook_program = self.hook_program.as_ref().ok_or(AmmError::MissingHookProgram)?;
+        require_keys_eq!(hook_program.key(), expected, AmmError::MissingHookProgram);
+        require!(hook_program.executable, AmmError::MissingHookProgram);
+        let registration = self.hook_registration.as_ref().ok_or(AmmError::HookNotRegistered)?;
+        require_keys_eq!(registration.program_id, expected, AmmError::HookNotRegistered);
+        Ok(Some(hook_program.to_account_info()))
+    }
+
+    pub fn quote(config: &Config, reserve_x: u64, reserve_y: u64, is_x: bool, amount_in: u64, min_amount_out: u64, fee: u16, oracle_price: Option<Price>) -> Result<SwapResult> {
+        require!(amount_in != 0, AmmError::InvalidAmount);
+        let (reserve_in, reserve_out) = match is_x {
+            true => (reserve_x, reserve_y),
+            false => (reserve_y, reserve_x),
+        };
+
+        let withdraw = config.curve(is_x, oracle_price)?.amount_out(reserve_in, reserve_out, amount_in, fee)?;
+
+        require!(withdraw != 0, AmmError::InvalidAmount);
+        require!(withdraw >= min_amount_out, AmmError::SlippageExceeded);
+
+        Ok(SwapResult {
+            deposit: amount_in,
+            withdraw,
+            fee: fee_amount(amount_in, fee)?,
+        })
+    }
+
+    // Returns the amount that actually reached the vault.
+    pub fn deposit_tokens(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let (to, mint, decimals, before) = match is_x {
             true => (
-                self.user_ata_x.to_account_info(),
                 self.vault_x.to_account_info(),
                 self.mint_x.to_account_info(),
                 self.mint_x.decimals,
+                self.vault_x.amount,
             ),
             false => (
-                self.user_ata_y.to_account_info(),
                 self.vault_y.to_account_info(),
                 self.mint_y.to_account_info(),
                 self.mint_y.decimals,
+                self.vault_y.amount,
             ),
         };
 
-        let cpi_program = self.token_program.to_account_info();
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
+
+        let cpi_accounts = TransferChecked {
+            from: self.source(is_x),
+            to,
+            mint,
+            authority: self.user.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
+
+        transfer_tokens(cpi_ctx, amount, decimals)?;
+
+        let vault = match is_x {
+            true => &mut self.vault_x,
+            false => &mut self.vault_y,
+        };
+        vault.reload()?;
+
+        Ok(vault.amount.checked_sub(before).ok_or(AmmError::Underflow)?)
+    }
+
+    fn source(&self, is_x: bool) -> AccountInfo<'info> {
+        match (self.source_ata.as_ref(), is_x) {
+            (Some(source_ata), _) => source_ata.to_account_info(),
+            (None, true) => self.user_ata_x.to_account_info(),
+            (None, false) => self.user_ata_y.to_account_info(),
+        }
+    }
+
+    pub fn transfer_fee(&self, is_x: bool, to: AccountInfo<'info>, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let from = self.source(is_x);
+        let (mint, decimals) = match is_x {
+            true => (self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
 
         let cpi_accounts = TransferChecked {
             from,
@@ -112,14 +434,16 @@ impl<'info> Swap<'info> {
             authority: self.user.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts)
+            .with_remaining_accounts(remaining_accounts.to_vec());
 
-        transfer_checked(cpi_ctx, amount, decimals)?;
+        transfer_tokens(cpi_ctx, amount, decimals)?;
 
         Ok(())
     }
 
-    pub fn withdraw_tokens(&mut self, is_x: bool, amount: u64) -> Result<()> {
+    // Returns the amount that reached the recipient, or the user when there is none.
+    pub fn withdraw_tokens(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
         let (from, to, mint, decimals) = match is_x {
             true => (
                 self.vault_x.to_account_info(),
@@ -134,20 +458,34 @@ impl<'info> Swap<'info> {
                 self.mint_y.decimals,
             ),
         };
+        let to = match self.recipient_ata {
+            Some(ref recipient_ata) => recipient_ata.to_account_info(),
+            None => to,
+        };
 
-        let cpi_program = self.token_program.to_account_info();
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
 
         let cpi_accounts = TransferChecked {
             from,
-            to,
+            to: to.clone(),
             mint,
-            authority: self.user.to_account_info(),
+            authority: self.config.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
 
-        transfer_checked(cpi_ctx, amount, decimals)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
 
-        Ok(())
+        measure_delivery(self.config.fee_on_transfer, &to, amount, || transfer_tokens(cpi_ctx, amount, decimals))
     }
 }
@@ -0,0 +1,278 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::amm_error::AmmError;
+use crate::constants::{BPS_DENOMINATOR, MAX_FEE_BPS, MAX_FEE_SHARE_BPS, MAX_JIT_WINDOW_SLOTS, MAX_WITHDRAW_FEE_BPS, TIMELOCK_DELAY, TOTAL_WEIGHT, VOLUME_TIER_COUNT};
+use crate::events::{ChangeProposed, FeeUpdated, JitProtectionUpdated, ProtocolFeeUpdated, HookUpdated, ReferralFeeUpdated, VolumeTiersUpdated, WithdrawFeeUpdated};
+use crate::price::Price;
+use crate::state::{Config, CurveType, OracleType, ParameterChange, Volatility};
+use crate::utils::emit_event_cpi;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFee<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
+        bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+    pub admin_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // required to apply a DynamicFee change that turns the dynamic fee on
+    #[account(
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+}
+
+impl<'info> UpdateFee<'info> {
+    // Records the change and when it may be applied, giving LPs time to exit first.
+    // A new proposal replaces any pending one and restarts the delay.
+    pub fn propose_change(&mut self, change: ParameterChange, bumps: &UpdateFeeBumps) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        // the NFT holder is the authority while one is bound; unbind it to hand over a key
+        if let ParameterChange::Authority(_) = change {
+            require!(self.config.admin_nft_mint.is_none(), AmmError::AdminNftBound);
+        }
+
+        let effective_at = Clock::get()?.unix_timestamp.checked_add(TIMELOCK_DELAY).ok_or(AmmError::Overflow)?;
+        self.config.pending_change = Some(change);
+        self.config.change_effective_at = effective_at;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, ChangeProposed {
+            config: self.config.key(),
+            change,
+            effective_at,
+        })
+    }
+
+    // Values are validated against the pool as it stands when the change lands.
+    pub fn apply_change(&mut self, bumps: &UpdateFeeBumps) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(Clock::get()?.unix_timestamp >= self.config.change_effective_at, AmmError::TimelockNotElapsed);
+
+        let change = self.config.pending_change.take().ok_or(AmmError::NoPendingChange)?;
+        self.config.change_effective_at = 0;
+
+        match change {
+            ParameterChange::Fee(fee) => self.update_fee(fee, bumps),
+            ParameterChange::ProtocolFee(protocol_fee_bps) => self.update_protocol_fee(protocol_fee_bps, bumps),
+            ParameterChange::ReferralFee(referral_fee_bps) => self.update_referral_fee(referral_fee_bps, bumps),
+            ParameterChange::Pmm { enabled, k_bps } => self.configure_pmm(enabled, k_bps),
+            ParameterChange::WithdrawFee { withdraw_fee_bps, to_treasury } => self.update_withdraw_fee(withdraw_fee_bps, to_treasury, bumps),
+            ParameterChange::JitProtection { window_slots, fee_bps } => self.update_jit_protection(window_slots, fee_bps, bumps),
+            ParameterChange::VolumeTiers(thresholds) => self.update_volume_tiers(thresholds, bumps),
+            ParameterChange::Hook(hook_program) => self.update_hook(hook_program, bumps),
+            ParameterChange::Authority(new_authority) => {
+                require!(self.config.admin_nft_mint.is_none(), AmmError::AdminNftBound);
+                self.config.pending_authority = Some(new_authority);
+                Ok(())
+            }
+            ParameterChange::DynamicFee { enabled, min_fee, max_fee } => self.update_dynamic_fee(enabled, min_fee, max_fee),
+            ParameterChange::Lbp { start_weight_x, end_weight_x, start_time, end_time } => self.configure_lbp(start_weight_x, end_weight_x, start_time, end_time),
+            ParameterChange::InventorySpread { spread_max_bps, spread_target } => self.update_inventory_spread(spread_max_bps, spread_target),
+            ParameterChange::LstCurve(enabled) => self.set_lst_curve(enabled),
+        }
+    }
+
+    pub fn cancel_change(&mut self) -> Result<()> {
+        self.config.check_authority(self.authority.key(), self.admin_nft_ata.as_deref())?;
+        require!(self.config.pending_change.is_some(), AmmError::NoPendingChange);
+
+        self.config.pending_change = None;
+        self.config.change_effective_at = 0;
+        Ok(())
+    }
+
+    fn update_fee(&mut self, fee: u16, bumps: &UpdateFeeBumps) -> Result<()> {
+        require!(fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+
+        let old_fee = self.config.fee;
+        self.config.fee = fee;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, FeeUpdated {
+            config: self.config.key(),
+            old_fee,
+            new_fee: fee,
+        })?;
+
+        Ok(())
+    }
+
+    fn update_protocol_fee(&mut self, protocol_fee_bps: u16, bumps: &UpdateFeeBumps) -> Result<()> {
+        require!(
+            protocol_fee_bps as u32 + self.config.referral_fee_bps as u32 <= MAX_FEE_SHARE_BPS as u32,
+            AmmError::InvalidFee
+        );
+
+        let old_protocol_fee_bps = self.config.protocol_fee_bps;
+        self.config.protocol_fee_bps = protocol_fee_bps;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, ProtocolFeeUpdated {
+            config: self.config.key(),
+            old_protocol_fee_bps,
+            new_protocol_fee_bps: protocol_fee_bps,
+        })?;
+
+        Ok(())
+    }
+
+    fn update_referral_fee(&mut self, referral_fee_bps: u16, bumps: &UpdateFeeBumps) -> Result<()> {
+        require!(
+            referral_fee_bps as u32 + self.config.protocol_fee_bps as u32 <= MAX_FEE_SHARE_BPS as u32,
+            AmmError::InvalidFee
+        );
+
+        let old_referral_fee_bps = self.config.referral_fee_bps;
+        self.config.referral_fee_bps = referral_fee_bps;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, ReferralFeeUpdated {
+            config: self.config.key(),
+            old_referral_fee_bps,
+            new_referral_fee_bps: referral_fee_bps,
+        })?;
+
+        Ok(())
+    }
+
+    fn update_withdraw_fee(&mut self, withdraw_fee_bps: u16, to_treasury: bool, bumps: &UpdateFeeBumps) -> Result<()> {
+        require!(withdraw_fee_bps <= MAX_WITHDRAW_FEE_BPS, AmmError::InvalidFee);
+
+        let old_withdraw_fee_bps = self.config.withdraw_fee_bps;
+        self.config.withdraw_fee_bps = withdraw_fee_bps;
+        self.config.withdraw_fee_to_treasury = to_treasury;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, WithdrawFeeUpdated {
+            config: self.config.key(),
+            old_withdraw_fee_bps,
+            new_withdraw_fee_bps: withdraw_fee_bps,
+            to_treasury,
+        })?;
+
+        Ok(())
+    }
+
+    fn update_jit_protection(&mut self, window_slots: u64, fee_bps: u16, bumps: &UpdateFeeBumps) -> Result<()> {
+        require!(window_slots <= MAX_JIT_WINDOW_SLOTS, AmmError::InvalidAmount);
+        require!(fee_bps <= MAX_WITHDRAW_FEE_BPS, AmmError::InvalidFee);
+
+        self.config.jit_window_slots = window_slots;
+        self.config.jit_fee_bps = fee_bps;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, JitProtectionUpdated {
+            config: self.config.key(),
+            window_slots,
+            fee_bps,
+        })?;
+
+        Ok(())
+    }
+
+    fn update_volume_tiers(&mut self, thresholds: [u64; VOLUME_TIER_COUNT], bumps: &UpdateFeeBumps) -> Result<()> {
+        // set tiers must be strictly ascending and come before any unset ones
+        let set = thresholds.iter().take_while(|threshold| **threshold != 0).count();
+        require!(thresholds[set..].iter().all(|threshold| *threshold == 0), AmmError::InvalidAmount);
+        require!(thresholds[..set].windows(2).all(|pair| pair[0] < pair[1]), AmmError::InvalidAmount);
+
+        self.config.volume_tiers = thresholds;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, VolumeTiersUpdated {
+            config: self.config.key(),
+            thresholds,
+        })
+    }
+
+    fn update_hook(&mut self, hook_program: Pubkey, bumps: &UpdateFeeBumps) -> Result<()> {
+        let hook_program = (hook_program != Pubkey::default()).then_some(hook_program);
+        self.config.hook_program = hook_program;
+
+        emit_event_cpi(&self.event_authority, bumps.event_authority, HookUpdated {
+            config: self.config.key(),
+            hook_program,
+        })
+    }
+
+    fn update_dynamic_fee(&mut self, enabled: bool, min_fee: u16, max_fee: u16) -> Result<()> {
+        require!(min_fee <= max_fee && max_fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        require!(!enabled || self.volatility.is_some(), AmmError::MissingVolatilityAccount);
+
+        self.config.dynamic_fee = enabled;
+        self.config.min_fee = min_fee;
+        self.config.max_fee = max_fee;
+        Ok(())
+    }
+
+    // The timelock already gave LPs notice, so the sale may start as soon as it lands.
+    fn configure_lbp(&mut self, start_weight_x: u16, end_weight_x: u16, start_time: i64, end_time: i64) -> Result<()> {
+        require!(!self.config.pmm_enabled, AmmError::UnsupportedForPmmPool);
+        require!(!matches!(self.config.curve_type, CurveType::Stable | CurveType::Lst), AmmError::UnsupportedForStablePool);
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(start_time >= now && end_time > start_time, AmmError::InvalidLbpSchedule);
+        // a running sale can't have its schedule changed underneath buyers
+        require!(!self.config.lbp_enabled() || self.config.lbp_start_time > now, AmmError::InvalidLbpSchedule);
+        require!(
+            start_weight_x != 0 && start_weight_x < TOTAL_WEIGHT && end_weight_x != 0 && end_weight_x < TOTAL_WEIGHT,
+            AmmError::InvalidWeights
+        );
+
+        self.config.lbp_start_time = start_time;
+        self.config.lbp_end_time = end_time;
+        self.config.lbp_start_weight_x = start_weight_x;
+        self.config.lbp_end_weight_x = end_weight_x;
+        self.config.curve_type = CurveType::Weighted;
+        Ok(())
+    }
+
+    // The spread reads the target off the constant-product spot price, so other curves
+    // are refused. A spread_target of zero follows the oracle and skips the skew while
+    // no oracle price is available.
+    fn update_inventory_spread(&mut self, spread_max_bps: u16, spread_target: Price) -> Result<()> {
+        require!(spread_max_bps <= MAX_FEE_BPS, AmmError::InvalidFee);
+        if spread_max_bps != 0 {
+            self.config.check_product_curve()?;
+            require!(spread_target.0 != 0 || self.config.oracle_type != OracleType::None, AmmError::MissingPriceFeed);
+        }
+
+        self.config.spread_max_bps = spread_max_bps;
+        self.config.spread_target = spread_target;
+        Ok(())
+    }
+
+    // Moves a stable pool onto the LST curve, which centres liquidity on the oracle's
+    // exchange rate of x in y instead of par, or back off it.
+    fn set_lst_curve(&mut self, enabled: bool) -> Result<()> {
+        require!(matches!(self.config.curve_type, CurveType::Stable | CurveType::Lst), AmmError::InvalidConfig);
+        if enabled {
+            require!(self.config.oracle_type != OracleType::None, AmmError::MissingPriceFeed);
+        }
+
+        self.config.curve_type = match enabled {
+            true => CurveType::Lst,
+            false => CurveType::Stable,
+        };
+        Ok(())
+    }
+
+    // k_bps is the PMM slippage coefficient: 0 trades flat at the oracle price, 10_000
+    // spreads depth like a constant-product pool centred on it.
+    fn configure_pmm(&mut self, enabled: bool, k_bps: u16) -> Result<()> {
+        require!(k_bps as u128 <= BPS_DENOMINATOR, AmmError::InvalidAmount);
+        if enabled {
+            require!(self.config.oracle_type != OracleType::None, AmmError::MissingPriceFeed);
+            self.config.check_product_curve()?;
+        }
+
+        self.config.pmm_enabled = enabled;
+        if self.config.check_product_curve().is_ok() {
+            self.config.curve_type = match enabled {
+                true => CurveType::Pmm,
+                false => CurveType::ConstantProduct,
+            };
+        }
+        self.config.pmm_k_bps = k_bps;
+        Ok(())
+    }
+}
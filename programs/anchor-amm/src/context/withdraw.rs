@@ -2,125 +2,411 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
-        burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+        burn, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
     },
 };
 
-use constant_product_curve::ConstantProduct;
-
-use crate::state::Config;
+use crate::state::{AllowlistEntry, Config, DepositRecord, GlobalConfig, Observations, Oracle, Volatility};
 use crate::amm_error::AmmError;
+use crate::constants::{PAUSE_EMERGENCY, PAUSE_WITHDRAW};
+use crate::events::Withdrawn;
+use crate::executor::{SwapExecutor, SwapRequest};
+use crate::math::{fee_amount, withdraw_amounts};
+use crate::utils::{bps_of, check_expiration, emit_event_cpi, is_native_mint, measure_delivery, transfer_tokens, unwrap_sol};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    pub mint_x: InterfaceAccount<'info, Mint>,
-    pub mint_y: InterfaceAccount<'info, Mint>,
+    pub mint_x: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_y: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_x,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program_x
     )]
-    pub user_ata_x: InterfaceAccount<'info, TokenAccount>,
+    pub user_ata_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         init_if_needed,
         payer = user,
         associated_token::mint = mint_y,
-        associated_token::authority = user
+        associated_token::authority = user,
+        associated_token::token_program = token_program_y
     )]
-    pub user_ata_y: InterfaceAccount<'info, TokenAccount>,
+    pub user_ata_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_x,
-        associated_token::authority = config
+        associated_token::authority = config,
+        associated_token::token_program = token_program_x
     )]
-    pub vault_x: InterfaceAccount<'info, TokenAccount>,
+    pub vault_x: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         associated_token::mint = mint_y,
-        associated_token::authority = config
+        associated_token::authority = config,
+        associated_token::token_program = token_program_y
     )]
-    pub vault_y: InterfaceAccount<'info, TokenAccount>,
+    pub vault_y: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
+        mut,
         has_one = mint_x,
         has_one = mint_y,
-        seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+        has_one = lp_mint,
+        seeds = [b"config", config.mint_x.as_ref(), config.mint_y.as_ref(), config.fee_tier.to_le_bytes().as_ref()],
         bump = config.config_bump
     )]
     pub config: Account<'info, Config>,
     #[account(
-        seeds = [b"lp", config.key().as_ref()],
-        bump = config.lp_bump
+        seeds = [b"global_config"],
+        bump = global_config.bump
     )]
-    pub lp_mint: InterfaceAccount<'info, Mint>,
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+    #[account(mut)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
+        mut,
         associated_token::mint = lp_mint,
         associated_token::authority = user
     )]
-    pub user_lp_ata: InterfaceAccount<'info, TokenAccount>,
+    pub user_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::mint = mint_x,
+        token::token_program = token_program_x
+    )]
+    pub recipient_ata_x: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        token::mint = mint_y,
+        token::token_program = token_program_y
+    )]
+    pub recipient_ata_y: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"oracle", config.key().as_ref()],
+        bump = oracle.bump
+    )]
+    pub oracle: Account<'info, Oracle>,
+    #[account(
+        mut,
+        seeds = [b"observations", config.key().as_ref()],
+        bump = observations.load()?.bump
+    )]
+    pub observations: AccountLoader<'info, Observations>,
+    #[account(
+        seeds = [b"treasury", config.key().as_ref()],
+        bump = config.treasury_bump
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_x
+    )]
+    pub protocol_fee_vault_x: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_y
+    )]
+    pub protocol_fee_vault_y: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// CHECK: the user's deposit record, which does not exist if they never deposited
+    #[account(
+        seeds = [b"deposit_record", config.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub deposit_record: UncheckedAccount<'info>,
+    // the accounts below are only read by withdraw_single, whose swap leg runs like any other swap
+    #[account(
+        seeds = [b"allowlist", config.key().as_ref(), user.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    #[account(token::authority = user)]
+    pub badge_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"volatility", config.key().as_ref()],
+        bump = volatility.bump
+    )]
+    pub volatility: Option<Account<'info, Volatility>>,
+    /// CHECK: validated against the pool's configured oracle in Config::oracle_price
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: validated against the pool's secondary oracle in Config::oracle_price
+    pub secondary_price_feed: Option<UncheckedAccount<'info>>,
+    /// CHECK: address is checked against the instructions sysvar; required by sandwich-guarded pools
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_x: Interface<'info, TokenInterface>,
+    pub token_program_y: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Withdraw<'info> {
-    pub fn withdraw(&mut self, amount: u64, min_x: u64, min_y: u64) -> Result<()> {
-
-        require!(self.config.locked == false, AmmError::PoolLocked);
+    pub fn withdraw(&mut self, amount: u64, min_x: u64, min_y: u64, expiration: Option<i64>, native_sol: bool, remaining_accounts: &[AccountInfo<'info>], bumps: &WithdrawBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_WITHDRAW)?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
         require!(amount != 0, AmmError::InvalidAmount);
         require!(min_x != 0 || min_y != 0, AmmError::InvalidAmount);
 
 
-        let ammounts = ConstantProduct::xy_withdraw_amounts_from_l(
-            self.vault_x.amount,
-            self.vault_y.amount,
-            self.lp_mint.supply,
-            amount,
-            6,
+        let (amount_x, amount_y) = withdraw_amounts(self.config.reserve_x, self.config.reserve_y, self.lp_mint.supply, amount)?;
+        let jit_fee_bps = self.jit_fee_bps()?;
+        let amount_x = self.take_withdraw_fee(true, amount_x, jit_fee_bps, remaining_accounts)?;
+        let amount_y = self.take_withdraw_fee(false, amount_y, jit_fee_bps, remaining_accounts)?;
+
+        let received_x = self.withdraw_token(true, amount_x, remaining_accounts)?;
+        let received_y = self.withdraw_token(false, amount_y, remaining_accounts)?;
+        require!(received_x >= min_x && received_y >= min_y, AmmError::SlippageExceeded);
+        self.burn_lp_tokens(amount)?;
+        self.emit_withdrawn(received_x, received_y, amount, bumps)?;
+
+        if native_sol {
+            self.unwrap_native()?;
+        }
+        Ok(())
+    }
+
+    fn unwrap_native(&self) -> Result<()> {
+        // proceeds sent to a separate recipient are left wrapped
+        let (user_ata, token_program) = match (is_native_mint(&self.mint_x.key()), is_native_mint(&self.mint_y.key())) {
+            (true, _) if self.recipient_ata_x.is_none() => (self.user_ata_x.to_account_info(), self.token_program_x.to_account_info()),
+            (_, true) if self.recipient_ata_y.is_none() => (self.user_ata_y.to_account_info(), self.token_program_y.to_account_info()),
+            _ => return Ok(()),
+        };
+
+        unwrap_sol(
+            self.user.to_account_info(),
+            user_ata,
+            token_program,
         )
-        .map_err(AmmError::from)?;
+    }
 
-        require!(min_x < ammounts.x && min_y < ammounts.y, AmmError::SlippageExceeded);
+    pub fn withdraw_single(&mut self, is_x: bool, amount: u64, min_amount_out: u64, expiration: Option<i64>, remaining_accounts: &[AccountInfo<'info>], bumps: &WithdrawBumps) -> Result<()> {
+        check_expiration(expiration)?;
+        self.config.check_not_paused(PAUSE_WITHDRAW)?;
+        require!(!self.config.flash_active(), AmmError::FlashActive);
+        require!(self.config.batch_window_secs == 0, AmmError::BatchModePool);
+        require!(!self.config.position_mode, AmmError::PositionModePool);
+        self.config.rebase(self.vault_x.amount, self.vault_y.amount)?;
+        self.oracle.update(&mut *self.observations.load_mut()?, self.config.reserve_x, self.config.reserve_y)?;
+        self.config.check_product_curve()?;
+        require!(amount != 0, AmmError::InvalidAmount);
 
+        let (amount_x, amount_y) = withdraw_amounts(self.config.reserve_x, self.config.reserve_y, self.lp_mint.supply, amount)?;
 
+        // the other side never leaves the vault: it is swapped back into the pool
+        // against the remaining reserves and the output is paid out in the chosen token
+        let (withdrawn, other) = match is_x {
+            true => (amount_x, amount_y),
+            false => (amount_y, amount_x),
+        };
+        self.config.debit_reserve(true, amount_x)?;
+        self.config.debit_reserve(false, amount_y)?;
+        let swapped = match other {
+            0 => 0,
+            _ => self.swap_back(!is_x, other, amount_x, amount_y, remaining_accounts)?,
+        };
+        let amount_out = withdrawn.checked_add(swapped).ok_or(AmmError::Overflow)?;
+        // handed back so the fees and the payout are booked as withdraw books them
+        self.config.credit_reserve(is_x, amount_out)?;
+        let jit_fee_bps = self.jit_fee_bps()?;
+        let amount_out = self.take_withdraw_fee(is_x, amount_out, jit_fee_bps, remaining_accounts)?;
 
-        self.withdraw_token(true, ammounts.x)?;
-        self.withdraw_token(false, ammounts.y)?;
+        require!(amount_out != 0, AmmError::InvalidAmount);
+
+        let received = self.withdraw_token(is_x, amount_out, remaining_accounts)?;
+        require!(received >= min_amount_out, AmmError::SlippageExceeded);
         self.burn_lp_tokens(amount)?;
+        match is_x {
+            true => self.emit_withdrawn(received, 0, amount, bumps)?,
+            false => self.emit_withdrawn(0, received, amount, bumps)?,
+        }
         Ok(())
     }
-    fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
-        let (from, to, mint, decimals) = match is_x {
-            true => (
-                self.vault_x.to_account_info(),
-                self.user_ata_x.to_account_info(),
-                self.mint_x.to_account_info(),
-                self.mint_x.decimals,
-            ),
-            false => (
-                self.vault_y.to_account_info(),
-                self.user_ata_y.to_account_info(),
-                self.mint_y.to_account_info(),
-                self.mint_y.decimals,
-            ),
+
+    // Swaps `amount_in`, which is still in the vault, into the pool as any swap would
+    // and returns the output owed. The reserves are already debited by the withdrawal
+    // of `amount_x` and `amount_y`, which have not left the vaults yet.
+    fn swap_back(&mut self, is_x: bool, amount_in: u64, amount_x: u64, amount_y: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let oracle_price = self.config.oracle_price(self.price_feed.as_deref(), self.secondary_price_feed.as_deref(), self.mint_x.decimals, self.mint_y.decimals)?;
+        let request = SwapRequest {
+            is_x,
+            amount_in,
+            min_amount_out: 0,
+            signer: self.user.key(),
+            runs_hook: false,
+            batch_settlement: false,
         };
+        let balances = (
+            self.vault_x.amount.checked_sub(amount_x).ok_or(AmmError::Underflow)?,
+            self.vault_y.amount.checked_sub(amount_y).ok_or(AmmError::Underflow)?,
+        );
+        let mut executor = SwapExecutor::begin(
+            &mut self.config,
+            &self.global_config,
+            &request,
+            self.instructions.as_deref(),
+            balances,
+            &mut self.oracle,
+            &self.observations,
+            oracle_price,
+        )?;
+        self.config.check_swap_allowed(self.allowlist_entry.as_deref())?;
+        self.config.check_badge(self.badge_ata.as_deref())?;
 
-        let cpi_program = self.token_program.to_account_info();
+        let fee = executor.pool_fee(&self.config, self.volatility.as_deref())?;
+        let res = executor.quote(&self.config, fee)?;
+        let protocol_fee = bps_of(res.fee, self.config.protocol_fee_bps)?;
+        let lp_deposit = res.deposit.checked_sub(protocol_fee).ok_or(AmmError::Underflow)?;
+
+        if protocol_fee > 0 {
+            let protocol_fee_vault = match is_x {
+                true => self.protocol_fee_vault_x.as_ref(),
+                false => self.protocol_fee_vault_y.as_ref(),
+            }
+            .ok_or(AmmError::MissingProtocolFeeVault)?
+            .to_account_info();
+            self.transfer_out(is_x, protocol_fee_vault, protocol_fee, remaining_accounts)?;
+        }
+        let (_, withdraw) = executor.settle(&mut self.config, &res, lp_deposit, lp_deposit, protocol_fee)?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        executor.finish(&self.config, (self.vault_x.amount, self.vault_y.amount), &res, withdraw, self.volatility.as_deref_mut())?;
+        Ok(withdraw)
+    }
+
+    // Once the authority flags the pool as compromised, LPs redeem pro-rata straight
+    // from the vault balances with no fees, skipping the oracle, curve and reserve
+    // bookkeeping that corrupted state could make fail.
+    pub fn emergency_withdraw(&mut self, amount: u64, remaining_accounts: &[AccountInfo<'info>], bumps: &WithdrawBumps) -> Result<()> {
+        require!(self.config.paused & PAUSE_EMERGENCY != 0, AmmError::NotInEmergency);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let (amount_x, amount_y) = withdraw_amounts(self.vault_x.amount, self.vault_y.amount, self.lp_mint.supply, amount)?;
+        require!(amount_x != 0 || amount_y != 0, AmmError::InvalidAmount);
+
+        let received_x = self.transfer_out(true, self.recipient(true), amount_x, remaining_accounts)?;
+        let received_y = self.transfer_out(false, self.recipient(false), amount_y, remaining_accounts)?;
+        self.config.reserve_x = self.config.reserve_x.saturating_sub(amount_x);
+        self.config.reserve_y = self.config.reserve_y.saturating_sub(amount_y);
+        self.burn_lp_tokens(amount)?;
+        self.emit_withdrawn(received_x, received_y, amount, bumps)
+    }
+
+    // Amounts are what reached the recipient, net of any transfer fee. Reserves are
+    // debited by withdraw_token; the LP supply is not reloaded, so it is derived.
+    fn emit_withdrawn(&self, amount_x: u64, amount_y: u64, lp_amount: u64, bumps: &WithdrawBumps) -> Result<()> {
+        emit_event_cpi(&self.event_authority, bumps.event_authority, Withdrawn {
+            config: self.config.key(),
+            user: self.user.key(),
+            amount_x,
+            amount_y,
+            lp_amount,
+            reserve_x: self.config.reserve_x,
+            reserve_y: self.config.reserve_y,
+            lp_supply: self.lp_mint.supply.checked_sub(lp_amount).ok_or(AmmError::Underflow)?,
+        })?;
+        Ok(())
+    }
+
+    // Fee owed for leaving within the JIT window of the user's last deposit.
+    fn jit_fee_bps(&self) -> Result<u16> {
+        if self.config.jit_window_slots == 0 || self.deposit_record.data_is_empty() {
+            return Ok(0);
+        }
+
+        let record = DepositRecord::try_deserialize(&mut &self.deposit_record.try_borrow_data()?[..])?;
+        if !record.in_jit_window(Clock::get()?.slot, self.config.jit_window_slots) {
+            return Ok(0);
+        }
+        require!(self.config.jit_fee_bps != 0, AmmError::WithdrawCooldown);
+        Ok(self.config.jit_fee_bps)
+    }
+
+    // Returns the amount left for the user. A fee kept in the pool stays in both the
+    // vault and the reserves, raising the value of every remaining LP share; the JIT
+    // fee always does.
+    fn take_withdraw_fee(&mut self, is_x: bool, amount: u64, jit_fee_bps: u16, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let jit_fee = fee_amount(amount, jit_fee_bps)?;
+        let fee = fee_amount(amount, self.config.withdraw_fee_bps)?;
+        if fee > 0 && self.config.withdraw_fee_to_treasury {
+            let protocol_fee_vault = match is_x {
+                true => self.protocol_fee_vault_x.as_ref(),
+                false => self.protocol_fee_vault_y.as_ref(),
+            }
+            .ok_or(AmmError::MissingProtocolFeeVault)?
+            .to_account_info();
+            self.transfer_out(is_x, protocol_fee_vault, fee, remaining_accounts)?;
+            self.config.debit_reserve(is_x, fee)?;
+        }
+        // both fees round up, so on dust they can add up to more than the amount
+        amount.checked_sub(fee).and_then(|left| left.checked_sub(jit_fee)).ok_or(error!(AmmError::Underflow))
+    }
+
+    // Returns the amount that reached the recipient.
+    fn withdraw_token(&mut self, is_x: bool, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        let received = self.transfer_out(is_x, self.recipient(is_x), amount, remaining_accounts)?;
+        self.config.debit_reserve(is_x, amount)?;
+        Ok(received)
+    }
+
+    fn recipient(&self, is_x: bool) -> AccountInfo<'info> {
+        match (is_x, &self.recipient_ata_x, &self.recipient_ata_y) {
+            (true, Some(recipient_ata_x), _) => recipient_ata_x.to_account_info(),
+            (true, None, _) => self.user_ata_x.to_account_info(),
+            (false, _, Some(recipient_ata_y)) => recipient_ata_y.to_account_info(),
+            (false, _, None) => self.user_ata_y.to_account_info(),
+        }
+    }
+
+    fn transfer_out(&self, is_x: bool, to: AccountInfo<'info>, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        let (from, mint, decimals) = match is_x {
+            true => (self.vault_x.to_account_info(), self.mint_x.to_account_info(), self.mint_x.decimals),
+            false => (self.vault_y.to_account_info(), self.mint_y.to_account_info(), self.mint_y.decimals),
+        };
+
+        let cpi_program = match is_x {
+            true => self.token_program_x.to_account_info(),
+            false => self.token_program_y.to_account_info(),
+        };
 
         let cpi_accounts = TransferChecked {
             from,
-            to,
+            to: to.clone(),
             mint,
-            authority: self.user.to_account_info(),
+            authority: self.config.to_account_info(),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let signer_seeds: &[&[&[u8]]; 1] = &[&[
+            b"config",
+            self.config.mint_x.as_ref(),
+            self.config.mint_y.as_ref(),
+            &self.config.fee_tier.to_le_bytes()[..],
+            &[self.config.config_bump],
+        ]];
 
-        transfer_checked(cpi_ctx, amount, decimals)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds)
+            .with_remaining_accounts(remaining_accounts.to_vec());
 
-        Ok(())
+        measure_delivery(self.config.fee_on_transfer, &to, amount, || transfer_tokens(cpi_ctx, amount, decimals))
     }
 
     fn burn_lp_tokens(&self, amount: u64) -> Result<()> {
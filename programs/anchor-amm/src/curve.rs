@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+
+use crate::math::{lst_amount_out, pmm_amount_out, stable_amount_out, swap_amount_out, weighted_amount_out};
+use crate::price::Price;
+
+// Swap pricing for one direction of a pool. Deposits and withdrawals are pro rata for
+// every curve, so a new invariant only has to price trades.
+pub trait CurveCalculator {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64>;
+}
+
+pub struct ConstantProduct;
+
+impl CurveCalculator for ConstantProduct {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+        swap_amount_out(reserve_in, reserve_out, amount_in, fee)
+    }
+}
+
+pub struct Stable {
+    pub amp: u64,
+}
+
+impl CurveCalculator for Stable {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+        stable_amount_out(reserve_in, reserve_out, amount_in, fee, self.amp)
+    }
+}
+
+pub struct Weighted {
+    pub weight_in: u16,
+    pub weight_out: u16,
+}
+
+impl CurveCalculator for Weighted {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+        weighted_amount_out(reserve_in, reserve_out, amount_in, self.weight_in, self.weight_out, fee)
+    }
+}
+
+pub struct Pmm {
+    pub oracle_price: Price,
+    pub is_x: bool,
+    pub k_bps: u16,
+}
+
+impl CurveCalculator for Pmm {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+        pmm_amount_out(reserve_in, reserve_out, amount_in, fee, self.oracle_price, self.is_x, self.k_bps)
+    }
+}
+
+pub struct Lst {
+    pub amp: u64,
+    pub rate: Price,
+    pub is_x: bool,
+}
+
+impl CurveCalculator for Lst {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+        lst_amount_out(reserve_in, reserve_out, amount_in, fee, self.amp, self.rate, self.is_x)
+    }
+}
+
+// Built per trade by Config::curve from the pool's CurveType and its live parameters.
+pub enum Curve {
+    ConstantProduct(ConstantProduct),
+    Stable(Stable),
+    Weighted(Weighted),
+    Pmm(Pmm),
+    Lst(Lst),
+}
+
+impl CurveCalculator for Curve {
+    fn amount_out(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+        match self {
+            Curve::ConstantProduct(curve) => curve.amount_out(reserve_in, reserve_out, amount_in, fee),
+            Curve::Stable(curve) => curve.amount_out(reserve_in, reserve_out, amount_in, fee),
+            Curve::Weighted(curve) => curve.amount_out(reserve_in, reserve_out, amount_in, fee),
+            Curve::Pmm(curve) => curve.amount_out(reserve_in, reserve_out, amount_in, fee),
+            Curve::Lst(curve) => curve.amount_out(reserve_in, reserve_out, amount_in, fee),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVE: u64 = 1_000_000_000;
+
+    fn curves() -> Vec<Curve> {
+        vec![
+            Curve::ConstantProduct(ConstantProduct),
+            Curve::Stable(Stable { amp: 100 }),
+            Curve::Weighted(Weighted { weight_in: 8_000, weight_out: 2_000 }),
+            Curve::Pmm(Pmm { oracle_price: Price::ONE, is_x: true, k_bps: 5_000 }),
+            Curve::Lst(Lst { amp: 100, rate: Price::from_ratio(11, 10).unwrap(), is_x: true }),
+        ]
+    }
+
+    #[test]
+    fn constant_product_keeps_k() {
+        let out = ConstantProduct.amount_out(RESERVE, RESERVE, 1_000_000, 0).unwrap();
+        assert!((RESERVE as u128 + 1_000_000) * (RESERVE - out) as u128 >= RESERVE as u128 * RESERVE as u128);
+        assert_eq!(out, 999_000);
+    }
+
+    #[test]
+    fn stable_trades_near_par_when_balanced() {
+        let stable = Stable { amp: 100 }.amount_out(RESERVE, RESERVE, 1_000_000, 0).unwrap();
+        let constant_product = ConstantProduct.amount_out(RESERVE, RESERVE, 1_000_000, 0).unwrap();
+        assert!(stable > constant_product);
+        assert!(stable < 1_000_000 && stable > 999_900);
+    }
+
+    #[test]
+    fn stable_holds_its_invariant() {
+        let amp = 100;
+        let d_before = crate::math::stable_invariant(RESERVE, RESERVE / 2, amp).unwrap();
+        let out = Stable { amp }.amount_out(RESERVE, RESERVE / 2, 50_000_000, 0).unwrap();
+        let d_after = crate::math::stable_invariant(RESERVE + 50_000_000, RESERVE / 2 - out, amp).unwrap();
+        assert!(d_after + 1 >= d_before);
+    }
+
+    #[test]
+    fn weighted_with_equal_weights_tracks_constant_product() {
+        let weighted = Weighted { weight_in: 5_000, weight_out: 5_000 }.amount_out(RESERVE, RESERVE, 1_000_000, 30).unwrap();
+        let constant_product = ConstantProduct.amount_out(RESERVE, RESERVE, 1_000_000, 30).unwrap();
+        assert!(weighted.abs_diff(constant_product) <= 2);
+    }
+
+    #[test]
+    fn pmm_without_slippage_trades_at_the_oracle_price() {
+        let pmm = Pmm { oracle_price: Price::from_ratio(2, 1).unwrap(), is_x: true, k_bps: 0 };
+        assert_eq!(pmm.amount_out(RESERVE, RESERVE, 1_000_000, 0).unwrap(), 2_000_000);
+    }
+
+    #[test]
+    fn lst_trades_near_the_exchange_rate_when_balanced_at_it() {
+        let rate = Price::from_ratio(11, 10).unwrap();
+        // 1.1 SOL per LST, so 1_000 LST balance 1_100 SOL
+        let (lst, sol) = (RESERVE, RESERVE / 10 * 11);
+        let sol_out = Lst { amp: 100, rate, is_x: true }.amount_out(lst, sol, 1_000_000, 0).unwrap();
+        assert!(sol_out < 1_100_000 && sol_out > 1_099_800);
+        let lst_out = Lst { amp: 100, rate, is_x: false }.amount_out(sol, lst, 1_100_000, 0).unwrap();
+        assert!(lst_out < 1_000_000 && lst_out > 999_800);
+    }
+
+    #[test]
+    fn every_curve_charges_the_fee_and_never_drains_the_pool() {
+        for curve in curves() {
+            let without_fee = curve.amount_out(RESERVE, RESERVE, 1_000_000, 0).unwrap();
+            let with_fee = curve.amount_out(RESERVE, RESERVE, 1_000_000, 30).unwrap();
+            assert!(with_fee < without_fee);
+            assert!(curve.amount_out(RESERVE, RESERVE, RESERVE, 30).unwrap() < RESERVE);
+        }
+    }
+}
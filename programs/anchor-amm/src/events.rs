@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::VOLUME_TIER_COUNT;
+use crate::price::Price;
+use crate::state::ParameterChange;
+
+#[event]
+pub struct ChangeProposed {
+    pub config: Pubkey,
+    pub change: ParameterChange,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub config: Pubkey,
+    pub old_fee: u16,
+    pub new_fee: u16,
+}
+
+#[event]
+pub struct ProtocolFeeUpdated {
+    pub config: Pubkey,
+    pub old_protocol_fee_bps: u16,
+    pub new_protocol_fee_bps: u16,
+}
+
+#[event]
+pub struct ReferralFeeUpdated {
+    pub config: Pubkey,
+    pub old_referral_fee_bps: u16,
+    pub new_referral_fee_bps: u16,
+}
+
+#[event]
+pub struct WithdrawFeeUpdated {
+    pub config: Pubkey,
+    pub old_withdraw_fee_bps: u16,
+    pub new_withdraw_fee_bps: u16,
+    pub to_treasury: bool,
+}
+
+#[event]
+pub struct HookUpdated {
+    pub config: Pubkey,
+    pub hook_program: Option<Pubkey>,
+}
+
+#[event]
+pub struct VolumeTiersUpdated {
+    pub config: Pubkey,
+    pub thresholds: [u64; VOLUME_TIER_COUNT],
+}
+
+#[event]
+pub struct JitProtectionUpdated {
+    pub config: Pubkey,
+    pub window_slots: u64,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub config: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub lp_mint: Pubkey,
+    pub fee_tier: u16,
+    pub protocol_fee_bps: u16,
+    pub weight_x: u16,
+    pub weight_y: u16,
+    pub authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct Deposited {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_amount: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_amount: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct Swapped {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub protocol_fee: u64,
+    pub referral_fee: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+#[event]
+pub struct FlashLoanRepaid {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct LiquidityLocked {
+    pub config: Pubkey,
+    pub lock: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub locked_at: i64,
+    pub unlock_at: i64,
+    pub vesting: bool,
+}
+
+#[event]
+pub struct LiquidityUnlocked {
+    pub config: Pubkey,
+    pub lock: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct PositionFeesCollected {
+    pub config: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct PositionCompounded {
+    pub config: Pubkey,
+    pub position: Pubkey,
+    pub caller: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub liquidity: u64,
+    pub bounty_x: u64,
+    pub bounty_y: u64,
+}
+
+#[event]
+pub struct BuybackExecuted {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_burned: u64,
+}
+
+#[event]
+pub struct MerkleClaimed {
+    pub distribution: Pubkey,
+    pub claimant: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BatchSettled {
+    pub config: Pubkey,
+    pub batch_id: u64,
+    pub total_x_in: u64,
+    pub total_y_in: u64,
+    pub amount_x_out: u64,
+    pub amount_y_out: u64,
+}
+
+#[event]
+pub struct LaunchGraduated {
+    pub config: Pubkey,
+    pub launch: Pubkey,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub lp_locked: u64,
+    pub base_burned: u64,
+}
+
+#[event]
+pub struct PriceAuctionSettled {
+    pub config: Pubkey,
+    pub auction: Pubkey,
+    pub price: Price,
+    pub sold_amount: u64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+#[event]
+pub struct AuthorityRenounced {
+    pub config: Pubkey,
+    pub authority: Pubkey,
+}
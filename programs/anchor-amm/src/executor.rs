@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+use amm_math::SwapResult;
+
+use crate::amm_error::AmmError;
+use crate::constants::{MAX_FEE_BPS, PAUSE_SWAP};
+use crate::context::Swap;
+use crate::price::Price;
+use crate::state::{Config, GlobalConfig, Observations, Oracle, Volatility};
+use crate::utils::require_sole_swapper;
+
+// What a swap path asks of the pool. The path moves the tokens itself.
+pub struct SwapRequest {
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    // the wallet a sandwich-guarded pool protects: cranks pass the order's owner, and
+    // paths no wallet stands behind pass Pubkey::default() so no other swap may join them
+    pub signer: Pubkey,
+    // only swap carries the hook accounts
+    pub runs_hook: bool,
+    // settle_batch is the one path allowed to trade a batch-mode pool
+    pub batch_settlement: bool,
+}
+
+// The pool side shared by every swap path: the entry checks, pricing against reserves
+// snapshotted once, booking what reached the vault, and the post-trade checks.
+pub struct SwapExecutor {
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub oracle_price: Option<Price>,
+    pub fee: u16,
+    k_before: u128,
+}
+
+impl SwapExecutor {
+    // `balances` are the vault balances before anything moves.
+    pub fn begin<'info>(
+        config: &mut Account<'info, Config>,
+        global_config: &GlobalConfig,
+        request: &SwapRequest,
+        instructions: Option<&AccountInfo<'info>>,
+        balances: (u64, u64),
+        oracle: &mut Oracle,
+        observations: &AccountLoader<'info, Observations>,
+        oracle_price: Option<Price>,
+    ) -> Result<Self> {
+        config.check_not_paused(PAUSE_SWAP)?;
+        global_config.check_not_paused()?;
+        require!(!config.flash_active(), AmmError::FlashActive);
+        require!(config.batch_window_secs == 0 || request.batch_settlement, AmmError::BatchModePool);
+        require!(!config.launch_pending, AmmError::LaunchPending);
+        require!(config.hook_program.is_none() || request.runs_hook, AmmError::HookedPoolRequiresSwap);
+        require!(request.amount_in > 0, AmmError::InvalidAmount);
+        if config.sandwich_guard {
+            let instructions = instructions.ok_or(AmmError::MissingInstructionsSysvar)?;
+            require_sole_swapper(instructions, &config.key(), &request.signer)?;
+        }
+
+        config.rebase(balances.0, balances.1)?;
+        let (reserve_x, reserve_y) = (config.reserve_x, config.reserve_y);
+        oracle.update(&mut *observations.load_mut()?, reserve_x, reserve_y)?;
+
+        Ok(Self {
+            is_x: request.is_x,
+            amount_in: request.amount_in,
+            min_amount_out: request.min_amount_out,
+            reserve_x,
+            reserve_y,
+            oracle_price,
+            fee: 0,
+            k_before: config.k(),
+        })
+    }
+
+    // The pool's own fee, before any discount or hook override.
+    pub fn pool_fee(&self, config: &Config, volatility: Option<&Volatility>) -> Result<u16> {
+        config.current_fee(volatility, self.is_x, self.oracle_price)
+    }
+
+    pub fn quote(&mut self, config: &Config, fee: u16) -> Result<SwapResult> {
+        require!(fee <= MAX_FEE_BPS, AmmError::InvalidFee);
+        self.fee = fee;
+        self.quote_amount(config, self.amount_in)
+    }
+
+    // A zap swaps only part of the request's input, sized once the fee is known.
+    pub fn quote_zap(&mut self, config: &Config, fee: u16, amount_in: u64) -> Result<SwapResult> {
+        require!(amount_in <= self.amount_in, AmmError::InvalidAmount);
+        self.amount_in = amount_in;
+        self.quote(config, fee)
+    }
+
+    fn quote_amount(&self, config: &Config, amount_in: u64) -> Result<SwapResult> {
+        Swap::quote(config, self.reserve_x, self.reserve_y, self.is_x, amount_in, self.min_amount_out, self.fee, self.oracle_price)
+    }
+
+    // A transfer fee on the input mint leaves the vault short of lp_deposit, so the
+    // output is re-quoted against what the pool actually received. `fees_out` is the
+    // part of the fee paid away from the pool. Returns the shortfall and the output owed.
+    pub fn settle(&self, config: &mut Config, res: &SwapResult, lp_deposit: u64, received: u64, fees_out: u64) -> Result<(u64, u64)> {
+        let shortfall = lp_deposit.checked_sub(received).ok_or(AmmError::Underflow)?;
+        let withdraw = match shortfall {
+            0 => res.withdraw,
+            _ => self.quote_amount(config, res.deposit.checked_sub(shortfall).ok_or(AmmError::Underflow)?)?.withdraw,
+        };
+
+        config.record_swap(self.is_x, res.deposit, res.fee)?;
+        config.credit_reserve(self.is_x, received)?;
+        config.debit_reserve(!self.is_x, withdraw)?;
+        config.accrue_position_fee(self.is_x, res.fee.saturating_sub(fees_out.saturating_add(shortfall)))?;
+        Ok((shortfall, withdraw))
+    }
+
+    // `balances` are the vault balances once every transfer has landed.
+    pub fn finish(&self, config: &Config, balances: (u64, u64), res: &SwapResult, withdraw: u64, volatility: Option<&mut Volatility>) -> Result<()> {
+        config.check_invariant(self.k_before, balances.0, balances.1)?;
+        config.check_price_impact(self.reserve_x, self.reserve_y)?;
+        config.check_oracle_price(self.oracle_price, self.is_x, res.deposit, withdraw)?;
+        config.update_volatility(volatility)
+    }
+}
@@ -3,6 +3,19 @@ use anchor_lang::prelude::*;
 pub mod state;
 pub mod context;
 pub mod amm_error;
+pub mod constants;
+pub mod curve;
+pub mod events;
+pub mod executor;
+pub mod math;
+pub mod price;
+pub mod price_feed;
+pub mod utils;
+
+pub use context::*;
+use constants::{HOLDING_TIER_COUNT, VOLUME_TIER_COUNT};
+use price::Price;
+use state::{OracleType, ParameterChange, SwapPermit, SwapReveal};
 
 
 
@@ -12,11 +25,447 @@ declare_id!("EwXDx5TcTyKHHGhhyXy1G3x97y785kXYBDe3beiDbqgY");
 pub mod anchor_amm {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        msg!("Greetings from: {:?}", ctx.program_id);
-        Ok(())
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        fee_tier: u16,
+        protocol_fee_bps: Option<u16>,
+        authority: Option<Pubkey>,
+        weight_x: u16,
+        weight_y: u16,
+        lp_token_2022: Option<LpMintConfig>,
+        position_mode: bool,
+        amp: u64,
+    ) -> Result<()> {
+        ctx.accounts.init(fee_tier, protocol_fee_bps, authority, weight_x, weight_y, lp_token_2022, position_mode, amp, &ctx.bumps)
+    }
+
+    pub fn deposit<'info>(ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>, amount: u64, max_x: u64, max_y: u64, expiration: Option<i64>, native_sol: bool) -> Result<()> {
+        ctx.accounts.deposit(amount, max_x, max_y, expiration, native_sol, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn deposit_single<'info>(ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>, is_x: bool, amount_in: u64, min_lp_out: u64, expiration: Option<i64>, native_sol: bool) -> Result<()> {
+        ctx.accounts.deposit_single(is_x, amount_in, min_lp_out, expiration, native_sol, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn deposit_imbalanced<'info>(ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>, amount_x: u64, amount_y: u64, min_lp_out: u64, expiration: Option<i64>, native_sol: bool) -> Result<()> {
+        ctx.accounts.deposit_imbalanced(amount_x, amount_y, min_lp_out, expiration, native_sol, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, amount: u64, min_x: u64, min_y: u64, expiration: Option<i64>, native_sol: bool) -> Result<()> {
+        ctx.accounts.withdraw(amount, min_x, min_y, expiration, native_sol, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn withdraw_single<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, is_x: bool, amount: u64, min_amount_out: u64, expiration: Option<i64>) -> Result<()> {
+        ctx.accounts.withdraw_single(is_x, amount, min_amount_out, expiration, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn swap<'info>(ctx: Context<'_, '_, 'info, 'info, Swap<'info>>, amount_in: u64, min_amount_out: u64, is_x: bool, expiration: Option<i64>, native_sol: bool, limit_price: Option<Price>) -> Result<()> {
+        ctx.accounts.swap(amount_in, min_amount_out, is_x, expiration, native_sol, limit_price, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn swap_with_permit(ctx: Context<SwapWithPermit>, permit: SwapPermit) -> Result<()> {
+        ctx.accounts.swap_with_permit(permit, &ctx.bumps)
+    }
+
+    pub fn lock_pool(ctx: Context<Lock>) -> Result<()> {
+        ctx.accounts.lock()
+    }
+
+    pub fn unlock_pool(ctx: Context<Lock>) -> Result<()> {
+        ctx.accounts.unlock()
+    }
+
+    pub fn set_pause_flags(ctx: Context<Lock>, flags: u8) -> Result<()> {
+        ctx.accounts.set_pause_flags(flags)
+    }
+
+    pub fn propose_change(ctx: Context<UpdateFee>, change: ParameterChange) -> Result<()> {
+        ctx.accounts.propose_change(change, &ctx.bumps)
+    }
+
+    pub fn apply_change(ctx: Context<UpdateFee>) -> Result<()> {
+        ctx.accounts.apply_change(&ctx.bumps)
+    }
+
+    pub fn cancel_change(ctx: Context<UpdateFee>) -> Result<()> {
+        ctx.accounts.cancel_change()
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        ctx.accounts.accept_authority()
+    }
+
+    pub fn renounce_authority(ctx: Context<RenounceAuthority>) -> Result<()> {
+        ctx.accounts.renounce_authority(&ctx.bumps)
+    }
+
+    pub fn set_admin_nft(ctx: Context<SetAdminNft>) -> Result<()> {
+        ctx.accounts.set_admin_nft()
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        ctx.accounts.close_pool()
+    }
+
+    pub fn collect_fees<'info>(ctx: Context<'_, '_, 'info, 'info, CollectFees<'info>>) -> Result<()> {
+        ctx.accounts.collect_fees(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn init_volatility(ctx: Context<InitVolatility>) -> Result<()> {
+        ctx.accounts.init_volatility(&ctx.bumps)
+    }
+
+    pub fn quote_swap(ctx: Context<Quote>, amount_in: u64, is_x: bool) -> Result<SwapQuote> {
+        ctx.accounts.quote_swap(amount_in, is_x)
+    }
+
+    pub fn quote_deposit(ctx: Context<Quote>, amount: u64) -> Result<LiquidityQuote> {
+        ctx.accounts.quote_deposit(amount)
+    }
+
+    pub fn quote_withdraw(ctx: Context<Quote>, amount: u64) -> Result<LiquidityQuote> {
+        ctx.accounts.quote_withdraw(amount)
+    }
+
+    pub fn flash_loan_begin<'info>(ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>, is_x: bool, amount: u64) -> Result<()> {
+        ctx.accounts.flash_loan_begin(is_x, amount, ctx.remaining_accounts)
+    }
+
+    pub fn flash_loan_end<'info>(ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>) -> Result<()> {
+        ctx.accounts.flash_loan_end(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn flash_swap_begin<'info>(ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>, is_x: bool, amount_out: u64) -> Result<()> {
+        ctx.accounts.flash_swap_begin(is_x, amount_out, ctx.remaining_accounts)
+    }
+
+    pub fn flash_swap_end<'info>(ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>, amount_in: u64) -> Result<()> {
+        ctx.accounts.flash_swap_end(amount_in, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn route_swap<'info>(ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>, amount_in: u64, min_amount_out: u64, expiration: Option<i64>) -> Result<()> {
+        ctx.accounts.route_swap(amount_in, min_amount_out, expiration, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn split_swap<'info>(ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>, amount_in: u64, weights: Vec<u16>, min_amount_out: u64, expiration: Option<i64>) -> Result<()> {
+        ctx.accounts.split_swap(amount_in, weights, min_amount_out, expiration, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn create_twamm_order(ctx: Context<CreateTwammOrder>, seed: u64, is_x: bool, amount: u64, duration: i64, min_price: Price) -> Result<()> {
+        ctx.accounts.create_twamm_order(seed, is_x, amount, duration, min_price, &ctx.bumps)
+    }
+
+    pub fn execute_twamm_order(ctx: Context<ExecuteTwammOrder>) -> Result<()> {
+        ctx.accounts.execute_twamm_order(&ctx.bumps)
+    }
+
+    pub fn close_twamm_order(ctx: Context<CloseTwammOrder>) -> Result<()> {
+        ctx.accounts.close_twamm_order()
+    }
+
+    pub fn create_dca(ctx: Context<CreateDca>, seed: u64, is_x: bool, amount: u64, amount_per_fill: u64, interval: i64, min_price: Price) -> Result<()> {
+        ctx.accounts.create_dca(seed, is_x, amount, amount_per_fill, interval, min_price, &ctx.bumps)
+    }
+
+    pub fn execute_dca(ctx: Context<ExecuteDca>) -> Result<()> {
+        ctx.accounts.execute_dca(&ctx.bumps)
+    }
+
+    pub fn close_dca(ctx: Context<CloseDca>) -> Result<()> {
+        ctx.accounts.close_dca()
+    }
+
+    pub fn create_limit_order(ctx: Context<CreateLimitOrder>, seed: u64, is_x: bool, amount: u64, min_price: Price, expires_at: i64) -> Result<()> {
+        ctx.accounts.create_limit_order(seed, is_x, amount, min_price, expires_at, &ctx.bumps)
+    }
+
+    pub fn fill_limit_order(ctx: Context<FillLimitOrder>) -> Result<()> {
+        ctx.accounts.fill_limit_order(&ctx.bumps)
+    }
+
+    pub fn cancel_limit_order(ctx: Context<CancelLimitOrder>) -> Result<()> {
+        ctx.accounts.cancel_limit_order()
+    }
+
+    pub fn commit_batch_swap(ctx: Context<CommitBatchSwap>, batch_id: u64, is_x: bool, amount_in: u64) -> Result<()> {
+        ctx.accounts.commit_batch_swap(batch_id, is_x, amount_in, &ctx.bumps)
+    }
+
+    pub fn settle_batch(ctx: Context<SettleBatch>) -> Result<()> {
+        ctx.accounts.settle_batch(&ctx.bumps)
+    }
+
+    pub fn claim_batch_order(ctx: Context<ClaimBatchOrder>) -> Result<()> {
+        ctx.accounts.claim_batch_order()
+    }
+
+    pub fn commit_swap(ctx: Context<CommitSwap>, hash: [u8; 32], amount_x: u64, amount_y: u64) -> Result<()> {
+        ctx.accounts.commit_swap(hash, amount_x, amount_y, &ctx.bumps)
+    }
+
+    pub fn reveal_swap(ctx: Context<RevealSwap>, reveal: SwapReveal, salt: [u8; 32]) -> Result<()> {
+        ctx.accounts.reveal_swap(reveal, salt, &ctx.bumps)
+    }
+
+    pub fn reclaim_swap_commitment(ctx: Context<ReclaimSwapCommitment>) -> Result<()> {
+        ctx.accounts.reclaim_swap_commitment()
+    }
+
+    pub fn create_launch(ctx: Context<CreateLaunch>, base_is_x: bool, base_amount: u64, virtual_quote: u64, raise_target: u64) -> Result<()> {
+        ctx.accounts.create_launch(base_is_x, base_amount, virtual_quote, raise_target, &ctx.bumps)
+    }
+
+    pub fn buy_launch(ctx: Context<BuyLaunch>, quote_in: u64, min_base_out: u64) -> Result<()> {
+        ctx.accounts.buy_launch(quote_in, min_base_out)
+    }
+
+    pub fn graduate_launch(ctx: Context<GraduateLaunch>) -> Result<()> {
+        ctx.accounts.graduate_launch(&ctx.bumps)
+    }
+
+    pub fn create_price_auction(ctx: Context<CreatePriceAuction>, base_is_x: bool, sale_amount: u64, liquidity_amount: u64, start_price: Price, end_price: Price, duration: i64) -> Result<()> {
+        ctx.accounts.create_price_auction(base_is_x, sale_amount, liquidity_amount, start_price, end_price, duration, &ctx.bumps)
+    }
+
+    pub fn bid_price_auction(ctx: Context<BidPriceAuction>, amount: u64) -> Result<()> {
+        ctx.accounts.bid_price_auction(amount, &ctx.bumps)
+    }
+
+    pub fn settle_price_auction(ctx: Context<SettlePriceAuction>) -> Result<()> {
+        ctx.accounts.settle_price_auction(&ctx.bumps)
+    }
+
+    pub fn claim_auction_bid(ctx: Context<ClaimAuctionBid>) -> Result<()> {
+        ctx.accounts.claim_auction_bid()
+    }
+
+    pub fn set_allowlist(ctx: Context<SetAllowlist>, deposits: bool, swaps: bool) -> Result<()> {
+        ctx.accounts.set_allowlist(deposits, swaps)
+    }
+
+    pub fn set_deposit_caps(ctx: Context<SetAllowlist>, max_tvl: u64, max_deposit_per_wallet: u64) -> Result<()> {
+        ctx.accounts.set_deposit_caps(max_tvl, max_deposit_per_wallet)
+    }
+
+    pub fn set_allowlist_phase(ctx: Context<SetAllowlist>, ends_at: i64) -> Result<()> {
+        ctx.accounts.set_allowlist_phase(ends_at)
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlist>, user: Pubkey) -> Result<()> {
+        ctx.accounts.add_to_allowlist(user, &ctx.bumps)
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        ctx.accounts.remove_from_allowlist()
+    }
+
+    pub fn set_badge_mint(ctx: Context<SetBadgeMint>) -> Result<()> {
+        ctx.accounts.set_badge_mint()
+    }
+
+    pub fn create_farm(ctx: Context<CreateFarm>, emission_rate: u64) -> Result<()> {
+        ctx.accounts.create_farm(emission_rate, &ctx.bumps)
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+        ctx.accounts.fund_rewards(amount)
+    }
+
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.stake(amount, &ctx.bumps)
+    }
+
+    pub fn unstake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake(amount, &ctx.bumps)
+    }
+
+    pub fn lock_stake(ctx: Context<Stake>, duration: i64) -> Result<()> {
+        ctx.accounts.lock_stake(duration, &ctx.bumps)
+    }
+
+    pub fn harvest(ctx: Context<Harvest>) -> Result<()> {
+        ctx.accounts.harvest()
+    }
+
+    pub fn create_gauge_controller(ctx: Context<CreateGaugeController>, epoch_duration: i64, emission_per_epoch: u64) -> Result<()> {
+        ctx.accounts.create_gauge_controller(epoch_duration, emission_per_epoch, &ctx.bumps)
+    }
+
+    pub fn add_gauge(ctx: Context<AddGauge>) -> Result<()> {
+        ctx.accounts.add_gauge(&ctx.bumps)
+    }
+
+    pub fn vote_gauge(ctx: Context<VoteGauge>, epoch: u64, amount: u64) -> Result<()> {
+        ctx.accounts.vote_gauge(epoch, amount, &ctx.bumps)
+    }
+
+    pub fn finalize_epoch(ctx: Context<FinalizeEpoch>) -> Result<()> {
+        ctx.accounts.finalize_epoch()
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize {}
+    pub fn distribute_gauge(ctx: Context<DistributeGauge>) -> Result<()> {
+        ctx.accounts.distribute_gauge()
+    }
+
+    pub fn withdraw_gauge_vote(ctx: Context<WithdrawGaugeVote>) -> Result<()> {
+        ctx.accounts.withdraw_gauge_vote()
+    }
+
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        ctx.accounts.sync()
+    }
+
+    pub fn set_rebasing(ctx: Context<SyncReserves>, enabled: bool) -> Result<()> {
+        ctx.accounts.set_rebasing(enabled)
+    }
+
+    pub fn skim<'info>(ctx: Context<'_, '_, 'info, 'info, SkimReserves<'info>>) -> Result<()> {
+        ctx.accounts.skim(ctx.remaining_accounts)
+    }
+
+    pub fn set_max_price_impact(ctx: Context<SetPriceGuard>, max_price_impact_bps: u16) -> Result<()> {
+        ctx.accounts.set_max_price_impact(max_price_impact_bps)
+    }
+
+    pub fn set_oracle_guard(ctx: Context<SetPriceGuard>, oracle_type: OracleType, oracle_feed: [u8; 32], max_oracle_deviation_bps: u16, max_staleness_secs: u64) -> Result<()> {
+        ctx.accounts.set_oracle_guard(oracle_type, oracle_feed, max_oracle_deviation_bps, max_staleness_secs)
+    }
+
+    pub fn set_oracle_fallback(ctx: Context<SetPriceGuard>, secondary_oracle_type: OracleType, secondary_oracle_feed: [u8; 32], halt_on_stale_oracle: bool) -> Result<()> {
+        ctx.accounts.set_oracle_fallback(secondary_oracle_type, secondary_oracle_feed, halt_on_stale_oracle)
+    }
+
+    pub fn set_sandwich_guard(ctx: Context<SetPriceGuard>, enabled: bool) -> Result<()> {
+        ctx.accounts.set_sandwich_guard(enabled)
+    }
+
+    pub fn set_batch_window(ctx: Context<SetPriceGuard>, batch_window_secs: u32) -> Result<()> {
+        ctx.accounts.set_batch_window(batch_window_secs)
+    }
+
+    pub fn set_launch_fee(ctx: Context<SetPriceGuard>, launch_fee: u16, starts_at: i64, decay_secs: u32) -> Result<()> {
+        ctx.accounts.set_launch_fee(launch_fee, starts_at, decay_secs)
+    }
+
+    pub fn emergency_withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, amount: u64) -> Result<()> {
+        ctx.accounts.emergency_withdraw(amount, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn initialize_global_config(ctx: Context<InitializeGlobalConfig>, treasury: Pubkey, default_protocol_fee_bps: u16, pool_creation_fee: u64) -> Result<()> {
+        ctx.accounts.initialize_global_config(treasury, default_protocol_fee_bps, pool_creation_fee, &ctx.bumps)
+    }
+
+    pub fn update_global_config(ctx: Context<UpdateGlobalConfig>, admin: Pubkey, treasury: Pubkey, default_protocol_fee_bps: u16, pool_creation_fee: u64) -> Result<()> {
+        ctx.accounts.update_global_config(admin, treasury, default_protocol_fee_bps, pool_creation_fee)
+    }
+
+    pub fn set_global_pause(ctx: Context<UpdateGlobalConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.set_global_pause(paused)
+    }
+
+    pub fn set_allow_unsafe_extensions(ctx: Context<UpdateGlobalConfig>, allowed: bool) -> Result<()> {
+        ctx.accounts.set_allow_unsafe_extensions(allowed)
+    }
+
+    pub fn set_fee_discounts(
+        ctx: Context<UpdateGlobalConfig>,
+        discount_mint: Pubkey,
+        holding_thresholds: [u64; HOLDING_TIER_COUNT],
+        holding_discount_bps: [u16; HOLDING_TIER_COUNT],
+        volume_discount_bps: [u16; VOLUME_TIER_COUNT],
+    ) -> Result<()> {
+        ctx.accounts.set_fee_discounts(discount_mint, holding_thresholds, holding_discount_bps, volume_discount_bps)
+    }
+
+    pub fn add_fee_waiver(ctx: Context<AddFeeWaiver>, creator: Pubkey) -> Result<()> {
+        ctx.accounts.add_fee_waiver(creator, &ctx.bumps)
+    }
+
+    pub fn remove_fee_waiver(ctx: Context<RemoveFeeWaiver>) -> Result<()> {
+        ctx.accounts.remove_fee_waiver()
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        ctx.accounts.migrate_config()
+    }
+
+    pub fn lock_lp(ctx: Context<LockLp>, seed: u64, amount: u64, unlock_at: i64, vesting: bool) -> Result<()> {
+        ctx.accounts.lock_lp(seed, amount, unlock_at, vesting, &ctx.bumps)
+    }
+
+    pub fn unlock_lp(ctx: Context<UnlockLp>) -> Result<()> {
+        ctx.accounts.unlock_lp(&ctx.bumps)
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        ctx.accounts.open_position(&ctx.bumps)
+    }
+
+    pub fn deposit_position<'info>(ctx: Context<'_, '_, 'info, 'info, ModifyPosition<'info>>, amount: u64, max_x: u64, max_y: u64, expiration: Option<i64>) -> Result<()> {
+        ctx.accounts.deposit_position(amount, max_x, max_y, expiration, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn withdraw_position<'info>(ctx: Context<'_, '_, 'info, 'info, ModifyPosition<'info>>, amount: u64, min_x: u64, min_y: u64, expiration: Option<i64>) -> Result<()> {
+        ctx.accounts.withdraw_position(amount, min_x, min_y, expiration, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn collect_position_fees<'info>(ctx: Context<'_, '_, 'info, 'info, ModifyPosition<'info>>) -> Result<()> {
+        ctx.accounts.collect_position_fees(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
+        ctx.accounts.close_position()
+    }
+
+    pub fn compound<'info>(ctx: Context<'_, '_, 'info, 'info, CompoundPosition<'info>>) -> Result<()> {
+        ctx.accounts.compound(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn configure_buyback(ctx: Context<ConfigureBuyback>, max_amount_in: u64, min_interval: i64, max_price_impact_bps: u16) -> Result<()> {
+        ctx.accounts.configure_buyback(max_amount_in, min_interval, max_price_impact_bps, &ctx.bumps)
+    }
+
+    pub fn execute_buyback<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteBuyback<'info>>) -> Result<()> {
+        ctx.accounts.execute_buyback(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    pub fn create_fee_share_vault(ctx: Context<CreateFeeShareVault>, epoch_duration: i64) -> Result<()> {
+        ctx.accounts.create_fee_share_vault(epoch_duration, &ctx.bumps)
+    }
+
+    pub fn stake_gov(ctx: Context<StakeGov>, amount: u64) -> Result<()> {
+        ctx.accounts.stake_gov(amount, &ctx.bumps)
+    }
+
+    pub fn unstake_gov(ctx: Context<StakeGov>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake_gov(amount, &ctx.bumps)
+    }
+
+    pub fn close_fee_epoch(ctx: Context<CloseFeeEpoch>) -> Result<()> {
+        ctx.accounts.close_fee_epoch(&ctx.bumps)
+    }
+
+    pub fn fund_fee_epoch(ctx: Context<FundFeeEpoch>, amount: u64) -> Result<()> {
+        ctx.accounts.fund_fee_epoch(amount, &ctx.bumps)
+    }
+
+    pub fn claim_fees(ctx: Context<ClaimFees>, epoch: u64) -> Result<()> {
+        ctx.accounts.claim_fees(epoch, &ctx.bumps)
+    }
+
+    pub fn create_distribution(ctx: Context<CreateDistribution>, seed: u64, root: [u8; 32], total_amount: u64) -> Result<()> {
+        ctx.accounts.create_distribution(seed, root, total_amount, &ctx.bumps)
+    }
+
+    pub fn claim(ctx: Context<ClaimDistribution>, index: u64, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        ctx.accounts.claim(index, amount, proof, &ctx.bumps)
+    }
+
+    pub fn register_hook(ctx: Context<RegisterHook>, program_id: Pubkey) -> Result<()> {
+        ctx.accounts.register_hook(program_id, &ctx.bumps)
+    }
+
+    pub fn deregister_hook(ctx: Context<DeregisterHook>) -> Result<()> {
+        ctx.accounts.deregister_hook()
+    }
+}
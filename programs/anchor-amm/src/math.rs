@@ -0,0 +1,423 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::BPS_DENOMINATOR;
+use crate::price::Price;
+
+pub const FIXED_ONE: u128 = 1 << 62;
+const LN_2: u128 = 3_196_577_161_300_663_914;
+
+pub use amm_math::isqrt;
+
+// The value math itself lives in the amm-math crate; these adapt its errors to AmmError.
+pub fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+    Ok(amm_math::mul_div(a, b, c).map_err(AmmError::from)?)
+}
+
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Result<u128> {
+    Ok(amm_math::mul_div_ceil(a, b, c).map_err(AmmError::from)?)
+}
+
+pub fn to_u64(n: u128) -> Result<u64> {
+    Ok(amm_math::to_u64(n).map_err(AmmError::from)?)
+}
+
+// Rounding policy: every division rounds in the pool's favour, up on amounts owed
+// to the pool and down on amounts paid out of it.
+
+// Tokens owed for minting `lp` shares.
+pub fn deposit_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> Result<(u64, u64)> {
+    Ok(amm_math::deposit_amounts(reserve_x, reserve_y, supply, lp).map_err(AmmError::from)?)
+}
+
+// Tokens paid out for burning `lp` shares.
+pub fn withdraw_amounts(reserve_x: u64, reserve_y: u64, supply: u64, lp: u64) -> Result<(u64, u64)> {
+    Ok(amm_math::withdraw_amounts(reserve_x, reserve_y, supply, lp).map_err(AmmError::from)?)
+}
+
+// Swap fee charged on `amount_in`.
+pub fn fee_amount(amount_in: u64, fee: u16) -> Result<u64> {
+    Ok(amm_math::fee_amount(amount_in, fee).map_err(AmmError::from)?)
+}
+
+// Output of a constant-product swap with the fee taken from the input side.
+pub fn swap_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16) -> Result<u64> {
+    Ok(amm_math::swap_amount_out(reserve_in, reserve_out, amount_in, fee).map_err(AmmError::from)?)
+}
+
+// Portion of a single-sided deposit that has to be swapped so the remainder
+// and the swap output match the post-swap pool ratio.
+pub fn zap_swap_amount(reserve_in: u64, amount_in: u64, fee: u16) -> Result<u64> {
+    require!((fee as u128) < BPS_DENOMINATOR, AmmError::InvalidFee);
+
+    let r = reserve_in as u128;
+    let a = amount_in as u128;
+    let fee_factor = BPS_DENOMINATOR - fee as u128;
+    let double_fee_factor = 2 * BPS_DENOMINATOR - fee as u128;
+
+    let b = r * double_fee_factor;
+    let inner = (r * double_fee_factor * double_fee_factor)
+        .checked_add(4 * a * fee_factor * BPS_DENOMINATOR)
+        .ok_or(AmmError::Overflow)?;
+    let root = match r.checked_mul(inner) {
+        Some(product) => isqrt(product),
+        None => isqrt(r) * isqrt(inner),
+    };
+
+    to_u64(root.saturating_sub(b) / (2 * fee_factor))
+}
+
+// log2(n) for an integer n >= 1, with 62 fractional bits.
+pub fn log2_fixed(n: u128) -> u128 {
+    let msb = 127 - n.leading_zeros();
+    let mut mantissa = match msb >= 62 {
+        true => n >> (msb - 62),
+        false => n << (62 - msb),
+    };
+
+    let mut result = (msb as u128) << 62;
+    let mut bit = FIXED_ONE >> 1;
+    while bit > 0 {
+        mantissa = (mantissa * mantissa) >> 62;
+        if mantissa >= 2 * FIXED_ONE {
+            mantissa >>= 1;
+            result += bit;
+        }
+        bit >>= 1;
+    }
+    result
+}
+
+// 2^-t for a fixed-point t >= 0.
+pub fn exp2_neg_fixed(t: u128) -> u128 {
+    let whole = t >> 62;
+    if whole >= 62 {
+        return 0;
+    }
+
+    // 2^frac = e^(frac * ln 2), summed as a Taylor series
+    let x = ((t & (FIXED_ONE - 1)) * LN_2) >> 62;
+    let mut sum = FIXED_ONE;
+    let mut term = FIXED_ONE;
+    let mut n = 1;
+    while term != 0 {
+        term = term * x / (n * FIXED_ONE);
+        sum += term;
+        n += 1;
+    }
+
+    (FIXED_ONE * FIXED_ONE / sum) >> whole
+}
+
+// (num / den)^(exp_num / exp_den) for num <= den, with 62 fractional bits.
+pub fn pow_ratio_fixed(num: u128, den: u128, exp_num: u128, exp_den: u128) -> Result<u128> {
+    require!(num != 0 && num <= den, AmmError::InvalidAmount);
+    let exponent = mul_div(log2_fixed(den) - log2_fixed(num), exp_num, exp_den)?;
+    Ok(exp2_neg_fixed(exponent))
+}
+
+// Output of a weighted constant-product swap:
+// out = reserve_out * (1 - (reserve_in / (reserve_in + in))^(weight_in / weight_out))
+pub fn weighted_amount_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    weight_in: u16,
+    weight_out: u16,
+    fee: u16,
+) -> Result<u64> {
+    require!((fee as u128) < BPS_DENOMINATOR, AmmError::InvalidFee);
+    require!(reserve_in != 0 && reserve_out != 0, AmmError::NoLiquidityInPool);
+
+    let amount_in_after_fee = mul_div(amount_in as u128, BPS_DENOMINATOR - fee as u128, BPS_DENOMINATOR)?;
+    let ratio = pow_ratio_fixed(
+        reserve_in as u128,
+        reserve_in as u128 + amount_in_after_fee,
+        weight_in as u128,
+        weight_out as u128,
+    )?;
+
+    // the fixed-point power is approximate, so one unit is held back to keep
+    // the error on the pool's side
+    Ok(to_u64(mul_div(reserve_out as u128, FIXED_ONE - ratio, FIXED_ONE)?)?.saturating_sub(1))
+}
+
+// Largest input, fee included, that keeps the post-trade price of the input token
+// (output per unit of input) at or above `limit_price`.
+// The fee stays in the pool, so sizing on the fee-free curve errs on the limit's side.
+pub fn max_amount_in_for_price(reserve_in: u64, reserve_out: u64, limit_price: Price) -> Result<u64> {
+    require!(limit_price.0 != 0, AmmError::InvalidAmount);
+    let k = reserve_in as u128 * reserve_out as u128;
+    let target_in = match limit_price.quote_inverse(k) {
+        Ok(target) => isqrt(target),
+        Err(_) => return Ok(u64::MAX),
+    };
+    Ok(to_u64(target_in.saturating_sub(reserve_in as u128)).unwrap_or(u64::MAX))
+}
+
+// DODO-style PMM quote anchored at the oracle price of x in y, with the pool taken to
+// be at its equilibrium: the marginal price slides from the oracle price by
+// k * reserve_in / (reserve_in + amount_in), so k = 0 trades flat at the oracle price
+// and k = BPS_DENOMINATOR gives constant-product depth around it.
+pub fn pmm_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16, oracle_price: Price, is_x: bool, k_bps: u16) -> Result<u64> {
+    require!((fee as u128) < BPS_DENOMINATOR, AmmError::InvalidFee);
+    require!((k_bps as u128) <= BPS_DENOMINATOR && oracle_price.0 != 0, AmmError::InvalidAmount);
+
+    let amount_in_after_fee = mul_div(amount_in as u128, BPS_DENOMINATOR - fee as u128, BPS_DENOMINATOR)?;
+    let at_oracle = match is_x {
+        true => oracle_price.quote(amount_in_after_fee)?,
+        false => oracle_price.quote_inverse(amount_in_after_fee)?,
+    };
+
+    let k = k_bps as u128;
+    let new_reserve_in = reserve_in as u128 + amount_in_after_fee;
+    let amount_out = mul_div(at_oracle, (BPS_DENOMINATOR - k) * new_reserve_in + k * reserve_in as u128, BPS_DENOMINATOR * new_reserve_in)?;
+
+    // a flat curve can quote more than the pool holds, so the output side is never drained
+    require!(amount_out < reserve_out as u128, AmmError::NoLiquidityInPool);
+    to_u64(amount_out)
+}
+
+// StableSwap invariant D for two coins, by Newton's method:
+// 4A(x + y) + D = 4AD + D^3 / (4xy)
+pub fn stable_invariant(x: u64, y: u64, amp: u64) -> Result<u128> {
+    require!(x != 0 && y != 0, AmmError::NoLiquidityInPool);
+    let (x, y) = (x as u128, y as u128);
+    let sum = x + y;
+    let ann = amp as u128 * 4;
+
+    let mut d = sum;
+    for _ in 0..255 {
+        let d_p = mul_div(mul_div(d, d, x * 2)?, d, y * 2)?;
+        let previous = d;
+        let numerator = (ann * sum).checked_add(d_p * 2).ok_or(AmmError::Overflow)?;
+        let denominator = ((ann - 1) * d).checked_add(d_p * 3).ok_or(AmmError::Overflow)?;
+        d = mul_div(numerator, d, denominator)?;
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+    err!(AmmError::InvariantViolation)
+}
+
+// Balance of the other coin that keeps the invariant at `d` once this one holds `x`.
+fn stable_balance(x: u128, d: u128, amp: u64) -> Result<u128> {
+    let ann = amp as u128 * 4;
+    let c = mul_div(mul_div(d, d, x * 2)?, d, ann * 2)?;
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..255 {
+        let previous = y;
+        let numerator = y.checked_mul(y).and_then(|yy| yy.checked_add(c)).ok_or(AmmError::Overflow)?;
+        let denominator = (2 * y + b).checked_sub(d).ok_or(AmmError::Underflow)?;
+        y = numerator / denominator;
+        if y.abs_diff(previous) <= 1 {
+            return Ok(y);
+        }
+    }
+    err!(AmmError::InvariantViolation)
+}
+
+// Output of a two-coin StableSwap trade with the fee taken from the input side. Both
+// tokens are assumed to share decimals; one unit is held back against Newton's error.
+pub fn stable_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16, amp: u64) -> Result<u64> {
+    require!((fee as u128) < BPS_DENOMINATOR, AmmError::InvalidFee);
+    require!(amp != 0, AmmError::InvalidAmount);
+
+    let d = stable_invariant(reserve_in, reserve_out, amp)?;
+    let amount_in_after_fee = mul_div(amount_in as u128, BPS_DENOMINATOR - fee as u128, BPS_DENOMINATOR)?;
+    let new_reserve_out = stable_balance(reserve_in as u128 + amount_in_after_fee, d, amp)?;
+    to_u64((reserve_out as u128).saturating_sub(new_reserve_out).saturating_sub(1))
+}
+
+// Stable swap with the x side valued in y at `rate`, so the curve is flattest where
+// the pool's balances match the exchange rate rather than at a 1:1 ratio.
+pub fn lst_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64, fee: u16, amp: u64, rate: Price, is_x: bool) -> Result<u64> {
+    require!(rate.0 != 0, AmmError::InvalidAmount);
+    match is_x {
+        true => {
+            let reserve_in = to_u64(rate.quote(reserve_in as u128)?)?;
+            let amount_in = to_u64(rate.quote(amount_in as u128)?)?;
+            stable_amount_out(reserve_in, reserve_out, amount_in, fee, amp)
+        }
+        false => {
+            let scaled_out = stable_amount_out(reserve_in, to_u64(rate.quote(reserve_out as u128)?)?, amount_in, fee, amp)?;
+            to_u64(rate.quote_inverse(scaled_out as u128)?)
+        }
+    }
+}
+
+// Liquidity bought by adding `amount_x` and `amount_y` at the pool ratio, floored so
+// the matching deposit_amounts never exceed them; the excess side is left over.
+pub fn balanced_liquidity(reserve_x: u64, reserve_y: u64, supply: u64, amount_x: u64, amount_y: u64) -> Result<u64> {
+    require!(supply != 0 && reserve_x != 0 && reserve_y != 0, AmmError::NoLiquidityInPool);
+    let liquidity_x = mul_div(supply as u128, amount_x as u128, reserve_x as u128)?;
+    let liquidity_y = mul_div(supply as u128, amount_y as u128, reserve_y as u128)?;
+    to_u64(liquidity_x.min(liquidity_y))
+}
+
+// Net input a batch sends through a constant-product pool so both sides clear at one
+// price: selling u of the excess side makes Y / (X - u) equal out(u) / u, which before
+// fees solves to u = (R_y X - R_x Y) / (R_y + Y). Returns whether x is the excess side.
+pub fn batch_net_amount(reserve_x: u64, reserve_y: u64, total_x: u64, total_y: u64) -> Result<(bool, u64)> {
+    require!(reserve_x != 0 && reserve_y != 0, AmmError::NoLiquidityInPool);
+    let x_value = total_x as u128 * reserve_y as u128;
+    let y_value = total_y as u128 * reserve_x as u128;
+    match x_value >= y_value {
+        true => Ok((true, to_u64((x_value - y_value) / (reserve_y as u128 + total_y as u128))?)),
+        false => Ok((false, to_u64((y_value - x_value) / (reserve_x as u128 + total_x as u128))?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_handles_256_bit_intermediates() {
+        let max = u64::MAX as u128;
+        assert_eq!(mul_div(u128::MAX, 3, 6).unwrap(), u128::MAX / 2);
+        assert_eq!(mul_div(max * max, max * max, max * max).unwrap(), max * max);
+        assert_eq!(mul_div_ceil(u128::MAX, 2, 4).unwrap(), u128::MAX / 2 + 1);
+        assert!(mul_div(u128::MAX, 2, 1).is_err());
+    }
+
+    #[test]
+    fn swaps_near_the_u64_limit_do_not_overflow() {
+        let reserve = u64::MAX / 2;
+        let out = swap_amount_out(reserve, reserve, reserve / 4, 30).unwrap();
+        assert!(out > 0 && out < reserve / 4);
+    }
+
+    #[test]
+    fn price_limited_input_stops_at_the_limit() {
+        let (reserve_in, reserve_out) = (1_000_000u64, 1_000_000u64);
+        let limit = Price::from_ratio(81, 100).unwrap();
+        let amount_in = max_amount_in_for_price(reserve_in, reserve_out, limit).unwrap();
+        assert_eq!(amount_in, 111_111);
+
+        let out = swap_amount_out(reserve_in, reserve_out, amount_in, 30).unwrap();
+        let price = Price::from_ratio((reserve_out - out) as u128, (reserve_in + amount_in) as u128).unwrap();
+        assert!(price >= limit);
+        assert_eq!(max_amount_in_for_price(reserve_in, reserve_out, Price::from_ratio(2, 1).unwrap()).unwrap(), 0);
+    }
+
+    #[test]
+    fn pmm_spans_flat_to_constant_product() {
+        let (reserve_x, reserve_y) = (1_000_000u64, 1_000_000u64);
+        assert_eq!(pmm_amount_out(reserve_x, reserve_y, 100_000, 0, Price::ONE, true, 0).unwrap(), 100_000);
+        assert_eq!(
+            pmm_amount_out(reserve_x, reserve_y, 100_000, 0, Price::ONE, true, 10_000).unwrap(),
+            swap_amount_out(reserve_x, reserve_y, 100_000, 0).unwrap()
+        );
+
+        // selling y is quoted at the inverse of the oracle price of x
+        assert_eq!(pmm_amount_out(reserve_y, reserve_x, 100_000, 0, Price::from_ratio(2, 1).unwrap(), false, 0).unwrap(), 50_000);
+        assert!(pmm_amount_out(reserve_x, reserve_y, 1_000_000, 0, Price::ONE, true, 0).is_err());
+    }
+
+    #[test]
+    fn liquidity_amounts_round_toward_the_pool() {
+        assert_eq!(deposit_amounts(10, 20, 3, 1).unwrap(), (4, 7));
+        assert_eq!(withdraw_amounts(10, 20, 3, 1).unwrap(), (3, 6));
+    }
+
+    #[test]
+    fn balanced_liquidity_never_overdraws() {
+        let (reserve_x, reserve_y, supply) = (1_000_003, 2_000_011, 1_414_217);
+        for (amount_x, amount_y) in [(1, 1), (7, 100), (1_000, 3), (12_345, 24_690)] {
+            let liquidity = balanced_liquidity(reserve_x, reserve_y, supply, amount_x, amount_y).unwrap();
+            let (x, y) = deposit_amounts(reserve_x, reserve_y, supply, liquidity).unwrap();
+            assert!(x <= amount_x && y <= amount_y);
+        }
+    }
+
+    #[test]
+    fn fees_round_up() {
+        assert_eq!(fee_amount(1, 30).unwrap(), 1);
+        assert_eq!(fee_amount(10_000, 30).unwrap(), 30);
+        assert_eq!(fee_amount(10_001, 30).unwrap(), 31);
+    }
+
+    #[test]
+    fn swap_output_rounds_down() {
+        assert_eq!(swap_amount_out(100, 100, 1, 0).unwrap(), 0);
+        assert_eq!(swap_amount_out(100, 100, 2, 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn weighted_output_holds_back_a_unit() {
+        let exact = swap_amount_out(1_000_000, 1_000_000, 1_000, 0).unwrap();
+        let weighted = weighted_amount_out(1_000_000, 1_000_000, 1_000, 5_000, 5_000, 0).unwrap();
+        assert!(weighted < exact);
+    }
+
+    #[test]
+    fn dust_deposit_withdraw_cycles_extract_nothing() {
+        let (mut reserve_x, mut reserve_y, mut supply) = (1_000_003u64, 777_777u64, 900_001u64);
+        let (start_x, start_y) = (reserve_x, reserve_y);
+
+        for lp in (1..=1_000).map(|i| i % 7 + 1) {
+            let (in_x, in_y) = deposit_amounts(reserve_x, reserve_y, supply, lp).unwrap();
+            reserve_x += in_x;
+            reserve_y += in_y;
+            supply += lp;
+
+            let (out_x, out_y) = withdraw_amounts(reserve_x, reserve_y, supply, lp).unwrap();
+            reserve_x -= out_x;
+            reserve_y -= out_y;
+            supply -= lp;
+
+            assert!(out_x <= in_x && out_y <= in_y);
+        }
+
+        assert!(reserve_x >= start_x && reserve_y >= start_y);
+    }
+
+    #[test]
+    fn dust_round_trip_swaps_extract_nothing() {
+        for fee in [0u16, 1, 30] {
+            let (mut reserve_x, mut reserve_y) = (5_000_011u64, 3_333_331u64);
+            let mut k = reserve_x as u128 * reserve_y as u128;
+            let start = 10_000u64;
+            let mut balance_x = start;
+
+            for amount in (1..=500u64).map(|i| i % 13 + 2) {
+                let out_y = swap_amount_out(reserve_x, reserve_y, amount, fee).unwrap();
+                reserve_x += amount;
+                reserve_y -= out_y;
+                balance_x -= amount;
+
+                let out_x = swap_amount_out(reserve_y, reserve_x, out_y, fee).unwrap();
+                reserve_y += out_y;
+                reserve_x -= out_x;
+                balance_x += out_x;
+
+                let next_k = reserve_x as u128 * reserve_y as u128;
+                assert!(next_k >= k);
+                k = next_k;
+            }
+
+            assert!(balance_x <= start);
+        }
+    }
+
+    #[test]
+    fn batch_net_amount_clears_both_sides_at_one_price() {
+        let (reserve_x, reserve_y) = (1_000_000u64, 1_000_000u64);
+        assert_eq!(batch_net_amount(reserve_x, reserve_y, 50_000, 50_000).unwrap(), (true, 0));
+        assert_eq!(batch_net_amount(reserve_x, reserve_y, 100_000, 0).unwrap(), (true, 100_000));
+
+        let (total_x, total_y) = (300_000u64, 100_000u64);
+        let (is_x, net) = batch_net_amount(reserve_x, reserve_y, total_x, total_y).unwrap();
+        assert!(is_x);
+        let out = swap_amount_out(reserve_x, reserve_y, net, 0).unwrap();
+        // y sellers receive what x sellers did not send to the pool, at the pool's price
+        let pool_price = Price::from_ratio(out as u128, net as u128).unwrap();
+        let matched_price = Price::from_ratio(total_y as u128, (total_x - net) as u128).unwrap();
+        assert!(pool_price.0.abs_diff(matched_price.0) <= pool_price.0 / 100_000);
+
+        assert_eq!(batch_net_amount(reserve_x, reserve_y, 0, 100_000).unwrap(), (false, 100_000));
+    }
+}
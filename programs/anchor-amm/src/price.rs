@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::math::mul_div;
+
+const Q64: u128 = 1 << 64;
+
+// Q64.64 fixed-point price in raw base units of y per raw base unit of x (or the
+// reverse for prices of y). Used by the TWAP oracle, price feeds, limit prices and
+// the PMM curve, so they all compare and convert the same way.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Price(pub u128);
+
+// InitSpace can't be derived for tuple structs.
+impl Space for Price {
+    const INIT_SPACE: usize = 16;
+}
+
+impl Price {
+    pub const ONE: Price = Price(Q64);
+
+    pub fn from_ratio(numerator: u128, denominator: u128) -> Result<Price> {
+        Ok(Price(mul_div(numerator, Q64, denominator)?))
+    }
+
+    // Spot price of x in y; reserves fit in 64 bits, so this can't overflow.
+    pub fn from_reserves(reserve_x: u64, reserve_y: u64) -> Price {
+        Price(((reserve_y as u128) << 64) / reserve_x as u128)
+    }
+
+    // Feeds quote one whole x in whole y as price * 10^expo.
+    pub fn from_decimal(price: i128, expo: i32, decimals_x: u8, decimals_y: u8) -> Result<Price> {
+        require!(price > 0, AmmError::InvalidOraclePrice);
+
+        let shift = expo + decimals_y as i32 - decimals_x as i32;
+        let factor = 10u128.checked_pow(shift.unsigned_abs()).ok_or(AmmError::Overflow)?;
+        let raw = match shift >= 0 {
+            true => (price as u128)
+                .checked_mul(factor)
+                .and_then(|scaled| scaled.checked_mul(Q64))
+                .ok_or(AmmError::Overflow)?,
+            false => mul_div(price as u128, Q64, factor)?,
+        };
+        require!(raw != 0, AmmError::InvalidOraclePrice);
+        Ok(Price(raw))
+    }
+
+    // Whole y per whole x scaled by 10^precision, for clients and logs.
+    pub fn to_decimal(self, decimals_x: u8, decimals_y: u8, precision: u8) -> Result<u128> {
+        let shift = decimals_x as i32 - decimals_y as i32 + precision as i32;
+        let factor = 10u128.checked_pow(shift.unsigned_abs()).ok_or(AmmError::Overflow)?;
+        match shift >= 0 {
+            true => mul_div(self.0, factor, Q64),
+            false => Ok(mul_div(self.0, 1, Q64)? / factor),
+        }
+    }
+
+    pub fn inverse(self) -> Result<Price> {
+        require!(self.0 != 0, AmmError::InvalidOraclePrice);
+        Price::from_ratio(Q64, self.0)
+    }
+
+    // Amount of y bought by `amount` of x at this price, rounded down.
+    pub fn quote(self, amount: u128) -> Result<u128> {
+        mul_div(amount, self.0, Q64)
+    }
+
+    // Amount of x bought by `amount` of y at this price, rounded down.
+    pub fn quote_inverse(self, amount: u128) -> Result<u128> {
+        require!(self.0 != 0, AmmError::InvalidOraclePrice);
+        mul_div(amount, Q64, self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_conversions_round_trip() {
+        // 150.25 y per x with 9-decimal x and 6-decimal y
+        let price = Price::from_decimal(15_025, -2, 9, 6).unwrap();
+        assert_eq!(price.to_decimal(9, 6, 2).unwrap(), 15_024);
+        assert_eq!(price.quote(1_000_000_000).unwrap(), 150_249_999);
+        assert_eq!(Price::from_decimal(2, 0, 6, 6).unwrap(), Price::from_ratio(2, 1).unwrap());
+        assert!(Price::from_decimal(0, 0, 6, 6).is_err());
+    }
+
+    #[test]
+    fn inverse_and_quotes_agree() {
+        let price = Price::from_ratio(4, 1).unwrap();
+        assert_eq!(price.inverse().unwrap(), Price::from_ratio(1, 4).unwrap());
+        assert_eq!(price.quote(1_000).unwrap(), 4_000);
+        assert_eq!(price.quote_inverse(4_000).unwrap(), 1_000);
+        assert_eq!(Price::from_reserves(1_000, 3_000), Price::from_ratio(3, 1).unwrap());
+    }
+}
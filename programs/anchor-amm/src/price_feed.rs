@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use switchboard_solana::{AggregatorAccountData, SWITCHBOARD_PROGRAM_ID};
+
+use crate::amm_error::AmmError;
+use crate::constants::BPS_DENOMINATOR;
+use crate::math::mul_div;
+use crate::price::Price;
+use crate::state::OracleType;
+
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+// byte offsets into the SPL stake pool's StakePool account
+const STAKE_POOL_MINT_OFFSET: usize = 162;
+const STAKE_POOL_TOTAL_LAMPORTS_OFFSET: usize = 258;
+const STAKE_POOL_TOKEN_SUPPLY_OFFSET: usize = 266;
+const STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET: usize = 274;
+
+// Returns None when the feed has not been updated within max_staleness_secs.
+pub fn feed_price(oracle_type: OracleType, oracle_feed: &[u8; 32], feed: Option<&AccountInfo>, max_staleness_secs: u64, decimals_x: u8, decimals_y: u8, mints: (Pubkey, Pubkey)) -> Result<Option<Price>> {
+    let feed = feed.ok_or(AmmError::MissingPriceFeed)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let (price, expo, published_at) = match oracle_type {
+        OracleType::Pyth => {
+            require_keys_eq!(*feed.owner, PriceUpdateV2::owner(), AmmError::InvalidPriceFeed);
+            let update = PriceUpdateV2::try_deserialize(&mut &feed.try_borrow_data()?[..])?;
+            let price = update.get_price_unchecked(oracle_feed)?;
+            (price.price as i128, price.exponent, price.publish_time)
+        }
+        OracleType::Switchboard => {
+            require_keys_eq!(*feed.owner, *SWITCHBOARD_PROGRAM_ID, AmmError::InvalidPriceFeed);
+            require_keys_eq!(feed.key(), Pubkey::new_from_array(*oracle_feed), AmmError::InvalidPriceFeed);
+
+            let data = feed.try_borrow_data()?;
+            let aggregator = AggregatorAccountData::new_from_bytes(&data)?;
+            let result = aggregator.get_result()?;
+            (result.mantissa, -(result.scale as i32), aggregator.latest_confirmed_round.round_open_timestamp)
+        }
+        OracleType::StakePool => {
+            require_keys_eq!(feed.key(), Pubkey::new_from_array(*oracle_feed), AmmError::InvalidPriceFeed);
+            return stake_pool_rate(feed, mints);
+        }
+        OracleType::None => return err!(AmmError::MissingPriceFeed),
+    };
+
+    if published_at.saturating_add(max_staleness_secs as i64) < now {
+        return Ok(None);
+    }
+    Price::from_decimal(price, expo, decimals_x, decimals_y).map(Some)
+}
+
+// Lamports per pool token as a price of x in y, for a pair of the pool's LST and
+// (wrapped) SOL. The rate only moves at epoch boundaries, so it is stale until the
+// stake pool has been updated for the current epoch.
+fn stake_pool_rate(feed: &AccountInfo, mints: (Pubkey, Pubkey)) -> Result<Option<Price>> {
+    require_keys_eq!(*feed.owner, STAKE_POOL_PROGRAM_ID, AmmError::InvalidPriceFeed);
+    let data = feed.try_borrow_data()?;
+    require!(data.len() >= STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET + 8, AmmError::InvalidPriceFeed);
+
+    let read_u64 = |offset: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&data[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    if read_u64(STAKE_POOL_LAST_UPDATE_EPOCH_OFFSET) < Clock::get()?.epoch {
+        return Ok(None);
+    }
+
+    let total_lamports = read_u64(STAKE_POOL_TOTAL_LAMPORTS_OFFSET) as u128;
+    let pool_token_supply = read_u64(STAKE_POOL_TOKEN_SUPPLY_OFFSET) as u128;
+    let pool_mint = Pubkey::try_from(&data[STAKE_POOL_MINT_OFFSET..STAKE_POOL_MINT_OFFSET + 32]).map_err(|_| AmmError::InvalidPriceFeed)?;
+    let rate = match pool_mint {
+        mint if mint == mints.0 => Price::from_ratio(total_lamports, pool_token_supply)?,
+        mint if mint == mints.1 => Price::from_ratio(pool_token_supply, total_lamports)?,
+        _ => return err!(AmmError::InvalidPriceFeed),
+    };
+    Ok(Some(rate))
+}
+
+// The trade's execution price of x in y must stay within max_deviation_bps of the oracle price.
+pub fn check_deviation(oracle_price: Price, is_x: bool, amount_in: u64, amount_out: u64, max_deviation_bps: u16) -> Result<()> {
+    let (amount_x, amount_y) = match is_x {
+        true => (amount_in, amount_out),
+        false => (amount_out, amount_in),
+    };
+    require!(amount_x != 0, AmmError::InvalidAmount);
+
+    let execution_price = Price::from_ratio(amount_y as u128, amount_x as u128)?;
+    let deviation_bps = mul_div(execution_price.0.abs_diff(oracle_price.0), BPS_DENOMINATOR, oracle_price.0)?;
+    require!(deviation_bps <= max_deviation_bps as u128, AmmError::OracleDeviationTooHigh);
+    Ok(())
+}
@@ -1,2 +1,53 @@
 pub mod config;
+pub mod volatility;
+pub mod oracle;
+pub mod observations;
+pub mod twamm_order;
+pub mod dca;
+pub mod limit_order;
+pub mod allowlist;
+pub mod farm;
+pub mod gauge;
+pub mod global_config;
+pub mod registry;
+pub mod deposit_record;
+pub mod lp_lock;
+pub mod position;
+pub mod buyback;
+pub mod fee_share;
+pub mod distribution;
+pub mod user_stats;
+pub mod hook;
+pub mod permit;
+pub mod batch;
+pub mod commitment;
+pub mod wallet_deposits;
+pub mod launch;
+pub mod price_auction;
+
 pub use config::*;
+pub use volatility::*;
+pub use oracle::*;
+pub use observations::*;
+pub use twamm_order::*;
+pub use dca::*;
+pub use limit_order::*;
+pub use allowlist::*;
+pub use farm::*;
+pub use gauge::*;
+pub use global_config::*;
+pub use registry::*;
+pub use deposit_record::*;
+pub use lp_lock::*;
+pub use position::*;
+pub use buyback::*;
+pub use fee_share::*;
+pub use distribution::*;
+pub use user_stats::*;
+pub use hook::*;
+pub use permit::*;
+pub use batch::*;
+pub use commitment::*;
+pub use wallet_deposits::*;
+pub use launch::*;
+pub use price_auction::*;
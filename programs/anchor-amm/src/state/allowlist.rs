@@ -0,0 +1,9 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistEntry {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub bump: u8,
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+// Swaps committed during one batch window of a pool, escrowed in the batch's ATAs.
+// Settlement nets the two sides against each other and leaves the escrows holding
+// the proceeds, which orders then claim pro rata.
+#[account]
+#[derive(InitSpace)]
+pub struct Batch {
+    pub config: Pubkey,
+    pub batch_id: u64,
+    pub ends_at: i64,
+    pub total_x_in: u64,
+    pub total_y_in: u64,
+    // proceeds owed to y sellers and x sellers respectively, set at settlement
+    pub amount_x_out: u64,
+    pub amount_y_out: u64,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BatchOrder {
+    pub owner: Pubkey,
+    pub batch: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub bump: u8,
+}
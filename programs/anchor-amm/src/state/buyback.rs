@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+// Admin-set parameters for spending a pool's protocol fees on buying back `mint`,
+// one side of the pair, and burning it. Each run spends at most max_amount_in and
+// runs are at least min_interval seconds apart.
+#[account]
+#[derive(InitSpace)]
+pub struct Buyback {
+    pub config: Pubkey,
+    pub mint: Pubkey,
+    pub max_amount_in: u64,
+    pub min_interval: i64,
+    pub max_price_impact_bps: u16,
+    pub last_executed_at: i64,
+    pub total_spent: u64,
+    pub total_burned: u64,
+    pub bump: u8,
+}
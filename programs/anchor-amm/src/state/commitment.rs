@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+// A hidden swap: the owner escrows funds alongside keccak(SwapReveal || salt) and
+// discloses the parameters only when executing. Escrowing on both sides lets the
+// owner keep the direction hidden too.
+#[account]
+#[derive(InitSpace)]
+pub struct SwapCommitment {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub hash: [u8; 32],
+    pub committed_slot: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SwapReveal {
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+}
@@ -1,14 +1,487 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::amm_error::AmmError;
+use crate::constants::{BPS_DENOMINATOR, CONFIG_RESERVED_BYTES, MAX_FEE_BPS, PAUSE_ALL, PAUSE_EMERGENCY, PAUSE_WITHDRAW, REWARD_PRECISION, SPREAD_FULL_DEVIATION_BPS, TOTAL_WEIGHT, VOLUME_TIER_COUNT};
+use crate::curve::{ConstantProduct, Curve, Lst, Pmm, Stable, Weighted};
+use crate::math::{mul_div, to_u64};
+use crate::price::Price;
+use crate::price_feed::{check_deviation, feed_price};
+use crate::state::{AllowlistEntry, CurveType, Volatility};
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum OracleType {
+    None,
+    Pyth,
+    Switchboard,
+    // an SPL stake pool account, read for its pool token exchange rate
+    StakePool,
+}
+
+// Sensitive parameter changes wait out TIMELOCK_DELAY between proposal and application.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterChange {
+    Fee(u16),
+    ProtocolFee(u16),
+    ReferralFee(u16),
+    Pmm { enabled: bool, k_bps: u16 },
+    WithdrawFee { withdraw_fee_bps: u16, to_treasury: bool },
+    JitProtection { window_slots: u64, fee_bps: u16 },
+    VolumeTiers([u64; VOLUME_TIER_COUNT]),
+    // Pubkey::default() clears the hook
+    Hook(Pubkey),
+    Authority(Pubkey),
+    DynamicFee { enabled: bool, min_fee: u16, max_fee: u16 },
+    Lbp { start_weight_x: u16, end_weight_x: u16, start_time: i64, end_time: i64 },
+    InventorySpread { spread_max_bps: u16, spread_target: Price },
+    LstCurve(bool),
+}
 
 #[account]
 #[derive(InitSpace)]
 pub struct Config {
-    pub seed: u64,
+    pub fee_tier: u16,
     pub authority: Option<Pubkey>,
+    pub pending_authority: Option<Pubkey>,
+    pub pending_change: Option<ParameterChange>,
+    pub change_effective_at: i64,
     pub mint_x: Pubkey,
     pub mint_y: Pubkey,
+    pub lp_mint: Pubkey,
     pub lp_bump: u8,
     pub config_bump: u8,
+    pub treasury_bump: u8,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
     pub fee: u16,
-    pub locked: bool
+    pub weight_x: u16,
+    pub weight_y: u16,
+    pub lbp_start_time: i64,
+    pub lbp_end_time: i64,
+    pub lbp_start_weight_x: u16,
+    pub lbp_end_weight_x: u16,
+    pub protocol_fee_bps: u16,
+    pub referral_fee_bps: u16,
+    pub dynamic_fee: bool,
+    pub min_fee: u16,
+    pub max_fee: u16,
+    pub max_price_impact_bps: u16,
+    pub oracle_type: OracleType,
+    // a Pyth feed id or a Switchboard aggregator address, depending on oracle_type
+    pub oracle_feed: [u8; 32],
+    pub secondary_oracle_type: OracleType,
+    pub secondary_oracle_feed: [u8; 32],
+    pub halt_on_stale_oracle: bool,
+    pub pmm_enabled: bool,
+    pub pmm_k_bps: u16,
+    pub max_oracle_deviation_bps: u16,
+    pub max_staleness_secs: u64,
+    pub paused: u8,
+    pub allowlist_deposits: bool,
+    pub allowlist_swaps: bool,
+    pub badge_mint: Option<Pubkey>,
+    pub flash_loan_active: bool,
+    pub flash_loan_is_x: bool,
+    pub flash_loan_amount: u64,
+    pub flash_loan_balance: u64,
+    pub flash_swap_active: bool,
+    pub flash_swap_is_x: bool,
+    pub flash_swap_amount_out: u64,
+    pub flash_swap_balance_x: u64,
+    pub flash_swap_balance_y: u64,
+    pub fees_x: u128,
+    pub fees_y: u128,
+    pub volume_x_in: u128,
+    pub volume_y_in: u128,
+    pub swap_count: u64,
+    // New fields go after version and are carved out of the reserved padding or
+    // added through migrate_config; zero bytes must decode as their defaults.
+    pub version: u8,
+    // kept in the pool for the remaining LPs unless routed to the treasury
+    pub withdraw_fee_bps: u16,
+    pub withdraw_fee_to_treasury: bool,
+    // withdrawals within jit_window_slots of the user's last deposit pay jit_fee_bps
+    // into the pool, or are refused outright while the fee is 0
+    pub jit_window_slots: u64,
+    pub jit_fee_bps: u16,
+    // Position-mode pools track liquidity in Position accounts instead of LP tokens,
+    // and hold the LP share of swap fees outside the reserves until positions collect it.
+    pub position_mode: bool,
+    pub position_liquidity: u64,
+    pub fee_growth_x: u128,
+    pub fee_growth_y: u128,
+    pub unclaimed_fees_x: u64,
+    pub unclaimed_fees_y: u64,
+    // ascending y-side volume a swapper needs for each UserStats tier, zero if unused
+    pub volume_tiers: [u64; VOLUME_TIER_COUNT],
+    // called before and after every swap; must hold a HookRegistration when it runs
+    pub hook_program: Option<Pubkey>,
+    // Pmm falls back to the constant-product curve while no oracle price is available
+    pub curve_type: CurveType,
+    pub amp: u64,
+    // set when either mint charges a transfer fee: payouts are measured at the
+    // recipient and slippage minimums apply to what actually arrives
+    pub fee_on_transfer: bool,
+    // LP shares are claims on whatever the vaults hold, so the reserves follow the
+    // vault balances of mints that rebase outside of transfers
+    pub rebasing: bool,
+    // rejects a swap sharing its transaction with another swap on this pool that a
+    // different wallet signed, the shape of a bundled sandwich
+    pub sandwich_guard: bool,
+    // while non-zero, swaps only execute through batch auctions of this many seconds
+    pub batch_window_secs: u32,
+    // launch caps on the pool's value and on each wallet's total deposits, both in y
+    // at the reserve ratio; 0 leaves a cap off
+    pub max_tvl: u64,
+    pub max_deposit_per_wallet: u64,
+    // the allowlist flags stop applying at this timestamp; 0 keeps them until cleared
+    pub allowlist_ends_at: i64,
+    // held empty for a bonding-curve launch until it graduates into the pool
+    pub launch_pending: bool,
+    // swaps pay launch_fee from launch_fee_starts_at, decaying linearly to the pool's
+    // own fee over launch_fee_decay_secs; 0 leaves the schedule off
+    pub launch_fee: u16,
+    pub launch_fee_starts_at: i64,
+    pub launch_fee_decay_secs: u32,
+    // fees skew by up to spread_max_bps against trades that push the spot price away
+    // from spread_target and for trades that pull it back; a zero target follows the oracle
+    pub spread_max_bps: u16,
+    pub spread_target: Price,
+    // while set, admin rights belong to whoever holds this NFT and authority is ignored
+    pub admin_nft_mint: Option<Pubkey>,
+    pub reserved: [u8; CONFIG_RESERVED_BYTES],
+}
+
+impl Config {
+    // The canonical pool for a pair and fee tier, whichever order the mints come in.
+    // Initialize only accepts the pair sorted, so there is one address per pair and tier.
+    pub fn address(mint_a: &Pubkey, mint_b: &Pubkey, fee_tier: u16) -> (Pubkey, u8) {
+        let (mint_x, mint_y) = match mint_a < mint_b {
+            true => (mint_a, mint_b),
+            false => (mint_b, mint_a),
+        };
+        Pubkey::find_program_address(&[b"config", mint_x.as_ref(), mint_y.as_ref(), &fee_tier.to_le_bytes()], &crate::ID)
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.lbp_enabled() || self.weight_x != self.weight_y
+    }
+
+    pub fn curve(&self, is_x: bool, oracle_price: Option<Price>) -> Result<Curve> {
+        Ok(match self.curve_type {
+            CurveType::ConstantProduct => Curve::ConstantProduct(ConstantProduct),
+            CurveType::Stable => Curve::Stable(Stable { amp: self.amp }),
+            CurveType::Weighted => {
+                let (weight_x, weight_y) = self.current_weights(Clock::get()?.unix_timestamp);
+                let (weight_in, weight_out) = match is_x {
+                    true => (weight_x, weight_y),
+                    false => (weight_y, weight_x),
+                };
+                Curve::Weighted(Weighted { weight_in, weight_out })
+            }
+            CurveType::Pmm => match oracle_price {
+                Some(oracle_price) => Curve::Pmm(Pmm { oracle_price, is_x, k_bps: self.pmm_k_bps }),
+                None => Curve::ConstantProduct(ConstantProduct),
+            },
+            // trading at par against a stale rate would hand arbitrageurs the drift
+            CurveType::Lst => match oracle_price {
+                Some(rate) => Curve::Lst(Lst { amp: self.amp, rate, is_x }),
+                None => return err!(AmmError::StaleOracle),
+            },
+        })
+    }
+
+    // Zaps, single-sided withdrawals, flash swaps and limit prices are sized with
+    // constant-product formulas, so they are refused on pools priced by another curve.
+    pub fn check_product_curve(&self) -> Result<()> {
+        match self.curve_type {
+            CurveType::Weighted => err!(AmmError::UnsupportedForWeightedPool),
+            CurveType::Stable | CurveType::Lst => err!(AmmError::UnsupportedForStablePool),
+            CurveType::ConstantProduct | CurveType::Pmm => Ok(()),
+        }
+    }
+
+    pub fn lbp_enabled(&self) -> bool {
+        self.lbp_end_time != 0
+    }
+
+    // Weights shift linearly from the start to the end weights over the LBP window.
+    pub fn current_weights(&self, now: i64) -> (u16, u16) {
+        if !self.lbp_enabled() {
+            return (self.weight_x, self.weight_y);
+        }
+
+        let weight_x = match now {
+            t if t <= self.lbp_start_time => self.lbp_start_weight_x,
+            t if t >= self.lbp_end_time => self.lbp_end_weight_x,
+            t => {
+                let start = self.lbp_start_weight_x as i128;
+                let end = self.lbp_end_weight_x as i128;
+                let elapsed = (t - self.lbp_start_time) as i128;
+                let duration = (self.lbp_end_time - self.lbp_start_time) as i128;
+                (start + (end - start) * elapsed / duration) as u16
+            }
+        };
+
+        (weight_x, TOTAL_WEIGHT - weight_x)
+    }
+
+    // Emergency mode halts everything except withdrawals, which it forces open.
+    pub fn check_not_paused(&self, flag: u8) -> Result<()> {
+        let paused = match self.paused & PAUSE_EMERGENCY != 0 {
+            true => PAUSE_ALL & !PAUSE_WITHDRAW,
+            false => self.paused,
+        };
+        require!(paused & flag == 0, AmmError::PoolLocked);
+        Ok(())
+    }
+
+    pub fn flash_active(&self) -> bool {
+        self.flash_loan_active || self.flash_swap_active
+    }
+
+    fn allowlist_active(&self) -> Result<bool> {
+        Ok(self.allowlist_ends_at == 0 || Clock::get()?.unix_timestamp < self.allowlist_ends_at)
+    }
+
+    // Allowlist entries are PDAs validated by seeds in the context, so only their presence is checked.
+    pub fn check_deposit_allowed(&self, entry: Option<&AllowlistEntry>) -> Result<()> {
+        require!(!self.launch_pending, AmmError::LaunchPending);
+        require!(!self.allowlist_deposits || entry.is_some() || !self.allowlist_active()?, AmmError::NotAllowlisted);
+        Ok(())
+    }
+
+    // Values x at the current reserve ratio, so call it once the deposit is credited.
+    pub fn y_value(&self, amount_x: u64, amount_y: u64) -> Result<u64> {
+        let x_value = match self.reserve_x {
+            0 => 0,
+            _ => mul_div(amount_x as u128, self.reserve_y as u128, self.reserve_x as u128)?,
+        };
+        to_u64(x_value + amount_y as u128)
+    }
+
+    pub fn check_tvl_cap(&self) -> Result<()> {
+        if self.max_tvl != 0 {
+            require!(self.y_value(self.reserve_x, self.reserve_y)? <= self.max_tvl, AmmError::TvlCapExceeded);
+        }
+        Ok(())
+    }
+
+    pub fn check_swap_allowed(&self, entry: Option<&AllowlistEntry>) -> Result<()> {
+        require!(!self.allowlist_swaps || entry.is_some() || !self.allowlist_active()?, AmmError::NotAllowlisted);
+        Ok(())
+    }
+
+    pub fn check_badge(&self, badge_ata: Option<&InterfaceAccount<TokenAccount>>) -> Result<()> {
+        if let Some(badge_mint) = self.badge_mint {
+            let badge_ata = badge_ata.ok_or(AmmError::MissingBadge)?;
+            require_keys_eq!(badge_ata.mint, badge_mint, AmmError::MissingBadge);
+            require!(badge_ata.amount > 0, AmmError::MissingBadge);
+        }
+        Ok(())
+    }
+
+    pub fn current_fee(&self, volatility: Option<&Volatility>, is_x: bool, oracle_price: Option<Price>) -> Result<u16> {
+        let fee = match self.dynamic_fee {
+            true => volatility.ok_or(AmmError::MissingVolatilityAccount)?.fee(self.min_fee, self.max_fee),
+            false => self.fee,
+        };
+        self.imbalance_fee(self.launch_fee_floor(fee)?, is_x, oracle_price)
+    }
+
+    // The skew grows linearly with the spot price's distance from the target and is
+    // at its widest once that reaches SPREAD_FULL_DEVIATION_BPS.
+    fn imbalance_fee(&self, fee: u16, is_x: bool, oracle_price: Option<Price>) -> Result<u16> {
+        if self.spread_max_bps == 0 || self.reserve_x == 0 || self.reserve_y == 0 {
+            return Ok(fee);
+        }
+        let target = match (self.spread_target, oracle_price) {
+            (Price(0), Some(oracle_price)) => oracle_price,
+            (Price(0), None) => return Ok(fee),
+            (target, _) => target,
+        };
+
+        let spot = Price::from_reserves(self.reserve_x, self.reserve_y);
+        let deviation_bps = mul_div(spot.0.abs_diff(target.0), BPS_DENOMINATOR, target.0)?.min(SPREAD_FULL_DEVIATION_BPS);
+        let skew = mul_div(self.spread_max_bps as u128, deviation_bps, SPREAD_FULL_DEVIATION_BPS)? as u16;
+
+        // selling x into the pool lowers its spot price, so it restores balance while x trades rich
+        match is_x == (spot > target) {
+            true => Ok(fee.saturating_sub(skew)),
+            false => Ok(fee.saturating_add(skew).min(MAX_FEE_BPS)),
+        }
+    }
+
+    // The launch fee holds until its schedule starts and then closes the gap to the
+    // regular fee linearly; it never takes the fee below what it would otherwise be.
+    fn launch_fee_floor(&self, fee: u16) -> Result<u16> {
+        if self.launch_fee <= fee {
+            return Ok(fee);
+        }
+
+        let elapsed = Clock::get()?.unix_timestamp.saturating_sub(self.launch_fee_starts_at).max(0) as u128;
+        let duration = self.launch_fee_decay_secs as u128;
+        if elapsed >= duration {
+            return Ok(fee);
+        }
+        let decay = mul_div((self.launch_fee - fee) as u128, elapsed, duration)?;
+        Ok(self.launch_fee - decay as u16)
+    }
+
+    // The EMA has to see every swap on a dynamic-fee pool, or a swapper could leave the
+    // account out and keep the fee from rising.
+    pub fn update_volatility(&self, volatility: Option<&mut Volatility>) -> Result<()> {
+        match volatility {
+            Some(volatility) => volatility.update(self.reserve_x, self.reserve_y),
+            None => {
+                require!(!self.dynamic_fee, AmmError::MissingVolatilityAccount);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn record_swap(&mut self, is_x: bool, amount_in: u64, fee: u64) -> Result<()> {
+        let (volume, fees) = match is_x {
+            true => (&mut self.volume_x_in, &mut self.fees_x),
+            false => (&mut self.volume_y_in, &mut self.fees_y),
+        };
+
+        *volume = volume.checked_add(amount_in as u128).ok_or(AmmError::Overflow)?;
+        *fees = fees.checked_add(fee as u128).ok_or(AmmError::Overflow)?;
+        self.swap_count = self.swap_count.checked_add(1).ok_or(AmmError::Overflow)?;
+
+        Ok(())
+    }
+
+    // Reserves are tracked in state so tokens sent straight to the vaults are
+    // ignored until absorbed with `sync` or swept with `skim`.
+    pub fn credit_reserve(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let reserve = match is_x {
+            true => &mut self.reserve_x,
+            false => &mut self.reserve_y,
+        };
+        *reserve = reserve.checked_add(amount).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    // Brings a rebasing pool's reserves up to date before anything is priced, so a
+    // rebase in either direction lands on the LPs pro rata instead of being skimmable
+    // or tripping the vault-backing check.
+    pub fn rebase(&mut self, balance_x: u64, balance_y: u64) -> Result<()> {
+        if !self.rebasing {
+            return Ok(());
+        }
+
+        self.reserve_x = balance_x.checked_sub(self.unclaimed_fees_x).ok_or(AmmError::Underflow)?;
+        self.reserve_y = balance_y.checked_sub(self.unclaimed_fees_y).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+
+    pub fn debit_reserve(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let reserve = match is_x {
+            true => &mut self.reserve_x,
+            false => &mut self.reserve_y,
+        };
+        *reserve = reserve.checked_sub(amount).ok_or(AmmError::Underflow)?;
+        Ok(())
+    }
+
+    // Moves the LP cut of a swap fee out of the reserves and into the per-liquidity fee
+    // growth, so each position earns it in proportion to its liquidity.
+    pub fn accrue_position_fee(&mut self, is_x: bool, lp_fee: u64) -> Result<()> {
+        if !self.position_mode || self.position_liquidity == 0 || lp_fee == 0 {
+            return Ok(());
+        }
+
+        self.debit_reserve(is_x, lp_fee)?;
+        let growth = mul_div(lp_fee as u128, REWARD_PRECISION, self.position_liquidity as u128)?;
+        let (fee_growth, unclaimed_fees) = match is_x {
+            true => (&mut self.fee_growth_x, &mut self.unclaimed_fees_x),
+            false => (&mut self.fee_growth_y, &mut self.unclaimed_fees_y),
+        };
+        *fee_growth = fee_growth.checked_add(growth).ok_or(AmmError::Overflow)?;
+        *unclaimed_fees = unclaimed_fees.checked_add(lp_fee).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn k(&self) -> u128 {
+        self.reserve_x as u128 * self.reserve_y as u128
+    }
+
+    // Defense in depth: a swap or deposit must never shrink the reserve product, nor
+    // leave the vaults holding less than the reserves they back. The product is only
+    // the invariant of the constant-product curves, so other curves skip that half.
+    pub fn check_invariant(&self, k_before: u128, balance_x: u64, balance_y: u64) -> Result<()> {
+        require!(
+            balance_x >= self.reserve_x.saturating_add(self.unclaimed_fees_x) && balance_y >= self.reserve_y.saturating_add(self.unclaimed_fees_y),
+            AmmError::InvariantViolation
+        );
+        if matches!(self.curve_type, CurveType::ConstantProduct | CurveType::Pmm) {
+            require!(self.k() >= k_before, AmmError::InvariantViolation);
+        }
+        Ok(())
+    }
+
+    // Rejects trades that moved the price of x by more than max_price_impact_bps; 0 disables the check.
+    pub fn check_price_impact(&self, reserve_x_before: u64, reserve_y_before: u64) -> Result<()> {
+        if self.max_price_impact_bps == 0 {
+            return Ok(());
+        }
+
+        let impact_bps = self.price_impact_bps(reserve_x_before, reserve_y_before)?;
+        require!(impact_bps <= self.max_price_impact_bps as u128, AmmError::PriceImpactTooHigh);
+        Ok(())
+    }
+
+    pub fn price_impact_bps(&self, reserve_x_before: u64, reserve_y_before: u64) -> Result<u128> {
+        let before = reserve_y_before as u128 * self.reserve_x as u128;
+        let after = self.reserve_y as u128 * reserve_x_before as u128;
+        mul_div(before.abs_diff(after), BPS_DENOMINATOR, before)
+    }
+
+    // Price of x in y, read from the primary feed or, once that is
+    // stale, the secondary. None means no oracle is configured or both feeds are stale
+    // and the pool degrades to pure curve pricing instead of halting.
+    pub fn oracle_price(&self, primary: Option<&AccountInfo>, secondary: Option<&AccountInfo>, decimals_x: u8, decimals_y: u8) -> Result<Option<Price>> {
+        if self.oracle_type == OracleType::None {
+            return Ok(None);
+        }
+
+        let mints = (self.mint_x, self.mint_y);
+        if let Some(price) = feed_price(self.oracle_type, &self.oracle_feed, primary, self.max_staleness_secs, decimals_x, decimals_y, mints)? {
+            return Ok(Some(price));
+        }
+        if self.secondary_oracle_type != OracleType::None {
+            if let Some(price) = feed_price(self.secondary_oracle_type, &self.secondary_oracle_feed, secondary, self.max_staleness_secs, decimals_x, decimals_y, mints)? {
+                return Ok(Some(price));
+            }
+        }
+
+        require!(!self.halt_on_stale_oracle, AmmError::StaleOracle);
+        Ok(None)
+    }
+
+    pub fn check_oracle_price(&self, oracle_price: Option<Price>, is_x: bool, amount_in: u64, amount_out: u64) -> Result<()> {
+        match oracle_price {
+            Some(oracle_price) => check_deviation(oracle_price, is_x, amount_in, amount_out, self.max_oracle_deviation_bps),
+            None => Ok(()),
+        }
+    }
+
+    // The admin NFT account is only read when one is bound; the signer must own it.
+    pub fn check_authority(&self, signer: Pubkey, admin_nft_ata: Option<&InterfaceAccount<TokenAccount>>) -> Result<()> {
+        if let Some(admin_nft_mint) = self.admin_nft_mint {
+            let admin_nft_ata = admin_nft_ata.ok_or(AmmError::MissingAdminNft)?;
+            require_keys_eq!(admin_nft_ata.mint, admin_nft_mint, AmmError::MissingAdminNft);
+            require_keys_eq!(admin_nft_ata.owner, signer, AmmError::Unauthorized);
+            require!(admin_nft_ata.amount > 0, AmmError::MissingAdminNft);
+            return Ok(());
+        }
+
+        match self.authority {
+            Some(authority) => require_keys_eq!(authority, signer, AmmError::Unauthorized),
+            None => return err!(AmmError::NoAuthoritySet),
+        }
+        Ok(())
+    }
 }
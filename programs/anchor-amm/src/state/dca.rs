@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+use crate::price::Price;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Dca {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub seed: u64,
+    pub is_x: bool,
+    pub amount_per_fill: u64,
+    pub interval: i64,
+    // minimum output per unit of input, as a Q64.64 price
+    pub min_price: Price,
+    pub next_fill_time: i64,
+    pub amount_spent: u64,
+    pub amount_out: u64,
+    pub bump: u8,
+}
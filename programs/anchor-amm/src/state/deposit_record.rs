@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+// When the depositor last added liquidity, so withdrawals right after a deposit can be
+// penalised. Only the depositing wallet is tracked: LP tokens moved to another wallet
+// leave the record behind.
+#[account]
+#[derive(InitSpace)]
+pub struct DepositRecord {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub last_deposit_slot: u64,
+    pub last_deposit_at: i64,
+    pub bump: u8,
+}
+
+impl DepositRecord {
+    pub fn in_jit_window(&self, slot: u64, window_slots: u64) -> bool {
+        slot < self.last_deposit_slot.saturating_add(window_slots)
+    }
+}
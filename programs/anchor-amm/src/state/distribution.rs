@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+// A funded Merkle airdrop. Each leaf commits to (index, claimant, amount) and a
+// ClaimStatus PDA per index stops the same leaf from being claimed twice.
+#[account]
+#[derive(InitSpace)]
+pub struct Distribution {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub seed: u64,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub num_claimed: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimStatus {
+    pub distribution: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
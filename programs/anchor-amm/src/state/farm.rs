@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::{BASE_BOOST_BPS, MAX_LOCK_BOOST_BPS, MAX_LOCK_DURATION, REWARD_PRECISION};
+use crate::math::{mul_div, to_u64};
+use crate::utils::bps_of;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    pub config: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub emission_rate: u64,
+    pub acc_reward_per_share: u128,
+    pub total_staked: u64,
+    pub total_weight: u64,
+    pub unallocated_rewards: u64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl Farm {
+    // Emits rewards for the time since the last update, never more than has been funded.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update {
+            return Ok(());
+        }
+
+        if self.total_weight > 0 {
+            let elapsed = (now - self.last_update) as u128;
+            let emitted = elapsed
+                .checked_mul(self.emission_rate as u128)
+                .ok_or(AmmError::Overflow)?
+                .min(self.unallocated_rewards as u128);
+            let per_share = mul_div(emitted, REWARD_PRECISION, self.total_weight as u128)?;
+            self.acc_reward_per_share = self.acc_reward_per_share.checked_add(per_share).ok_or(AmmError::Overflow)?;
+            self.unallocated_rewards -= emitted as u64;
+        }
+
+        self.last_update = now;
+        Ok(())
+    }
+
+    // Recomputes the position's boosted weight and keeps the farm total in step.
+    pub fn reweight(&mut self, position: &mut StakePosition) -> Result<()> {
+        let weight = bps_of(position.amount, position.boost_bps)?;
+        self.total_weight = self
+            .total_weight
+            .checked_sub(position.weight)
+            .ok_or(AmmError::Underflow)?
+            .checked_add(weight)
+            .ok_or(AmmError::Overflow)?;
+        position.weight = weight;
+        Ok(())
+    }
+
+    // An expired lock drops the position back to the base weight.
+    pub fn expire_lock(&mut self, position: &mut StakePosition, now: i64) -> Result<()> {
+        if position.lock_end != 0 && !position.is_locked(now) {
+            position.lock_end = 0;
+            position.boost_bps = BASE_BOOST_BPS;
+            self.reweight(position)?;
+        }
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct StakePosition {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub weight: u64,
+    pub boost_bps: u16,
+    pub lock_end: i64,
+    pub reward_debt: u128,
+    pub pending_rewards: u64,
+    pub bump: u8,
+}
+
+impl StakePosition {
+    // Moves rewards accrued since the last sync into pending_rewards.
+    pub fn settle(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        let accrued = mul_div(self.weight as u128, acc_reward_per_share, REWARD_PRECISION)?
+            .checked_sub(self.reward_debt)
+            .ok_or(AmmError::Underflow)?;
+        self.pending_rewards = self.pending_rewards.checked_add(to_u64(accrued)?).ok_or(AmmError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn sync(&mut self, acc_reward_per_share: u128) -> Result<()> {
+        self.reward_debt = mul_div(self.weight as u128, acc_reward_per_share, REWARD_PRECISION)?;
+        Ok(())
+    }
+
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.lock_end
+    }
+
+    // The boost scales linearly from 1x with no lock up to MAX_LOCK_BOOST_BPS at MAX_LOCK_DURATION.
+    pub fn lock_boost(duration: i64) -> u16 {
+        let duration = duration.clamp(0, MAX_LOCK_DURATION) as i128;
+        let extra = (MAX_LOCK_BOOST_BPS - BASE_BOOST_BPS) as i128 * duration / MAX_LOCK_DURATION as i128;
+        BASE_BOOST_BPS + extra as u16
+    }
+}
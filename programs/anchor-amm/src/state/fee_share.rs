@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+
+// Stakers of gov_mint share the protocol fees paid into each epoch. Epochs close
+// permissionlessly after epoch_duration, snapshotting the stake eligible for them.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeShareVault {
+    pub gov_mint: Pubkey,
+    pub total_staked: u64,
+    pub eligible_stake: u64,
+    pub current_epoch: u64,
+    pub epoch_start: i64,
+    pub epoch_duration: i64,
+    pub bump: u8,
+}
+
+// A staker earns for an epoch on the lowest balance held during it, so stake added
+// mid-epoch only counts from the next one. Only the last two checkpoints are kept.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeShareStake {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub checkpoint_epoch: u64,
+    pub checkpoint_min: u64,
+    pub balance_before: u64,
+    pub previous_checkpoint_epoch: u64,
+    pub bump: u8,
+}
+
+impl FeeShareStake {
+    // Returns how far the stake eligible for `epoch` dropped with the change.
+    pub fn apply_change(&mut self, epoch: u64, amount: u64) -> u64 {
+        if self.checkpoint_epoch != epoch {
+            self.previous_checkpoint_epoch = self.checkpoint_epoch;
+            self.balance_before = self.amount;
+            self.checkpoint_min = self.amount;
+            self.checkpoint_epoch = epoch;
+        }
+
+        let checkpoint_min = self.checkpoint_min.min(amount);
+        let dropped = self.checkpoint_min - checkpoint_min;
+        self.checkpoint_min = checkpoint_min;
+        self.amount = amount;
+        dropped
+    }
+
+    pub fn eligible_stake(&self, epoch: u64) -> Result<u64> {
+        match epoch {
+            e if e > self.checkpoint_epoch => Ok(self.amount),
+            e if e == self.checkpoint_epoch => Ok(self.checkpoint_min),
+            e if e > self.previous_checkpoint_epoch => Ok(self.balance_before),
+            _ => err!(AmmError::EpochClaimExpired),
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FeeEpoch {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub eligible_stake: u64,
+    pub bump: u8,
+}
+
+// Fees of one mint paid into a closed epoch. Funding stops once claims begin so every
+// staker is paid against the same total.
+#[account]
+#[derive(InitSpace)]
+pub struct EpochReward {
+    pub fee_epoch: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+// Receipt that stops a staker claiming the same epoch reward twice.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeClaim {
+    pub epoch_reward: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
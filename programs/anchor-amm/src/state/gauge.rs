@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct GaugeController {
+    pub authority: Pubkey,
+    pub governance_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    pub genesis: i64,
+    pub epoch_duration: i64,
+    pub emission_per_epoch: u64,
+    // finalized emissions not yet paid out to farms
+    pub pending_distribution: u64,
+    pub bump: u8,
+}
+
+impl GaugeController {
+    pub fn current_epoch(&self, now: i64) -> u64 {
+        ((now - self.genesis).max(0) / self.epoch_duration) as u64
+    }
+
+    pub fn epoch_end(&self, epoch: u64) -> Result<i64> {
+        (epoch as i64)
+            .checked_add(1)
+            .and_then(|n| n.checked_mul(self.epoch_duration))
+            .and_then(|d| d.checked_add(self.genesis))
+            .ok_or(error!(AmmError::Overflow))
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Gauge {
+    pub controller: Pubkey,
+    pub farm: Pubkey,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Epoch {
+    pub controller: Pubkey,
+    pub epoch: u64,
+    pub total_votes: u64,
+    pub finalized: bool,
+    pub emission: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GaugeEpoch {
+    pub gauge: Pubkey,
+    pub epoch: u64,
+    pub votes: u64,
+    pub distributed: bool,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GaugeVote {
+    pub voter: Pubkey,
+    pub gauge: Pubkey,
+    pub epoch: u64,
+    pub amount: u64,
+    pub bump: u8,
+}
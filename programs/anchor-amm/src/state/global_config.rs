@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::{BPS_DENOMINATOR, HOLDING_TIER_COUNT, VOLUME_TIER_COUNT};
+
+// Protocol-wide settings shared by every pool, held in a singleton PDA.
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub default_protocol_fee_bps: u16,
+    pub pool_creation_fee: u64,
+    pub paused: bool,
+    pub pool_count: u64,
+    pub bump: u8,
+    // Swappers get the larger of the discount for their discount_mint balance and the one
+    // for their UserStats volume tier on the pool; tier 0 holds no discount.
+    pub discount_mint: Pubkey,
+    pub holding_thresholds: [u64; HOLDING_TIER_COUNT],
+    pub holding_discount_bps: [u16; HOLDING_TIER_COUNT],
+    pub volume_discount_bps: [u16; VOLUME_TIER_COUNT],
+    // lets new pools list mints with extensions check_mint_extensions would reject
+    pub allow_unsafe_extensions: bool,
+}
+
+impl GlobalConfig {
+    // Halts pool creation, swaps and deposits across the protocol; withdrawals stay open.
+    pub fn check_not_paused(&self) -> Result<()> {
+        require!(!self.paused, AmmError::ProtocolPaused);
+        Ok(())
+    }
+
+    pub fn discounted_fee(&self, fee: u16, holding: u64, volume_tier: u8) -> u16 {
+        let holding_discount = self
+            .holding_thresholds
+            .iter()
+            .zip(self.holding_discount_bps)
+            .filter(|(threshold, _)| **threshold != 0 && holding >= **threshold)
+            .map(|(_, discount)| discount)
+            .max()
+            .unwrap_or(0);
+        let volume_discount = match volume_tier {
+            0 => 0,
+            tier => self.volume_discount_bps[(tier as usize).min(VOLUME_TIER_COUNT) - 1],
+        };
+
+        let discount = holding_discount.max(volume_discount) as u128;
+        fee - (fee as u128 * discount / BPS_DENOMINATOR) as u16
+    }
+}
+
+// Creators holding a waiver skip the pool creation fee; only its presence is checked.
+#[account]
+#[derive(InitSpace)]
+pub struct FeeWaiver {
+    pub creator: Pubkey,
+    pub bump: u8,
+}
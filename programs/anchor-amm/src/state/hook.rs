@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+// Programs the protocol admin has approved as swap hooks. A pool's hook only runs
+// while its registration exists, so removing one disables it on every pool at once.
+#[account]
+#[derive(InitSpace)]
+pub struct HookRegistration {
+    pub program_id: Pubkey,
+    pub bump: u8,
+}
+
+// Sent to the hook's before_swap and after_swap instructions. Before the swap
+// amount_out is 0 and the reserves are the pre-swap ones; a before_swap hook may set
+// a u16 fee in bps as return data to override the pool fee for this swap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SwapHookArgs {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_bps: u16,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
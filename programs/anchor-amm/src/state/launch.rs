@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+
+// A token sale along a constant-product curve with a virtual quote reserve, run ahead
+// of its (empty) pool. The virtual reserve sets the opening price: buyers receive
+// base_remaining * q / (virtual_quote + quote_raised + q).
+#[account]
+#[derive(InitSpace)]
+pub struct Launch {
+    pub creator: Pubkey,
+    pub config: Pubkey,
+    pub base_is_x: bool,
+    pub virtual_quote: u64,
+    pub raise_target: u64,
+    pub quote_raised: u64,
+    pub base_remaining: u64,
+    pub graduated: bool,
+    pub bump: u8,
+}
+
+impl Launch {
+    pub fn quote_reserve(&self) -> Result<u64> {
+        Ok(self.virtual_quote.checked_add(self.quote_raised).ok_or(AmmError::Overflow)?)
+    }
+}
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+use crate::price::Price;
+
+#[account]
+#[derive(InitSpace)]
+pub struct LimitOrder {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub seed: u64,
+    pub is_x: bool,
+    // minimum output per unit of input, as a Q64.64 price
+    pub min_price: Price,
+    pub expires_at: i64,
+    pub filled: bool,
+    pub amount_out: u64,
+    pub bump: u8,
+}
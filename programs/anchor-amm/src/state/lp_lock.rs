@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::math::{mul_div, to_u64};
+
+// LP tokens escrowed until unlock_at. With vesting they release linearly from
+// locked_at to unlock_at instead of all at once.
+#[account]
+#[derive(InitSpace)]
+pub struct LpLock {
+    pub config: Pubkey,
+    pub owner: Pubkey,
+    pub lp_mint: Pubkey,
+    pub seed: u64,
+    pub amount: u64,
+    pub withdrawn: u64,
+    pub locked_at: i64,
+    pub unlock_at: i64,
+    pub vesting: bool,
+    pub bump: u8,
+}
+
+impl LpLock {
+    pub fn unlocked(&self, now: i64) -> Result<u64> {
+        if now >= self.unlock_at {
+            return Ok(self.amount);
+        }
+        if !self.vesting || now <= self.locked_at {
+            return Ok(0);
+        }
+
+        let elapsed = (now - self.locked_at) as u128;
+        let duration = (self.unlock_at - self.locked_at) as u128;
+        to_u64(mul_div(self.amount as u128, elapsed, duration)?)
+    }
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::OBSERVATION_CARDINALITY;
+use crate::price::Price;
+use crate::state::Oracle;
+
+// Fields are ordered so neither struct carries implicit padding, which zero-copy
+// accounts can't have.
+#[zero_copy]
+#[derive(Default)]
+pub struct Observation {
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+    pub timestamp: i64,
+    pub padding: [u8; 8],
+}
+
+impl Observation {
+    pub fn new(timestamp: i64, price_x_cumulative: u128, price_y_cumulative: u128) -> Self {
+        Self {
+            price_x_cumulative,
+            price_y_cumulative,
+            timestamp,
+            padding: [0; 8],
+        }
+    }
+}
+
+// Written on every swap and liquidity change, so it is zero-copy to avoid
+// deserializing the whole ring buffer each time.
+#[account(zero_copy)]
+pub struct Observations {
+    pub observations: [Observation; OBSERVATION_CARDINALITY],
+    pub config: Pubkey,
+    pub index: u16,
+    pub count: u16,
+    pub bump: u8,
+    pub padding: [u8; 11],
+}
+
+impl Observations {
+    // At most one snapshot per second; later updates in the same second are dropped.
+    pub fn write(&mut self, timestamp: i64, price_x_cumulative: u128, price_y_cumulative: u128) {
+        if self.count > 0 && self.observations[self.index as usize].timestamp == timestamp {
+            return;
+        }
+
+        if self.count > 0 {
+            self.index = (self.index + 1) % OBSERVATION_CARDINALITY as u16;
+        }
+        self.observations[self.index as usize] = Observation::new(timestamp, price_x_cumulative, price_y_cumulative);
+        self.count = self.count.saturating_add(1).min(OBSERVATION_CARDINALITY as u16);
+    }
+
+    // Time-weighted Q64.64 prices (y per x, x per y) over the last `window_secs`,
+    // given the oracle and the current vault reserves.
+    pub fn consult(&self, oracle: &Oracle, reserve_x: u64, reserve_y: u64, window_secs: u32) -> Result<(Price, Price)> {
+        require!(window_secs > 0, AmmError::InvalidTwapWindow);
+
+        let now = Clock::get()?.unix_timestamp;
+        let target = now - window_secs as i64;
+        let (current_x, current_y) = oracle.cumulative_at(now, reserve_x, reserve_y);
+
+        // Walk back from the newest snapshot to the first one at or before the target.
+        let mut after = Observation::new(now, current_x, current_y);
+        let mut before = None;
+        for i in 0..self.count as usize {
+            let idx = (self.index as usize + OBSERVATION_CARDINALITY - i) % OBSERVATION_CARDINALITY;
+            let observation = self.observations[idx];
+            if observation.timestamp <= target {
+                before = Some(observation);
+                break;
+            }
+            after = observation;
+        }
+        let before = before.ok_or(AmmError::ObservationTooOld)?;
+
+        // Interpolate the cumulative prices at exactly the target timestamp.
+        let span = (after.timestamp - before.timestamp) as u128;
+        let offset = (target - before.timestamp) as u128;
+        let interpolate = |start: u128, end: u128| -> u128 {
+            if span == 0 {
+                return start;
+            }
+            start.wrapping_add(end.wrapping_sub(start) / span * offset)
+        };
+        let target_x = interpolate(before.price_x_cumulative, after.price_x_cumulative);
+        let target_y = interpolate(before.price_y_cumulative, after.price_y_cumulative);
+
+        Ok((
+            Price(current_x.wrapping_sub(target_x) / window_secs as u128),
+            Price(current_y.wrapping_sub(target_y) / window_secs as u128),
+        ))
+    }
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::price::Price;
+use crate::state::Observations;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Oracle {
+    pub config: Pubkey,
+    pub price_x_cumulative: u128,
+    pub price_y_cumulative: u128,
+    pub last_timestamp: i64,
+    pub bump: u8,
+}
+
+impl Oracle {
+    // Accumulators extended to `now` with the given reserves, which must be the
+    // reserves in effect since the last update.
+    // Accumulators wrap on overflow; consumers only ever use differences.
+    pub fn cumulative_at(&self, now: i64, reserve_x: u64, reserve_y: u64) -> (u128, u128) {
+        let elapsed = now.saturating_sub(self.last_timestamp);
+
+        if elapsed <= 0 || reserve_x == 0 || reserve_y == 0 {
+            return (self.price_x_cumulative, self.price_y_cumulative);
+        }
+
+        let price_x = Price::from_reserves(reserve_x, reserve_y).0;
+        let price_y = Price::from_reserves(reserve_y, reserve_x).0;
+        (
+            self.price_x_cumulative.wrapping_add(price_x.wrapping_mul(elapsed as u128)),
+            self.price_y_cumulative.wrapping_add(price_y.wrapping_mul(elapsed as u128)),
+        )
+    }
+
+    // Accumulates the pre-trade Q64.64 prices over the time since the last update
+    // and records a snapshot in the observation buffer.
+    pub fn update(&mut self, observations: &mut Observations, reserve_x: u64, reserve_y: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        (self.price_x_cumulative, self.price_y_cumulative) = self.cumulative_at(now, reserve_x, reserve_y);
+        self.last_timestamp = now;
+
+        observations.write(now, self.price_x_cumulative, self.price_y_cumulative);
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+// Replay protection for an owner's signed swap permits. Permits are consumed in
+// order: only the one carrying next_nonce is accepted.
+#[account]
+#[derive(InitSpace)]
+pub struct PermitNonce {
+    pub owner: Pubkey,
+    pub next_nonce: u64,
+    pub bump: u8,
+}
+
+// The order an owner signs off-chain with ed25519; the Borsh encoding is the signed
+// message. Binding the pool and direction keeps a permit from being replayed elsewhere.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SwapPermit {
+    pub config: Pubkey,
+    pub is_x: bool,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    pub expiry: i64,
+    pub nonce: u64,
+}
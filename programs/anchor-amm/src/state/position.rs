@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::REWARD_PRECISION;
+use crate::math::{mul_div, to_u64};
+
+// A share of a position-mode pool, owned by whoever holds the one-of-one position_mint.
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    pub config: Pubkey,
+    pub position_mint: Pubkey,
+    pub liquidity: u64,
+    pub fee_growth_last_x: u128,
+    pub fee_growth_last_y: u128,
+    pub fees_owed_x: u64,
+    pub fees_owed_y: u64,
+    pub bump: u8,
+}
+
+impl Position {
+    // Banks the fees earned since the last checkpoint; must run before liquidity changes.
+    pub fn settle(&mut self, fee_growth_x: u128, fee_growth_y: u128) -> Result<()> {
+        let owed_x = mul_div(self.liquidity as u128, fee_growth_x - self.fee_growth_last_x, REWARD_PRECISION)?;
+        let owed_y = mul_div(self.liquidity as u128, fee_growth_y - self.fee_growth_last_y, REWARD_PRECISION)?;
+
+        self.fees_owed_x = self.fees_owed_x.checked_add(to_u64(owed_x)?).ok_or(AmmError::Overflow)?;
+        self.fees_owed_y = self.fees_owed_y.checked_add(to_u64(owed_y)?).ok_or(AmmError::Overflow)?;
+        self.fee_growth_last_x = fee_growth_x;
+        self.fee_growth_last_y = fee_growth_y;
+        Ok(())
+    }
+}
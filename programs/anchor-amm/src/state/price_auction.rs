@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::math::mul_div;
+use crate::price::Price;
+
+// A descending-price sale of sale_amount base that opens an empty pool. Prices are
+// Q64.64 quote per base and fall linearly from start_price to end_price;
+// the auction clears as soon as the bids cover the whole sale at the current price.
+#[account]
+#[derive(InitSpace)]
+pub struct PriceAuction {
+    pub creator: Pubkey,
+    pub config: Pubkey,
+    pub base_is_x: bool,
+    pub sale_amount: u64,
+    // base the creator sets aside to pair with the proceeds in the pool
+    pub liquidity_amount: u64,
+    pub start_price: Price,
+    pub end_price: Price,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub total_quote: u64,
+    // None until the auction clears
+    pub clearing_price: Option<Price>,
+    // filled in at settlement: base owed to bidders and the quote they paid for it
+    pub sold_amount: u64,
+    pub quote_used: u64,
+    pub settled: bool,
+    pub bump: u8,
+}
+
+impl PriceAuction {
+    pub fn price_at(&self, now: i64) -> Result<Price> {
+        if now >= self.ends_at {
+            return Ok(self.end_price);
+        }
+        let elapsed = now.saturating_sub(self.starts_at).max(0) as u128;
+        let duration = (self.ends_at - self.starts_at) as u128;
+        let drop = mul_div(self.start_price.0 - self.end_price.0, elapsed, duration)?;
+        Ok(Price(self.start_price.0 - drop))
+    }
+
+    // Cleared auctions settle at their clearing price; ones that ran out of time sell
+    // what the bids cover at the floor.
+    pub fn settlement_price(&self) -> Price {
+        self.clearing_price.unwrap_or(self.end_price)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AuctionBid {
+    pub bidder: Pubkey,
+    pub auction: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::REGISTRY_PAGE_SIZE;
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, PartialEq, Eq)]
+pub enum CurveType {
+    ConstantProduct,
+    Weighted,
+    Stable,
+    Pmm,
+    // stable curve over the LST side valued at its stake pool exchange rate
+    Lst,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy)]
+pub struct PoolRecord {
+    pub config: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub fee_tier: u16,
+    pub curve_type: CurveType,
+}
+
+// Pools are appended in creation order, REGISTRY_PAGE_SIZE per page, so clients can
+// enumerate them from GlobalConfig::pool_count without scanning program accounts.
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryPage {
+    pub page: u64,
+    #[max_len(REGISTRY_PAGE_SIZE)]
+    pub pools: Vec<PoolRecord>,
+    pub bump: u8,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::math::{mul_div, to_u64};
+use crate::price::Price;
+
+#[account]
+#[derive(InitSpace)]
+pub struct TwammOrder {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub seed: u64,
+    pub is_x: bool,
+    // minimum output per unit of input, as a Q64.64 price; bounds every execution
+    pub min_price: Price,
+    pub amount_total: u64,
+    pub amount_sold: u64,
+    pub amount_out: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub last_executed: i64,
+    pub bump: u8,
+}
+
+impl TwammOrder {
+    // Portion of the order that should have been sold by `now` but has not been yet.
+    pub fn amount_due(&self, now: i64) -> Result<u64> {
+        let elapsed = now.min(self.end_time) - self.start_time;
+        let duration = self.end_time - self.start_time;
+        let target = to_u64(mul_div(self.amount_total as u128, elapsed as u128, duration as u128)?)?;
+        Ok(target.checked_sub(self.amount_sold).ok_or(AmmError::Underflow)?)
+    }
+}
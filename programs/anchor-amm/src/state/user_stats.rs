@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::VOLUME_TIER_COUNT;
+
+// A wallet's swap history on one pool, created on its first swap if the swapper passes
+// the account. Volume is kept per side in raw token units.
+#[account]
+#[derive(InitSpace)]
+pub struct UserStats {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub volume_x: u128,
+    pub volume_y: u128,
+    pub swap_count: u64,
+    pub first_swap_at: i64,
+    pub last_swap_at: i64,
+    pub bump: u8,
+}
+
+impl UserStats {
+    pub fn record(&mut self, volume_x: u64, volume_y: u64, now: i64) {
+        if self.swap_count == 0 {
+            self.first_swap_at = now;
+        }
+        self.volume_x = self.volume_x.saturating_add(volume_x as u128);
+        self.volume_y = self.volume_y.saturating_add(volume_y as u128);
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.last_swap_at = now;
+    }
+
+    // Tiers are counted on the y-side volume, the pool's quote token. An unset (zero)
+    // threshold is never reached.
+    pub fn tier(&self, thresholds: &[u64; VOLUME_TIER_COUNT]) -> u8 {
+        thresholds
+            .iter()
+            .take_while(|threshold| **threshold != 0 && self.volume_y >= **threshold as u128)
+            .count() as u8
+    }
+}
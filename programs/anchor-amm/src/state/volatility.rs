@@ -0,0 +1,51 @@
+use std::num::NonZeroU128;
+
+use anchor_lang::prelude::*;
+
+use crate::amm_error::AmmError;
+use crate::constants::{BPS_DENOMINATOR, PRICE_SCALE, VOLATILITY_CAP_BPS, VOLATILITY_EMA_PERIOD};
+
+#[account]
+#[derive(InitSpace)]
+pub struct Volatility {
+    pub config: Pubkey,
+    pub last_price: u128,
+    pub ema_bps: u64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+impl Volatility {
+    pub fn fee(&self, min_fee: u16, max_fee: u16) -> u16 {
+        let ema = self.ema_bps.min(VOLATILITY_CAP_BPS) as u128;
+        let range = max_fee.saturating_sub(min_fee) as u128;
+        min_fee + (range * ema / VOLATILITY_CAP_BPS as u128) as u16
+    }
+
+    pub fn update(&mut self, reserve_x: u64, reserve_y: u64) -> Result<()> {
+        if reserve_x == 0 {
+            return Ok(());
+        }
+
+        let price = (reserve_y as u128)
+            .checked_mul(PRICE_SCALE)
+            .ok_or(AmmError::Overflow)?
+            / reserve_x as u128;
+
+        if let Some(last_price) = NonZeroU128::new(self.last_price) {
+            let change_bps = price.abs_diff(self.last_price)
+                .checked_mul(BPS_DENOMINATOR)
+                .ok_or(AmmError::Overflow)?
+                / last_price;
+            let change_bps = u64::try_from(change_bps).unwrap_or(u64::MAX);
+
+            self.ema_bps = ((self.ema_bps as u128 * (VOLATILITY_EMA_PERIOD - 1) as u128
+                + change_bps as u128)
+                / VOLATILITY_EMA_PERIOD as u128) as u64;
+        }
+
+        self.last_price = price;
+        self.last_update = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
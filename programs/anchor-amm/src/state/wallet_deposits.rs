@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+// Everything a wallet has deposited into a capped pool, valued in y when it went in.
+// Withdrawals do not free up room under the cap.
+#[account]
+#[derive(InitSpace)]
+pub struct WalletDeposits {
+    pub config: Pubkey,
+    pub user: Pubkey,
+    pub deposited: u64,
+    pub bump: u8,
+}
@@ -0,0 +1,408 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    keccak,
+    program::{get_return_data, invoke},
+    program::invoke_signed,
+    program_option::COption,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    onchain::invoke_transfer_checked,
+    state::Mint as MintState,
+};
+use anchor_spl::token_interface::{close_account, sync_native, CloseAccount, SyncNative, TokenAccount, TransferChecked};
+
+use crate::amm_error::AmmError;
+use crate::constants::BPS_DENOMINATOR;
+use crate::state::SwapHookArgs;
+
+pub fn check_expiration(expiration: Option<i64>) -> Result<()> {
+    if let Some(expiration) = expiration {
+        require!(Clock::get()?.unix_timestamp <= expiration, AmmError::OfferExpired);
+    }
+    Ok(())
+}
+
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let share = (amount as u128)
+        .checked_mul(bps as u128)
+        .ok_or(AmmError::Overflow)?
+        / BPS_DENOMINATOR;
+    Ok(u64::try_from(share).map_err(|_| AmmError::Overflow)?)
+}
+
+// Drop-in for `transfer_checked` that resolves transfer-hook extra accounts
+// for the mint out of the context's remaining accounts.
+pub fn transfer_tokens<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )?;
+    Ok(())
+}
+
+// Rejects Token-2022 mints whose extensions let a third party move pooled funds or
+// that can't be transferred plainly. A confidential-transfer mint still moves plain
+// amounts into the vaults, which are never configured for confidential credits.
+// The protocol admin can waive the check for every new pool through GlobalConfig.
+pub fn check_mint_extensions(mint: &AccountInfo, allow_unsafe_extensions: bool) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID || allow_unsafe_extensions {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        match extension {
+            ExtensionType::PermanentDelegate => return err!(AmmError::PermanentDelegateNotAllowed),
+            ExtensionType::NonTransferable => return err!(AmmError::NonTransferableNotAllowed),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+pub fn is_non_transferable(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(state.get_extension_types()?.contains(&ExtensionType::NonTransferable))
+}
+
+// A permanent delegate or transfer hook lets someone other than the holder move or
+// block the token, so such a mint can't carry pool admin rights.
+pub fn check_admin_nft_extensions(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    let extensions = state.get_extension_types()?;
+    require!(
+        !extensions.contains(&ExtensionType::PermanentDelegate) && !extensions.contains(&ExtensionType::TransferHook),
+        AmmError::InvalidAdminNft
+    );
+    Ok(())
+}
+
+// Token-2022 TransferFee mints deliver less than the amount sent.
+pub fn has_transfer_fee(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(state.get_extension_types()?.contains(&ExtensionType::TransferFeeConfig))
+}
+
+// LP mints from before MintCloseAuthority was added can never be closed.
+pub fn has_close_authority(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(state.get_extension_types()?.contains(&ExtensionType::MintCloseAuthority))
+}
+
+pub fn token_balance(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+// Sends `amount` with `transfer` and returns what actually reached `to`, which
+// only differs from `amount` when the pool runs in fee-on-transfer mode.
+pub fn measure_delivery(fee_on_transfer: bool, to: &AccountInfo, amount: u64, send: impl FnOnce() -> Result<()>) -> Result<u64> {
+    if !fee_on_transfer {
+        send()?;
+        return Ok(amount);
+    }
+
+    let before = token_balance(to)?;
+    send()?;
+    Ok(token_balance(to)?.checked_sub(before).ok_or(AmmError::Underflow)?)
+}
+
+// The signer may spend from an account it owns, or from one that approved it as
+// delegate; the token program still caps the spend at the delegated amount.
+pub fn check_spend_authority(account: &TokenAccount, signer: &Pubkey, amount: u64) -> Result<()> {
+    if account.owner == *signer {
+        return Ok(());
+    }
+    require!(account.delegate == COption::Some(*signer), AmmError::NotDelegate);
+    require!(account.delegated_amount >= amount, AmmError::DelegationExceeded);
+    Ok(())
+}
+
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == native_mint::ID
+}
+
+pub fn wrap_sol<'info>(
+    user: AccountInfo<'info>,
+    user_ata: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    transfer(
+        CpiContext::new(system_program, Transfer { from: user, to: user_ata.clone() }),
+        amount,
+    )?;
+    sync_native(CpiContext::new(token_program, SyncNative { account: user_ata }))
+}
+
+pub fn unwrap_sol<'info>(
+    user: AccountInfo<'info>,
+    user_ata: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    close_account(CpiContext::new(
+        token_program,
+        CloseAccount {
+            account: user_ata,
+            destination: user.clone(),
+            authority: user,
+        },
+    ))
+}
+
+// Same self-CPI as `emit_cpi!`, usable from account-struct methods that have no `ctx`.
+pub fn emit_event_cpi<'info, E: anchor_lang::Event>(event_authority: &AccountInfo<'info>, bump: u8, event: E) -> Result<()> {
+    let data: Vec<u8> = anchor_lang::event::EVENT_IX_TAG_LE
+        .iter()
+        .copied()
+        .chain(event.data())
+        .collect();
+    let ix = Instruction::new_with_bytes(
+        crate::ID,
+        &data,
+        vec![AccountMeta::new_readonly(event_authority.key(), true)],
+    );
+    invoke_signed(&ix, std::slice::from_ref(event_authority), &[&[b"__event_authority", &[bump]]])?;
+    Ok(())
+}
+
+// Fails when another instruction of the transaction moves `config`'s price without
+// `signer` among its signers. The instructions sysvar only lists top-level
+// instructions, so a guarded swap must be one itself or a CPI could hide a second.
+pub fn require_sole_swapper(instructions: &AccountInfo, config: &Pubkey, signer: &Pubkey) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    let mut transaction = vec![];
+    while let Ok(ix) = load_instruction_at_checked(transaction.len(), instructions) {
+        transaction.push(ix);
+    }
+    check_sole_swapper(&transaction, current_index, config, signer)
+}
+
+fn check_sole_swapper(transaction: &[Instruction], current_index: usize, config: &Pubkey, signer: &Pubkey) -> Result<()> {
+    let current = transaction.get(current_index).ok_or(AmmError::SandwichDetected)?;
+    require_keys_eq!(current.program_id, crate::ID, AmmError::GuardedSwapCpi);
+
+    let price_moving: [&[u8]; 14] = [
+        &crate::instruction::Swap::DISCRIMINATOR,
+        &crate::instruction::SwapWithPermit::DISCRIMINATOR,
+        &crate::instruction::RouteSwap::DISCRIMINATOR,
+        &crate::instruction::SplitSwap::DISCRIMINATOR,
+        &crate::instruction::FlashSwapBegin::DISCRIMINATOR,
+        &crate::instruction::ExecuteTwammOrder::DISCRIMINATOR,
+        &crate::instruction::ExecuteDca::DISCRIMINATOR,
+        &crate::instruction::FillLimitOrder::DISCRIMINATOR,
+        &crate::instruction::RevealSwap::DISCRIMINATOR,
+        &crate::instruction::SettleBatch::DISCRIMINATOR,
+        &crate::instruction::ExecuteBuyback::DISCRIMINATOR,
+        &crate::instruction::DepositSingle::DISCRIMINATOR,
+        &crate::instruction::DepositImbalanced::DISCRIMINATOR,
+        &crate::instruction::WithdrawSingle::DISCRIMINATOR,
+    ];
+
+    for (index, ix) in transaction.iter().enumerate() {
+        if index != current_index
+            && ix.program_id == crate::ID
+            && price_moving.iter().any(|discriminator| ix.data.starts_with(discriminator))
+            && ix.accounts.iter().any(|meta| meta.pubkey == *config)
+        {
+            require!(
+                ix.accounts.iter().any(|meta| meta.is_signer && meta.pubkey == *signer),
+                AmmError::SandwichDetected
+            );
+        }
+    }
+    Ok(())
+}
+
+// A relayed permit must be preceded by an ed25519 program instruction that verifies
+// exactly one signature by `signer` over `message`, with all data inline.
+pub fn require_ed25519_signature(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    require!(current_index > 0, AmmError::InvalidPermit);
+    let ix = load_instruction_at_checked(current_index - 1, instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, AmmError::InvalidPermit);
+
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, AmmError::InvalidPermit);
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+    // offsets of the signature, public key and message, each followed by the index
+    // of the instruction holding it; u16::MAX means this instruction
+    require!(
+        read_u16(4) == u16::MAX && read_u16(8) == u16::MAX && read_u16(14) == u16::MAX,
+        AmmError::InvalidPermit
+    );
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+
+    let public_key = data.get(public_key_offset..public_key_offset + 32).ok_or(AmmError::InvalidPermit)?;
+    let signed = data.get(message_offset..message_offset + message_size).ok_or(AmmError::InvalidPermit)?;
+    require!(public_key == signer.as_ref() && signed == message, AmmError::InvalidPermit);
+    Ok(())
+}
+
+// Flash operations must be top-level instructions followed later in the same
+// transaction by the matching settlement instruction on the same pool.
+pub fn require_followed_by(instructions: &AccountInfo, discriminator: &[u8], config: &Pubkey, missing: AmmError) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    let current = load_instruction_at_checked(current_index, instructions)?;
+    require_keys_eq!(current.program_id, crate::ID, AmmError::FlashCpiNotAllowed);
+
+    let mut index = current_index + 1;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == crate::ID
+            && ix.data.starts_with(discriminator)
+            && ix.accounts.iter().any(|meta| meta.pubkey == *config)
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+
+    Err(missing.into())
+}
+
+// Pairs are hashed in sorted order, so proofs don't need to carry left/right flags.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| match node <= *sibling {
+        true => keccak::hashv(&[&node, sibling]).0,
+        false => keccak::hashv(&[sibling, &node]).0,
+    });
+    computed == root
+}
+
+// Hooks get the pool and the swapper read-only and unsigned, so they can inspect but
+// never move the user's funds. Returns the fee override a before_swap hook set, if any.
+pub fn invoke_swap_hook<'info>(hook_program: &AccountInfo<'info>, config: &AccountInfo<'info>, user: &AccountInfo<'info>, name: &str, args: &SwapHookArgs) -> Result<Option<u16>> {
+    let mut data = hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec();
+    args.serialize(&mut data)?;
+
+    let ix = Instruction {
+        program_id: hook_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(config.key(), false),
+            AccountMeta::new_readonly(user.key(), false),
+        ],
+        data,
+    };
+    invoke(&ix, &[config.clone(), user.clone(), hook_program.clone()])?;
+
+    Ok(match get_return_data() {
+        Some((program_id, data)) if program_id == hook_program.key() && data.len() == 2 => Some(u16::from_le_bytes([data[0], data[1]])),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ix(discriminator: &[u8], accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction::new_with_bytes(crate::ID, discriminator, accounts)
+    }
+
+    fn swap(config: Pubkey, user: Pubkey) -> Instruction {
+        ix(&crate::instruction::Swap::DISCRIMINATOR, vec![AccountMeta::new(user, true), AccountMeta::new(config, false)])
+    }
+
+    #[test]
+    fn route_swap_around_a_swap_is_rejected() {
+        let (config, victim, attacker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        // the pool only appears among the route's remaining accounts
+        let route = ix(
+            &crate::instruction::RouteSwap::DISCRIMINATOR,
+            vec![AccountMeta::new(attacker, true), AccountMeta::new(Pubkey::new_unique(), false), AccountMeta::new(config, false)],
+        );
+        let transaction = [route.clone(), swap(config, victim), route];
+
+        assert!(check_sole_swapper(&transaction, 1, &config, &victim).is_err());
+        assert!(check_sole_swapper(&transaction, 0, &config, &attacker).is_err());
+        assert!(check_sole_swapper(&transaction[..2], 1, &config, &victim).is_err());
+    }
+
+    #[test]
+    fn permit_beside_a_swap_is_rejected() {
+        let (config, owner, relayer) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        // the relayer signs the permit swap; the owner only signed the permit off-chain
+        let permit = ix(
+            &crate::instruction::SwapWithPermit::DISCRIMINATOR,
+            vec![AccountMeta::new(relayer, true), AccountMeta::new_readonly(owner, false), AccountMeta::new(config, false)],
+        );
+        let transaction = [permit, swap(config, relayer)];
+
+        assert!(check_sole_swapper(&transaction, 0, &config, &owner).is_err());
+        assert!(check_sole_swapper(&transaction, 1, &config, &relayer).is_ok());
+
+        let attacker = Pubkey::new_unique();
+        let transaction = [transaction[0].clone(), swap(config, attacker)];
+        assert!(check_sole_swapper(&transaction, 0, &config, &owner).is_err());
+        assert!(check_sole_swapper(&transaction, 1, &config, &attacker).is_err());
+    }
+
+    #[test]
+    fn own_and_unrelated_swaps_pass() {
+        let (config, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let other_pool = swap(Pubkey::new_unique(), Pubkey::new_unique());
+        let crank = ix(
+            &crate::instruction::ExecuteTwammOrder::DISCRIMINATOR,
+            vec![AccountMeta::new_readonly(Pubkey::new_unique(), true), AccountMeta::new(config, false)],
+        );
+
+        assert!(check_sole_swapper(&[swap(config, user), other_pool.clone(), swap(config, user)], 0, &config, &user).is_ok());
+        // a crank protects the order's owner, who does not sign it
+        assert!(check_sole_swapper(&[other_pool, crank.clone()], 1, &config, &user).is_ok());
+        assert!(check_sole_swapper(&[crank.clone(), swap(config, Pubkey::new_unique())], 0, &config, &user).is_err());
+        assert!(check_sole_swapper(&[crank, swap(config, Pubkey::new_unique())], 0, &config, &Pubkey::default()).is_err());
+    }
+
+    #[test]
+    fn guarded_swap_must_be_top_level() {
+        let (config, user) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let wrapper = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![AccountMeta::new(user, true), AccountMeta::new(config, false)]);
+
+        assert!(check_sole_swapper(&[wrapper], 0, &config, &user).is_err());
+        assert!(check_sole_swapper(&[swap(config, user)], 1, &config, &user).is_err());
+    }
+}
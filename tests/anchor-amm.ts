@@ -1,16 +1,59 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { createMint, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { AnchorAmm } from "../target/types/anchor_amm";
 
 describe("anchor-amm", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.AnchorAmm as Program<AnchorAmm>;
+  const payer = (provider.wallet as anchor.Wallet).payer;
+  const treasury = anchor.web3.Keypair.generate().publicKey;
+
+  it("Initializes the global config", async () => {
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    await program.methods
+      .initializeGlobalConfig(treasury, 0, new anchor.BN(0))
+      .accountsPartial({
+        admin: provider.wallet.publicKey,
+        programData,
+      })
+      .rpc();
+  });
 
   it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+    const [mintX, mintY] = (
+      await Promise.all([
+        createMint(provider.connection, payer, payer.publicKey, null, 6),
+        createMint(provider.connection, payer, payer.publicKey, null, 6),
+      ])
+    ).sort((a, b) => a.toBuffer().compare(b.toBuffer()));
+
+    // the first pool lands on registry page 0
+    const [registryPage] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), new anchor.BN(0).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .initialize(30, null, provider.wallet.publicKey, 5000, 5000, null, false, new anchor.BN(0))
+      .accountsPartial({
+        initializer: provider.wallet.publicKey,
+        protocolTreasury: treasury,
+        registryPage,
+        mintX,
+        mintY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgramX: TOKEN_PROGRAM_ID,
+        tokenProgramY: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
     console.log("Your transaction signature", tx);
   });
 });